@@ -0,0 +1,17 @@
+module github.com/phodal/scie-go-example
+
+go 1.15
+
+require (
+	github.com/gin-gonic/gin v1.6.3
+	github.com/stretchr/testify v1.6.1 // indirect
+	golang.org/x/text v0.3.3
+)
+
+require gopkg.in/yaml.v2 v2.3.0
+
+replace golang.org/x/text => golang.org/x/text v0.3.4
+
+replace github.com/phodal/local => ../local
+
+exclude github.com/gin-gonic/gin v1.6.2
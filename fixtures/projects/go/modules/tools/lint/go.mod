@@ -0,0 +1,5 @@
+module github.com/phodal/scie-go-example/tools/lint
+
+go 1.14
+
+require github.com/golangci/golangci-lint v1.31.0
@@ -0,0 +1,4 @@
+package com.phodal;
+
+public class App {
+}
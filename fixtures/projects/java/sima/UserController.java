@@ -0,0 +1,34 @@
+package com.phodal.demo;
+
+import java.util.List;
+import static org.junit.Assert.*;
+
+/**
+ * user controller
+ */
+@RestController
+@RequestMapping(value = "/users", produces = {"a", "b"})
+public class UserController extends BaseController<User> implements Api, Serializable {
+    /** the service */
+    @Autowired
+    private final Map<String, List<User>> cache = new HashMap<>(), backup;
+    int[] ids;
+
+    public UserController() {
+        int local = 0;
+    }
+
+    public List<User> find(@PathVariable("id") final String id, int... rest) throws IOException, SQLException {
+        return null;
+    }
+
+    public <T> T[] convert(T[] input, Map<String, Integer> map) {
+        return input;
+    }
+
+    enum Status { ACTIVE, DISABLED }
+}
+
+interface Api extends Base, Other {
+    String name();
+}
@@ -0,0 +1,7 @@
+package com.phodal;
+
+public class Main {
+    public static void main(String[] args) {
+        System.out.println("Hello, World");
+    }
+}
@@ -0,0 +1,10 @@
+package com.phodal;
+
+import org.junit.Test;
+
+public class MainTest {
+    @Test
+    public void shouldRun() {
+        Main.main(new String[]{});
+    }
+}
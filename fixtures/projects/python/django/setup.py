@@ -0,0 +1,12 @@
+from setuptools import setup, find_packages
+
+setup(
+    name="mysite",
+    version="0.1.0",
+    packages=find_packages(),
+    install_requires=[
+        "Django>=3.1,<3.2",
+        'gunicorn==20.0.4',
+    ],
+    tests_require=["pytest"],
+)
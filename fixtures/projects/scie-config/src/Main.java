@@ -0,0 +1,3 @@
+@EnableDemo
+public class Main {
+}
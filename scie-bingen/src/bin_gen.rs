@@ -1,21 +1,72 @@
+#[cfg(feature = "columnar")]
+use crate::columnar_gen::{ColumnarFormat, ColumnarWriter};
+use crate::frame_file::FrameWriter;
+use crate::sqlite_gen::{ExportSummary, SqliteGen};
+use crate::token_format::TokenWriter;
+use crate::vfs_container::VfsContainer;
+use scie_detector::framework_detector::FrameworkDetector;
 use scie_model::artifact::CodeFile;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 
 pub struct BinGen {}
 
 impl BinGen {
-    /// output by each file for better performance
-    pub fn iter_file(_files: Vec<CodeFile>, _path: &str) {}
+    /// output by each file for better performance, in the format of `FrameWriter`, returns the
+    /// count of files
+    pub fn iter_file<I: IntoIterator<Item = CodeFile>>(
+        files: I,
+        path: &str,
+    ) -> Result<usize, String> {
+        let mut writer = FrameWriter::create(path)?;
+        for file in files {
+            writer.write(&file)?;
+        }
+        writer.finish()
+    }
 
     // output by one file
-    pub fn code_files(files: Vec<CodeFile>, path: &str) {
-        let encoded: Vec<u8> = bincode::serialize(&files).unwrap();
-        let mut file = File::create(path).unwrap();
-        match file.write_all(&*encoded) {
-            Ok(_) => {}
-            Err(_) => {}
-        };
+    pub fn code_files(files: Vec<CodeFile>, path: &str) -> io::Result<()> {
+        BinGen::encode(&files, path)
+    }
+
+    /// output in the compact format of `TokenWriter`, which can be read by other languages, see
+    /// `docs/design/token-format.md`
+    pub fn tokens<I: IntoIterator<Item = CodeFile>>(files: I, path: &str) -> Result<usize, String> {
+        let file = File::create(path).map_err(|err| err.to_string())?;
+        let mut writer = TokenWriter::new(BufWriter::new(file))?;
+        let mut count = 0;
+        for file in files {
+            writer.write_file(&file)?;
+            count += 1;
+        }
+        writer.finish()?;
+        Ok(count)
+    }
+
+    /// output to a `VfsContainer`, which can read the tokens of a file without the others
+    pub fn vfs<T: Serialize>(files: &[CodeFile], detect: &T, path: &str) -> Result<(), String> {
+        let mut container = VfsContainer::create(path)?;
+        for file in files {
+            container.put_file(file)?;
+        }
+        container.put_data("detect", detect)?;
+        container.flush()
+    }
+
+    /// the models which built from code files, like `JavaSima`
+    pub fn encode<T: Serialize>(data: &T, path: &str) -> io::Result<()> {
+        let encoded: Vec<u8> = bincode::serialize(data).map_err(io::Error::other)?;
+        let mut file = File::create(path)?;
+        file.write_all(&encoded)
+    }
+
+    pub fn decode<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Option<T> {
+        let bytes = std::fs::read(path).ok()?;
+        bincode::deserialize(&bytes).ok()
     }
 
     pub fn jsonify(files: Vec<CodeFile>, path: &str) {
@@ -28,6 +79,32 @@ impl BinGen {
             Err(_) => {}
         };
     }
+
+    /// output the tokens as a table in Arrow or Parquet, returns the count of rows
+    #[cfg(feature = "columnar")]
+    pub fn columnar<I: IntoIterator<Item = CodeFile>>(
+        files: I,
+        format: ColumnarFormat,
+        path: &str,
+    ) -> Result<usize, String> {
+        let mut writer = ColumnarWriter::create(path, format)?;
+        for file in files {
+            writer.write(&file)?;
+        }
+        writer.finish()
+    }
+
+    /// output to a SQLite database, which only updates the changed files when it exists
+    pub fn sqlite(
+        files: &[CodeFile],
+        detector: &FrameworkDetector,
+        path: &str,
+    ) -> Result<ExportSummary, String> {
+        let mut gen = SqliteGen::open(path)?;
+        let summary = gen.export_files(files)?;
+        gen.export_detector(detector)?;
+        Ok(summary)
+    }
 }
 
 #[cfg(test)]
@@ -40,8 +117,12 @@ mod tests {
         let code_file = CodeFile {
             path: "path".to_string(),
             name: "name".to_string(),
+            kind: Default::default(),
             elements: vec![],
+            test_ranges: vec![],
+            code_elements: vec![],
+            line_breaks: Default::default(),
         };
-        BinGen::code_files(vec![code_file], "demo.bin");
+        BinGen::code_files(vec![code_file], "demo.bin").unwrap();
     }
 }
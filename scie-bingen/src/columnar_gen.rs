@@ -0,0 +1,263 @@
+use crate::sqlite_gen::SqliteGen;
+use arrow_array::builder::{Int32Builder, ListBuilder, StringBuilder, StringDictionaryBuilder};
+use arrow_array::types::Int32Type;
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use scie_model::artifact::CodeFile;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::sync::Arc;
+
+/// the rows of a record batch, the tokens are flushed by batches to keep the memory low
+pub const BATCH_ROWS: usize = 64 * 1024;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum ColumnarFormat {
+    // Arrow IPC stream, like `pyarrow.ipc.open_stream`
+    Arrow,
+    Parquet,
+}
+
+impl ColumnarFormat {
+    pub fn from_name(name: &str) -> Option<ColumnarFormat> {
+        match name.to_lowercase().as_str() {
+            "arrow" | "ipc" => Some(ColumnarFormat::Arrow),
+            "parquet" => Some(ColumnarFormat::Parquet),
+            _ => None,
+        }
+    }
+}
+
+enum Sink {
+    Arrow(StreamWriter<BufWriter<File>>),
+    Parquet(ArrowWriter<File>),
+}
+
+/// writes the tokens of code files as a table, one row per token, for the analytics in DuckDB
+/// or pandas:
+///
+/// | path | language | line | start | end | value | scopes |
+/// |------|----------|------|-------|-------|-------|--------|
+/// | dictionary | dictionary | int32 | int32 | int32 | utf8 | list<dictionary> |
+pub struct ColumnarWriter {
+    schema: SchemaRef,
+    sink: Sink,
+    path: StringDictionaryBuilder<Int32Type>,
+    language: StringDictionaryBuilder<Int32Type>,
+    line: Int32Builder,
+    start: Int32Builder,
+    end: Int32Builder,
+    value: StringBuilder,
+    scopes: ListBuilder<StringDictionaryBuilder<Int32Type>>,
+    pending: usize,
+    rows: usize,
+}
+
+impl ColumnarWriter {
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        format: ColumnarFormat,
+    ) -> Result<ColumnarWriter, String> {
+        let schema = ColumnarWriter::schema();
+        let file = File::create(path).map_err(|err| err.to_string())?;
+        let sink = match format {
+            ColumnarFormat::Arrow => Sink::Arrow(
+                StreamWriter::try_new(BufWriter::new(file), &schema)
+                    .map_err(|err| err.to_string())?,
+            ),
+            ColumnarFormat::Parquet => {
+                let props = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                Sink::Parquet(
+                    ArrowWriter::try_new(file, schema.clone(), Some(props))
+                        .map_err(|err| err.to_string())?,
+                )
+            }
+        };
+
+        Ok(ColumnarWriter {
+            schema,
+            sink,
+            path: StringDictionaryBuilder::new(),
+            language: StringDictionaryBuilder::new(),
+            line: Int32Builder::new(),
+            start: Int32Builder::new(),
+            end: Int32Builder::new(),
+            value: StringBuilder::new(),
+            scopes: ListBuilder::new(StringDictionaryBuilder::new()),
+            pending: 0,
+            rows: 0,
+        })
+    }
+
+    pub fn schema() -> SchemaRef {
+        let dictionary = DataType::Dictionary(Box::new(DataType::Int32), Box::new(DataType::Utf8));
+        Arc::new(Schema::new(vec![
+            Field::new("path", dictionary.clone(), false),
+            Field::new("language", dictionary.clone(), true),
+            Field::new("line", DataType::Int32, false),
+            Field::new("start", DataType::Int32, false),
+            Field::new("end", DataType::Int32, false),
+            Field::new("value", DataType::Utf8, false),
+            Field::new_list("scopes", Field::new_list_field(dictionary, true), false),
+        ]))
+    }
+
+    pub fn write(&mut self, file: &CodeFile) -> Result<(), String> {
+        let language = SqliteGen::language(file);
+        for element in file.elements.iter() {
+            self.path.append_value(&file.path);
+            match &language {
+                Some(language) => self.language.append_value(language),
+                None => self.language.append_null(),
+            }
+            self.line.append_value(element.line_num);
+            self.start.append_value(element.start_index);
+            self.end.append_value(element.end_index);
+            self.value.append_value(&element.value);
+            for scope in element.scopes.iter() {
+                self.scopes.values().append_value(scope);
+            }
+            self.scopes.append(true);
+
+            self.pending += 1;
+            if self.pending >= BATCH_ROWS {
+                self.flush_batch()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// returns the count of rows
+    pub fn finish(mut self) -> Result<usize, String> {
+        self.flush_batch()?;
+        match self.sink {
+            Sink::Arrow(mut writer) => writer.finish().map_err(|err| err.to_string())?,
+            Sink::Parquet(writer) => {
+                writer.close().map_err(|err| err.to_string())?;
+            }
+        }
+
+        Ok(self.rows)
+    }
+
+    fn flush_batch(&mut self) -> Result<(), String> {
+        if self.pending == 0 {
+            return Ok(());
+        }
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.path.finish()),
+            Arc::new(self.language.finish()),
+            Arc::new(self.line.finish()),
+            Arc::new(self.start.finish()),
+            Arc::new(self.end.finish()),
+            Arc::new(self.value.finish()),
+            Arc::new(self.scopes.finish()),
+        ];
+        let batch =
+            RecordBatch::try_new(self.schema.clone(), columns).map_err(|err| err.to_string())?;
+        match &mut self.sink {
+            Sink::Arrow(writer) => writer.write(&batch).map_err(|err| err.to_string())?,
+            Sink::Parquet(writer) => writer.write(&batch).map_err(|err| err.to_string())?,
+        }
+
+        self.rows += self.pending;
+        self.pending = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::columnar_gen::{ColumnarFormat, ColumnarWriter};
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int32Type;
+    use arrow_array::{Array, RecordBatch, StringArray};
+    use arrow_ipc::reader::StreamReader;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use scie_model::artifact::{CodeFile, TokenElement};
+    use std::fs::File;
+    use std::path::PathBuf;
+
+    fn code_file() -> CodeFile {
+        let mut file = CodeFile::new(PathBuf::from("/demo/src/main.rs"));
+        let scopes = |leaf: &str| vec!["source.rust".to_string(), leaf.to_string()];
+        file.elements = vec![
+            TokenElement::new(1, 0, 2, "fn".to_string(), scopes("keyword.other.fn.rust")),
+            TokenElement::new(1, 2, 3, " ".to_string(), scopes("meta.function.rust")),
+            TokenElement::new(
+                1,
+                3,
+                7,
+                "main".to_string(),
+                scopes("entity.name.function.rust"),
+            ),
+        ];
+        file
+    }
+
+    fn write(name: &str, format: ColumnarFormat) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let mut writer = ColumnarWriter::create(&path, format).unwrap();
+        writer.write(&code_file()).unwrap();
+        writer
+            .write(&CodeFile::new(PathBuf::from("/demo/empty.rs")))
+            .unwrap();
+        assert_eq!(3, writer.finish().unwrap());
+        path
+    }
+
+    fn assert_tokens(batches: Vec<RecordBatch>) {
+        assert_eq!(1, batches.len());
+        let batch = &batches[0];
+        assert_eq!(3, batch.num_rows());
+
+        let language = batch.column_by_name("language").unwrap();
+        let language = language.as_dictionary::<Int32Type>();
+        let names = language
+            .values()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!("rust", names.value(language.keys().value(0) as usize));
+
+        let value = batch.column_by_name("value").unwrap().as_string::<i32>();
+        assert_eq!("main", value.value(2));
+
+        let scopes = batch.column_by_name("scopes").unwrap().as_list::<i32>();
+        let scopes = scopes.value(2);
+        let scopes = scopes.as_dictionary::<Int32Type>();
+        let names = scopes
+            .values()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let leaf = names.value(scopes.keys().value(1) as usize);
+        assert_eq!("entity.name.function.rust", leaf);
+    }
+
+    #[test]
+    fn should_write_arrow_stream() {
+        let path = write("scie_tokens.arrows", ColumnarFormat::Arrow);
+        let reader = StreamReader::try_new(File::open(path).unwrap(), None).unwrap();
+        assert_tokens(reader.collect::<Result<_, _>>().unwrap());
+    }
+
+    #[test]
+    fn should_write_parquet() {
+        let path = write("scie_tokens.parquet", ColumnarFormat::Parquet);
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_tokens(reader.collect::<Result<_, _>>().unwrap());
+    }
+}
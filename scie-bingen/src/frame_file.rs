@@ -0,0 +1,203 @@
+use scie_model::artifact::CodeFile;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"SCIE-FRM";
+// the length of index and the magic
+const FOOTER_SIZE: u64 = 8 + 8;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct FrameIndex {
+    pub path: String,
+    // the offset of frame, which starts with the length
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// writes the code files one by one, each file is a frame which is the length (`u64`, little
+/// endian) and the bincode of file, then the index of frames and the footer:
+///
+/// | frame ... | index | index length | `SCIE-FRM` |
+///
+/// so only the current file is in memory
+pub struct FrameWriter {
+    writer: BufWriter<File>,
+    offset: u64,
+    index: Vec<FrameIndex>,
+}
+
+impl FrameWriter {
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<FrameWriter, String> {
+        let file = File::create(path).map_err(|err| err.to_string())?;
+        Ok(FrameWriter {
+            writer: BufWriter::new(file),
+            offset: 0,
+            index: vec![],
+        })
+    }
+
+    pub fn write(&mut self, code_file: &CodeFile) -> Result<(), String> {
+        let bytes = bincode::serialize(code_file).map_err(|err| err.to_string())?;
+        let length = bytes.len() as u64;
+        self.writer
+            .write_all(&length.to_le_bytes())
+            .and_then(|_| self.writer.write_all(&bytes))
+            .map_err(|err| err.to_string())?;
+
+        self.index.push(FrameIndex {
+            path: code_file.path.clone(),
+            offset: self.offset,
+            length,
+        });
+        self.offset += 8 + length;
+        Ok(())
+    }
+
+    /// writes the footer, returns the count of files
+    pub fn finish(mut self) -> Result<usize, String> {
+        let index = bincode::serialize(&self.index).map_err(|err| err.to_string())?;
+        self.writer
+            .write_all(&index)
+            .and_then(|_| self.writer.write_all(&(index.len() as u64).to_le_bytes()))
+            .and_then(|_| self.writer.write_all(MAGIC))
+            .and_then(|_| self.writer.flush())
+            .map_err(|err| err.to_string())?;
+
+        Ok(self.index.len())
+    }
+}
+
+/// reads the output of `FrameWriter`, by the index or frame by frame
+pub struct FrameReader {
+    reader: BufReader<File>,
+    index: Vec<FrameIndex>,
+    // the end of frames, where the index starts
+    end: u64,
+}
+
+impl FrameReader {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<FrameReader, String> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let mut reader = BufReader::new(file);
+
+        let size = reader
+            .seek(SeekFrom::End(0))
+            .map_err(|err| err.to_string())?;
+        let mut footer = [0; FOOTER_SIZE as usize];
+        if size < FOOTER_SIZE
+            || reader.seek(SeekFrom::End(-(FOOTER_SIZE as i64))).is_err()
+            || reader.read_exact(&mut footer).is_err()
+            || &footer[8..] != MAGIC
+        {
+            return Err(format!("{}: not a frame file", path.display()));
+        }
+
+        let mut length = [0; 8];
+        length.copy_from_slice(&footer[..8]);
+        let length = u64::from_le_bytes(length);
+        if length > size - FOOTER_SIZE {
+            return Err(format!("{}: broken index", path.display()));
+        }
+
+        let end = size - FOOTER_SIZE - length;
+        let mut index = vec![0; length as usize];
+        reader
+            .seek(SeekFrom::Start(end))
+            .and_then(|_| reader.read_exact(&mut index))
+            .map_err(|err| err.to_string())?;
+        let index = bincode::deserialize(&index).map_err(|err| err.to_string())?;
+
+        Ok(FrameReader { reader, index, end })
+    }
+
+    pub fn index(&self) -> &[FrameIndex] {
+        &self.index
+    }
+
+    pub fn get(&mut self, path: &str) -> Result<Option<CodeFile>, String> {
+        let frame = match self.index.iter().find(|frame| frame.path == path) {
+            Some(frame) => frame.clone(),
+            None => return Ok(None),
+        };
+
+        self.read_frame(&frame).map(Some)
+    }
+
+    /// the files by the order of writing, one at a time
+    pub fn files(&mut self) -> impl Iterator<Item = Result<CodeFile, String>> + '_ {
+        let index = self.index.clone();
+        index.into_iter().map(move |frame| self.read_frame(&frame))
+    }
+
+    fn read_frame(&mut self, frame: &FrameIndex) -> Result<CodeFile, String> {
+        // the lengths of a broken index may be larger than the file
+        let frame_end = frame
+            .offset
+            .checked_add(8)
+            .and_then(|start| start.checked_add(frame.length));
+        if !matches!(frame_end, Some(frame_end) if frame_end <= self.end) {
+            return Err(format!("broken frame of {}", frame.path));
+        }
+
+        let mut bytes = vec![0; frame.length as usize];
+        self.reader
+            .seek(SeekFrom::Start(frame.offset + 8))
+            .and_then(|_| self.reader.read_exact(&mut bytes))
+            .map_err(|err| err.to_string())?;
+
+        bincode::deserialize(&bytes).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frame_file::{FrameReader, FrameWriter};
+    use scie_model::artifact::CodeFile;
+    use std::path::PathBuf;
+
+    fn code_file(path: &str) -> CodeFile {
+        CodeFile::new(PathBuf::from(path))
+    }
+
+    #[test]
+    fn should_write_frames_and_index() {
+        let path = std::env::temp_dir().join("scie_frames.bin");
+        let mut writer = FrameWriter::create(&path).unwrap();
+        for name in ["A.java", "B.java", "C.java"] {
+            writer.write(&code_file(name)).unwrap();
+        }
+        assert_eq!(3, writer.finish().unwrap());
+
+        let mut reader = FrameReader::open(&path).unwrap();
+        assert_eq!(3, reader.index().len());
+        assert_eq!("B.java", reader.get("B.java").unwrap().unwrap().path);
+        assert!(reader.get("D.java").unwrap().is_none());
+
+        let paths: Vec<String> = reader.files().map(|file| file.unwrap().path).collect();
+        assert_eq!(vec!["A.java", "B.java", "C.java"], paths);
+    }
+
+    #[test]
+    fn should_reject_frames_out_of_file() {
+        let path = std::env::temp_dir().join("scie_frames_broken.bin");
+        let mut writer = FrameWriter::create(&path).unwrap();
+        writer.write(&code_file("A.java")).unwrap();
+        writer.write(&code_file("B.java")).unwrap();
+        writer.index[0].length = u64::MAX - 4;
+        writer.index[1].length = 1 << 40;
+        writer.finish().unwrap();
+
+        let mut reader = FrameReader::open(&path).unwrap();
+        assert!(reader.get("A.java").is_err());
+        assert!(reader.get("B.java").is_err());
+    }
+
+    #[test]
+    fn should_reject_other_files() {
+        let path = std::env::temp_dir().join("scie_frames_other.bin");
+        std::fs::write(&path, "SCIE-FRM").unwrap();
+        assert!(FrameReader::open(&path).is_err());
+    }
+}
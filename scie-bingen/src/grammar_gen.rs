@@ -46,12 +46,26 @@ impl GrammarGen {
         GrammarGen { grammar_map }
     }
 
+    /// the grammar file of config, which is not bundled, like the extensions dirs of `scie.toml`
+    pub fn read_grammar(config: &LangConfig) -> Result<IRawGrammar, String> {
+        let data = std::fs::read_to_string(&config.path)
+            .map_err(|err| format!("{}: {}", config.path, err))?;
+        serde_json::from_str(&data).map_err(|err| format!("{}: {}", config.path, err))
+    }
+
     pub fn build_grammar_map() -> BTreeMap<String, LangConfig> {
-        let langs = LangExtGen::default();
+        GrammarGen::grammar_map_of(&LangExtGen::default())
+    }
+
+    /// the grammars by the extensions, like the extensions dirs of `scie.toml`
+    pub fn grammar_map_of(langs: &LangExtGen) -> BTreeMap<String, LangConfig> {
         let mut raw_grammar_map: BTreeMap<String, LangConfig> = Default::default();
         for (ext, entry) in langs.ext_map.iter() {
             let lang = entry.name.clone();
-            let tm_grammar = langs.grammar_map[&lang].clone();
+            let tm_grammar = match langs.grammar_map.get(&lang) {
+                Some(grammar) => grammar.clone(),
+                None => continue,
+            };
 
             let mut grammar_path = entry.path.clone();
             grammar_path.push_str(&*tm_grammar.path);
@@ -10,6 +10,9 @@ use std::path::PathBuf;
 pub struct ExtEntry {
     pub name: String,
     pub path: String,
+    // the `language-configuration.json`, relative to `path`
+    #[serde(default)]
+    pub configuration: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -58,6 +61,19 @@ impl LangExtGen {
         };
     }
 
+    /// like `extensions/java/language-configuration.json` for `.java`
+    pub fn language_config_path(&self, ext: &str) -> Option<PathBuf> {
+        let entry = self.ext_map.get(ext)?;
+        let configuration = entry.configuration.as_ref()?;
+        Some(PathBuf::from(&entry.path).join(configuration))
+    }
+
+    /// the languages of `other` replace the ones with the same extensions or names
+    pub fn merge(&mut self, other: LangExtGen) {
+        self.ext_map.extend(other.ext_map);
+        self.grammar_map.extend(other.grammar_map);
+    }
+
     pub fn from_path(ext_path: PathBuf) -> LangExtGen {
         let package_files = ExtFile::walk_dir(ext_path.to_str().unwrap().to_string());
         let mut lang_ext_map = LangExtGen::new();
@@ -67,7 +83,7 @@ impl LangExtGen {
             let pkg: JsonPackage = match serde_json::from_str(&package) {
                 Ok(x) => x,
                 Err(err) => {
-                    println!("{:?}, {:?}", &path, err);
+                    eprintln!("{:?}, {:?}", &path, err);
                     panic!(err)
                 }
             };
@@ -96,6 +112,7 @@ impl LangExtGen {
                     let ext_entry = ExtEntry {
                         name: lang_ext.id.clone(),
                         path,
+                        configuration: lang_ext.configuration.clone(),
                     };
                     lang_ext_map.ext_map.insert(ext, ext_entry);
                 }
@@ -122,6 +139,10 @@ mod tests {
 
         let languages_map = LangExtGen::from_path(ext_path);
         assert_eq!("css", languages_map.ext_map[".css"].name);
+        assert!(languages_map
+            .language_config_path(".css")
+            .unwrap()
+            .ends_with("css/language-configuration.json"));
         assert!(languages_map.ext_map[".css"].path.ends_with("css"));
 
         assert_eq!("source.css", languages_map.grammar_map["css"].scope_name);
@@ -159,6 +180,7 @@ mod tests {
             ExtEntry {
                 name: "css".to_string(),
                 path: "css".to_string(),
+                configuration: None,
             },
         );
 
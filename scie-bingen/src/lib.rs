@@ -7,6 +7,13 @@ extern crate serde;
 extern crate bincode;
 
 pub mod bin_gen;
+#[cfg(feature = "columnar")]
+pub mod columnar_gen;
 pub mod ext_file;
+pub mod frame_file;
 pub mod grammar_gen;
 pub mod language_gen;
+pub mod result_reader;
+pub mod sqlite_gen;
+pub mod token_format;
+pub mod vfs_container;
@@ -0,0 +1,199 @@
+use crate::frame_file::FrameReader;
+use crate::token_format::{TokenReader, TOKEN_MAGIC};
+use crate::vfs_container::VfsContainer;
+use scie_model::artifact::CodeFile;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// the outputs of `BinGen`
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum ResultFormat {
+    // `BinGen::jsonify`
+    Json,
+    // `BinGen::code_files`
+    Bincode,
+    // `BinGen::iter_file`
+    Frames,
+    // `BinGen::tokens`
+    Tokens,
+    // `BinGen::vfs`
+    Vfs,
+}
+
+/// reads the code files back from the outputs of `BinGen`, the format is detected by the content
+pub struct ResultReader {}
+
+impl ResultReader {
+    pub fn detect<P: AsRef<Path>>(path: P) -> Result<ResultFormat, String> {
+        let path = path.as_ref();
+        let mut file = File::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+        let mut head = vec![];
+        file.by_ref()
+            .take(8)
+            .read_to_end(&mut head)
+            .map_err(|err| err.to_string())?;
+        if head.starts_with(TOKEN_MAGIC) {
+            return Ok(ResultFormat::Tokens);
+        }
+        if head.starts_with(b"SCIE-VFS") {
+            return Ok(ResultFormat::Vfs);
+        }
+
+        let mut tail = [0; 8];
+        if file.seek(SeekFrom::End(-8)).is_ok()
+            && file.read_exact(&mut tail).is_ok()
+            && &tail == b"SCIE-FRM"
+        {
+            return Ok(ResultFormat::Frames);
+        }
+
+        match head.iter().find(|byte| !byte.is_ascii_whitespace()) {
+            Some(b'[') => Ok(ResultFormat::Json),
+            _ => Ok(ResultFormat::Bincode),
+        }
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<CodeFile>, String> {
+        let path = path.as_ref();
+        match ResultReader::detect(path)? {
+            ResultFormat::Json => {
+                let file = File::open(path).map_err(|err| err.to_string())?;
+                serde_json::from_reader(BufReader::new(file)).map_err(|err| err.to_string())
+            }
+            ResultFormat::Bincode => {
+                let bytes = std::fs::read(path).map_err(|err| err.to_string())?;
+                bincode::deserialize(&bytes)
+                    .map_err(|_| format!("{}: unknown format", path.display()))
+            }
+            ResultFormat::Frames => FrameReader::open(path)?.files().collect(),
+            ResultFormat::Tokens => {
+                let file = File::open(path).map_err(|err| err.to_string())?;
+                TokenReader::new(BufReader::new(file))?.collect()
+            }
+            ResultFormat::Vfs => {
+                let mut container = VfsContainer::open(path)?;
+                let paths: Vec<String> = container.index().files.keys().cloned().collect();
+                let mut files = vec![];
+                for file_path in paths {
+                    files.extend(container.get_file(&file_path)?);
+                }
+                Ok(files)
+            }
+        }
+    }
+
+    /// the file of path, or which path ends with the path, like `src/main.rs`; only the file is
+    /// read for the formats with index
+    pub fn read_file<P: AsRef<Path>>(path: P, file_path: &str) -> Result<Option<CodeFile>, String> {
+        let path = path.as_ref();
+        let is_match = |candidate: &str| ResultReader::is_path_of(candidate, file_path);
+        match ResultReader::detect(path)? {
+            ResultFormat::Frames => {
+                let mut reader = FrameReader::open(path)?;
+                let found = reader
+                    .index()
+                    .iter()
+                    .find(|frame| is_match(&frame.path))
+                    .map(|frame| frame.path.clone());
+                match found {
+                    Some(found) => reader.get(&found),
+                    None => Ok(None),
+                }
+            }
+            ResultFormat::Vfs => {
+                let mut container = VfsContainer::open(path)?;
+                let found = container
+                    .index()
+                    .files
+                    .keys()
+                    .find(|candidate| is_match(candidate))
+                    .cloned();
+                match found {
+                    Some(found) => container.get_file(&found),
+                    None => Ok(None),
+                }
+            }
+            _ => Ok(ResultReader::read(path)?
+                .into_iter()
+                .find(|file| is_match(&file.path))),
+        }
+    }
+
+    fn is_path_of(candidate: &str, path: &str) -> bool {
+        let candidate = candidate.replace('\\', "/");
+        let path = path.replace('\\', "/");
+        candidate == path || candidate.ends_with(&format!("/{}", path.trim_start_matches("./")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bin_gen::BinGen;
+    use crate::result_reader::{ResultFormat, ResultReader};
+    use scie_model::artifact::{CodeFile, TokenElement};
+    use std::path::PathBuf;
+
+    fn code_files() -> Vec<CodeFile> {
+        ["/demo/src/main.rs", "/demo/src/lib.rs"]
+            .iter()
+            .map(|path| {
+                let mut file = CodeFile::new(PathBuf::from(path));
+                file.elements.push(TokenElement::new(
+                    1,
+                    0,
+                    2,
+                    "fn".to_string(),
+                    vec![
+                        "source.rust".to_string(),
+                        "keyword.other.fn.rust".to_string(),
+                    ],
+                ));
+                file
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_read_all_formats() {
+        let dir = std::env::temp_dir();
+        let outputs = [
+            (ResultFormat::Json, dir.join("scie_result.json")),
+            (ResultFormat::Bincode, dir.join("scie_result.bin")),
+            (ResultFormat::Frames, dir.join("scie_result_frames.bin")),
+            (ResultFormat::Tokens, dir.join("scie_result.tok")),
+            (ResultFormat::Vfs, dir.join("scie_result.vfs")),
+        ];
+
+        let path_of = |format: ResultFormat| {
+            let (_, path) = outputs.iter().find(|(it, _)| *it == format).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+        BinGen::jsonify(code_files(), &path_of(ResultFormat::Json));
+        BinGen::code_files(code_files(), &path_of(ResultFormat::Bincode)).unwrap();
+        BinGen::iter_file(code_files(), &path_of(ResultFormat::Frames)).unwrap();
+        BinGen::tokens(code_files(), &path_of(ResultFormat::Tokens)).unwrap();
+        BinGen::vfs(&code_files(), &"", &path_of(ResultFormat::Vfs)).unwrap();
+
+        for (format, path) in outputs.iter() {
+            assert_eq!(*format, ResultReader::detect(path).unwrap());
+
+            let mut paths: Vec<String> = ResultReader::read(path)
+                .unwrap()
+                .into_iter()
+                .map(|file| file.path)
+                .collect();
+            paths.sort();
+            assert_eq!(vec!["/demo/src/lib.rs", "/demo/src/main.rs"], paths);
+
+            let file = ResultReader::read_file(path, "src/main.rs")
+                .unwrap()
+                .unwrap();
+            assert_eq!("fn", file.elements[0].value);
+            assert!(ResultReader::read_file(path, "main.rs.bak")
+                .unwrap()
+                .is_none());
+        }
+    }
+}
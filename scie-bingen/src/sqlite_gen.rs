@@ -0,0 +1,555 @@
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use scie_detector::framework_detector::FrameworkDetector;
+use scie_model::artifact::CodeFile;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS languages (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE IF NOT EXISTS files (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    language_id INTEGER REFERENCES languages(id),
+    kind TEXT NOT NULL,
+    lines INTEGER NOT NULL,
+    hash TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS scopes (
+    id INTEGER PRIMARY KEY,
+    stack TEXT NOT NULL UNIQUE,
+    leaf TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS tokens (
+    file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    seq INTEGER NOT NULL,
+    line INTEGER NOT NULL,
+    start_index INTEGER NOT NULL,
+    end_index INTEGER NOT NULL,
+    value TEXT NOT NULL,
+    scope_id INTEGER NOT NULL REFERENCES scopes(id),
+    PRIMARY KEY (file_id, seq)
+) WITHOUT ROWID;
+CREATE TABLE IF NOT EXISTS code_elements (
+    id INTEGER PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    parent TEXT,
+    start_line INTEGER NOT NULL,
+    end_line INTEGER NOT NULL,
+    modifiers TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS frameworks (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    ecosystem TEXT NOT NULL,
+    version TEXT NOT NULL,
+    confidence TEXT NOT NULL,
+    evidences TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS modules (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    path TEXT NOT NULL UNIQUE,
+    build_tool TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS dependencies (
+    id INTEGER PRIMARY KEY,
+    group_name TEXT NOT NULL,
+    name TEXT NOT NULL,
+    version TEXT NOT NULL,
+    source TEXT NOT NULL,
+    UNIQUE (group_name, name, version, source)
+);
+CREATE TABLE IF NOT EXISTS module_dependencies (
+    module_id INTEGER NOT NULL REFERENCES modules(id) ON DELETE CASCADE,
+    dependency_id INTEGER NOT NULL REFERENCES dependencies(id),
+    scope TEXT NOT NULL,
+    PRIMARY KEY (module_id, dependency_id, scope)
+);
+CREATE INDEX IF NOT EXISTS files_language ON files(language_id);
+CREATE INDEX IF NOT EXISTS scopes_leaf ON scopes(leaf);
+CREATE INDEX IF NOT EXISTS tokens_scope ON tokens(scope_id);
+CREATE INDEX IF NOT EXISTS tokens_value ON tokens(value);
+CREATE INDEX IF NOT EXISTS code_elements_file ON code_elements(file_id);
+CREATE INDEX IF NOT EXISTS code_elements_name ON code_elements(name);
+CREATE INDEX IF NOT EXISTS code_elements_kind ON code_elements(kind);
+CREATE INDEX IF NOT EXISTS dependencies_name ON dependencies(name);
+CREATE INDEX IF NOT EXISTS module_dependencies_dependency ON module_dependencies(dependency_id);
+";
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct ExportSummary {
+    pub inserted: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+/// exports to SQLite for querying by SQL, the files are updated by the hash of them, so re-runs
+/// only write the changed files. The scope stacks of tokens are interned in `scopes`, which
+/// `stack` is the scopes joined by spaces and `leaf` is the last scope
+pub struct SqliteGen {
+    conn: Connection,
+}
+
+impl SqliteGen {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<SqliteGen, String> {
+        let conn = Connection::open(path).map_err(|err| err.to_string())?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")
+            .and_then(|_| conn.execute_batch(SCHEMA))
+            .map_err(|err| err.to_string())?;
+
+        Ok(SqliteGen { conn })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// the files which are not in `files` are removed, as they are removed from the project
+    pub fn export_files(&mut self, files: &[CodeFile]) -> Result<ExportSummary, String> {
+        let tx = self.conn.transaction().map_err(|err| err.to_string())?;
+        let mut summary = ExportSummary::default();
+        let mut scopes: HashMap<String, i64> = HashMap::new();
+
+        let paths: HashSet<&str> = files.iter().map(|file| file.path.as_str()).collect();
+        let existing: Vec<(i64, String)> = {
+            let mut stmt = tx
+                .prepare("SELECT id, path FROM files")
+                .map_err(|err| err.to_string())?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|err| err.to_string())?;
+            rows.collect::<Result<_, _>>()
+                .map_err(|err| err.to_string())?
+        };
+        for (id, path) in existing {
+            if !paths.contains(path.as_str()) {
+                tx.execute("DELETE FROM files WHERE id = ?1", params![id])
+                    .map_err(|err| err.to_string())?;
+                summary.removed += 1;
+            }
+        }
+
+        for file in files {
+            let hash = SqliteGen::hash(file)?;
+            let row: Option<(i64, String)> = tx
+                .query_row(
+                    "SELECT id, hash FROM files WHERE path = ?1",
+                    params![file.path],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .map_err(|err| err.to_string())?;
+
+            match row {
+                Some((_, old_hash)) if old_hash == hash => summary.unchanged += 1,
+                Some((id, _)) => {
+                    tx.execute("DELETE FROM files WHERE id = ?1", params![id])
+                        .map_err(|err| err.to_string())?;
+                    SqliteGen::insert_file(&tx, file, &hash, &mut scopes)?;
+                    summary.updated += 1;
+                }
+                None => {
+                    SqliteGen::insert_file(&tx, file, &hash, &mut scopes)?;
+                    summary.inserted += 1;
+                }
+            }
+        }
+
+        tx.commit().map_err(|err| err.to_string())?;
+        Ok(summary)
+    }
+
+    /// the frameworks, modules and dependencies, which are replaced on each export
+    pub fn export_detector(&mut self, detector: &FrameworkDetector) -> Result<(), String> {
+        let tx = self.conn.transaction().map_err(|err| err.to_string())?;
+        tx.execute_batch("DELETE FROM frameworks; DELETE FROM modules;")
+            .map_err(|err| err.to_string())?;
+
+        for usage in detector.result.frameworks.iter() {
+            let evidences =
+                serde_json::to_string(&usage.evidences).map_err(|err| err.to_string())?;
+            tx.execute(
+                "INSERT INTO frameworks (name, ecosystem, version, confidence, evidences)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    usage.name,
+                    format!("{:?}", usage.ecosystem),
+                    usage.version,
+                    format!("{:?}", usage.confidence),
+                    evidences
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+        }
+
+        for module in detector.frameworks.iter() {
+            let name = Path::new(&module.path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or(&module.path);
+            tx.execute(
+                "INSERT OR IGNORE INTO modules (name, path, build_tool) VALUES (?1, ?2, ?3)",
+                params![name, module.path, module.name],
+            )
+            .map_err(|err| err.to_string())?;
+            let module_id: i64 = tx
+                .query_row(
+                    "SELECT id FROM modules WHERE path = ?1",
+                    params![module.path],
+                    |row| row.get(0),
+                )
+                .map_err(|err| err.to_string())?;
+
+            for library in module.dependencies.iter() {
+                tx.execute(
+                    "INSERT OR IGNORE INTO dependencies (group_name, name, version, source)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![library.group, library.name, library.version, library.source],
+                )
+                .map_err(|err| err.to_string())?;
+                let dependency_id: i64 = tx
+                    .query_row(
+                        "SELECT id FROM dependencies
+                         WHERE group_name = ?1 AND name = ?2 AND version = ?3 AND source = ?4",
+                        params![library.group, library.name, library.version, library.source],
+                        |row| row.get(0),
+                    )
+                    .map_err(|err| err.to_string())?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO module_dependencies (module_id, dependency_id, scope)
+                     VALUES (?1, ?2, ?3)",
+                    params![module_id, dependency_id, library.scope],
+                )
+                .map_err(|err| err.to_string())?;
+            }
+        }
+
+        tx.execute(
+            "DELETE FROM dependencies
+             WHERE id NOT IN (SELECT dependency_id FROM module_dependencies)",
+            [],
+        )
+        .map_err(|err| err.to_string())?;
+        tx.commit().map_err(|err| err.to_string())
+    }
+
+    fn insert_file(
+        tx: &Transaction,
+        file: &CodeFile,
+        hash: &str,
+        scopes: &mut HashMap<String, i64>,
+    ) -> Result<(), String> {
+        let language_id = match SqliteGen::language(file) {
+            Some(language) => {
+                tx.execute(
+                    "INSERT OR IGNORE INTO languages (name) VALUES (?1)",
+                    params![language],
+                )
+                .map_err(|err| err.to_string())?;
+                let id: i64 = tx
+                    .query_row(
+                        "SELECT id FROM languages WHERE name = ?1",
+                        params![language],
+                        |row| row.get(0),
+                    )
+                    .map_err(|err| err.to_string())?;
+                Some(id)
+            }
+            None => None,
+        };
+
+        let lines = file
+            .elements
+            .last()
+            .map(|element| element.line_num)
+            .unwrap_or(0)
+            .max(file.line_breaks.lines);
+        tx.execute(
+            "INSERT INTO files (path, name, language_id, kind, lines, hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                file.path,
+                file.name,
+                language_id,
+                format!("{:?}", file.kind),
+                lines,
+                hash
+            ],
+        )
+        .map_err(|err| err.to_string())?;
+        let file_id = tx.last_insert_rowid();
+
+        let mut insert_token = tx
+            .prepare_cached(
+                "INSERT INTO tokens (file_id, seq, line, start_index, end_index, value, scope_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )
+            .map_err(|err| err.to_string())?;
+        for (seq, element) in file.elements.iter().enumerate() {
+            let stack = element.scopes.join(" ");
+            let scope_id = match scopes.get(&stack) {
+                Some(id) => *id,
+                None => {
+                    let leaf = element
+                        .scopes
+                        .last()
+                        .map(|scope| scope.as_str())
+                        .unwrap_or("");
+                    tx.execute(
+                        "INSERT OR IGNORE INTO scopes (stack, leaf) VALUES (?1, ?2)",
+                        params![stack, leaf],
+                    )
+                    .map_err(|err| err.to_string())?;
+                    let id: i64 = tx
+                        .query_row(
+                            "SELECT id FROM scopes WHERE stack = ?1",
+                            params![stack],
+                            |row| row.get(0),
+                        )
+                        .map_err(|err| err.to_string())?;
+                    scopes.insert(stack, id);
+                    id
+                }
+            };
+
+            insert_token
+                .execute(params![
+                    file_id,
+                    seq as i64,
+                    element.line_num,
+                    element.start_index,
+                    element.end_index,
+                    element.value,
+                    scope_id
+                ])
+                .map_err(|err| err.to_string())?;
+        }
+
+        for element in file.code_elements.iter() {
+            tx.execute(
+                "INSERT INTO code_elements (file_id, kind, name, parent, start_line, end_line, modifiers)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    file_id,
+                    format!("{:?}", element.kind),
+                    element.name,
+                    element.parent,
+                    element.range.start,
+                    element.range.end,
+                    element.modifiers.join(" ")
+                ],
+            )
+            .map_err(|err| err.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    /// by the root scope of tokens, like `java` of `source.java`, or the extension
+    pub(crate) fn language(file: &CodeFile) -> Option<String> {
+        let root = file
+            .elements
+            .iter()
+            .find_map(|element| element.scopes.first())
+            .map(|scope| {
+                scope
+                    .trim_start_matches("source.")
+                    .trim_start_matches("text.")
+                    .to_string()
+            });
+
+        root.or_else(|| {
+            Path::new(&file.path)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext.to_string())
+        })
+    }
+
+    /// FNV-1a of the bincode of file, which is stable between runs
+    fn hash(file: &CodeFile) -> Result<String, String> {
+        let bytes = bincode::serialize(file).map_err(|err| err.to_string())?;
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        Ok(format!("{:016x}", hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sqlite_gen::SqliteGen;
+    use scie_detector::dependency::base_library::BaseLibrary;
+    use scie_detector::framework_detector::{Framework, FrameworkDetector};
+    use scie_model::artifact::{CodeElement, CodeElementKind, CodeFile, TokenElement};
+    use std::path::PathBuf;
+
+    fn java_file(path: &str, name: &str) -> CodeFile {
+        let mut file = CodeFile::new(PathBuf::from(path));
+        let scopes = |leaf: &str| {
+            vec![
+                "source.java".to_string(),
+                "meta.class.java".to_string(),
+                leaf.to_string(),
+            ]
+        };
+        file.elements = vec![
+            TokenElement::new(
+                1,
+                0,
+                5,
+                "class".to_string(),
+                scopes("storage.modifier.java"),
+            ),
+            TokenElement::new(
+                1,
+                5,
+                6,
+                " ".to_string(),
+                scopes("meta.class.identifier.java"),
+            ),
+            TokenElement::new(
+                1,
+                6,
+                6 + name.len() as i32,
+                name.to_string(),
+                scopes("entity.name.type.class.java"),
+            ),
+        ];
+        file.code_elements = vec![CodeElement::new(CodeElementKind::Class, name, 1)];
+        file
+    }
+
+    fn database(name: &str) -> SqliteGen {
+        let path = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_file(&path);
+        SqliteGen::open(&path).unwrap()
+    }
+
+    fn count(gen: &SqliteGen, sql: &str) -> i64 {
+        gen.connection()
+            .query_row(sql, [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn should_export_normalized_tables() {
+        let mut gen = database("scie_export.db");
+        let files = vec![
+            java_file("/demo/A.java", "A"),
+            java_file("/demo/B.java", "B"),
+        ];
+        let summary = gen.export_files(&files).unwrap();
+        assert_eq!(2, summary.inserted);
+
+        assert_eq!(6, count(&gen, "SELECT count(*) FROM tokens"));
+        assert_eq!(3, count(&gen, "SELECT count(*) FROM scopes"));
+        assert_eq!(
+            1,
+            count(&gen, "SELECT count(*) FROM languages WHERE name = 'java'")
+        );
+
+        let name: String = gen
+            .connection()
+            .query_row(
+                "SELECT t.value FROM tokens t
+                 JOIN scopes s ON s.id = t.scope_id
+                 JOIN files f ON f.id = t.file_id
+                 WHERE s.leaf LIKE 'entity.name.type%' AND f.name = 'B.java'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!("B", name);
+        assert_eq!(
+            2,
+            count(
+                &gen,
+                "SELECT count(*) FROM code_elements WHERE kind = 'Class'"
+            )
+        );
+    }
+
+    #[test]
+    fn should_update_changed_files_only() {
+        let mut gen = database("scie_export_update.db");
+        gen.export_files(&[
+            java_file("/demo/A.java", "A"),
+            java_file("/demo/B.java", "B"),
+        ])
+        .unwrap();
+
+        let summary = gen
+            .export_files(&[
+                java_file("/demo/A.java", "A"),
+                java_file("/demo/B.java", "Bee"),
+            ])
+            .unwrap();
+        assert_eq!(
+            (0, 1, 1, 0),
+            (
+                summary.inserted,
+                summary.updated,
+                summary.unchanged,
+                summary.removed
+            )
+        );
+        assert_eq!(
+            1,
+            count(&gen, "SELECT count(*) FROM tokens WHERE value = 'Bee'")
+        );
+
+        let summary = gen.export_files(&[java_file("/demo/A.java", "A")]).unwrap();
+        assert_eq!(1, summary.removed);
+        assert_eq!(3, count(&gen, "SELECT count(*) FROM tokens"));
+        assert_eq!(1, count(&gen, "SELECT count(*) FROM code_elements"));
+    }
+
+    #[test]
+    fn should_export_module_dependencies() {
+        let library = |name: &str| BaseLibrary {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            group: "org.demo".to_string(),
+            source: "maven".to_string(),
+            scope: "compile".to_string(),
+        };
+        let module = |path: &str, dependencies: Vec<BaseLibrary>| Framework {
+            name: "gradle".to_string(),
+            path: path.to_string(),
+            relative_path: "".to_string(),
+            framework_files: vec![],
+            language: vec!["java".to_string()],
+            content_roots: vec![],
+            dependencies,
+        };
+
+        let mut detector = FrameworkDetector::new();
+        detector.frameworks = vec![
+            module("/demo/app", vec![library("core"), library("web")]),
+            module("/demo/lib", vec![library("core")]),
+        ];
+
+        let mut gen = database("scie_export_modules.db");
+        gen.export_detector(&detector).unwrap();
+        gen.export_detector(&detector).unwrap();
+
+        assert_eq!(2, count(&gen, "SELECT count(*) FROM dependencies"));
+        assert_eq!(3, count(&gen, "SELECT count(*) FROM module_dependencies"));
+        let users = count(
+            &gen,
+            "SELECT count(*) FROM module_dependencies md
+             JOIN dependencies d ON d.id = md.dependency_id WHERE d.name = 'core'",
+        );
+        assert_eq!(2, users);
+    }
+}
@@ -0,0 +1,656 @@
+use scie_model::artifact::{CodeFile, CodeFileKind, LineBreaks, LineRange, TokenElement};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+
+/// the magic and the version, see `docs/design/token-format.md` for the layout
+pub const TOKEN_MAGIC: &[u8; 7] = b"SCIETLV";
+pub const TOKEN_VERSION: u8 = 1;
+
+const TAG_END: u8 = 0x00;
+const TAG_STRINGS: u8 = 0x01;
+const TAG_STACKS: u8 = 0x02;
+const TAG_FILE: u8 = 0x03;
+
+const TAG_PATH: u8 = 0x10;
+const TAG_NAME: u8 = 0x11;
+const TAG_KIND: u8 = 0x12;
+const TAG_TOKENS: u8 = 0x13;
+const TAG_LINE_BREAKS: u8 = 0x14;
+const TAG_TEST_RANGES: u8 = 0x15;
+const TAG_CODE_ELEMENTS: u8 = 0x16;
+
+/// writes the code files in the compact format, the scopes are interned into the string table and
+/// the scope stack table, the new entries of tables are written before the file which uses them,
+/// so files can be written one by one
+pub struct TokenWriter<W: Write> {
+    writer: W,
+    strings: HashMap<String, u64>,
+    // (parent stack, scope string) to the stack id, 0 is the empty stack
+    stacks: HashMap<(u64, u64), u64>,
+}
+
+impl<W: Write> TokenWriter<W> {
+    pub fn new(mut writer: W) -> Result<TokenWriter<W>, String> {
+        writer
+            .write_all(TOKEN_MAGIC)
+            .and_then(|_| writer.write_all(&[TOKEN_VERSION]))
+            .map_err(|err| err.to_string())?;
+
+        Ok(TokenWriter {
+            writer,
+            strings: HashMap::new(),
+            stacks: HashMap::new(),
+        })
+    }
+
+    pub fn write_file(&mut self, code_file: &CodeFile) -> Result<(), String> {
+        let mut new_strings = vec![];
+        let mut new_stacks = vec![];
+        let mut stack_ids = Vec::with_capacity(code_file.elements.len());
+        for element in code_file.elements.iter() {
+            let mut stack = 0;
+            for scope in element.scopes.iter() {
+                let next_string = self.strings.len() as u64;
+                let string = *self.strings.entry(scope.clone()).or_insert_with(|| {
+                    new_strings.push(scope.as_str());
+                    next_string
+                });
+
+                let next_stack = self.stacks.len() as u64 + 1;
+                stack = *self.stacks.entry((stack, string)).or_insert_with(|| {
+                    new_stacks.push((stack, string));
+                    next_stack
+                });
+            }
+            stack_ids.push(stack);
+        }
+
+        if !new_strings.is_empty() {
+            let mut value = vec![];
+            put_varint(&mut value, (self.strings.len() - new_strings.len()) as u64);
+            put_varint(&mut value, new_strings.len() as u64);
+            for string in new_strings {
+                put_str(&mut value, string);
+            }
+            self.write_record(TAG_STRINGS, &value)?;
+        }
+
+        if !new_stacks.is_empty() {
+            let mut value = vec![];
+            put_varint(
+                &mut value,
+                (self.stacks.len() - new_stacks.len()) as u64 + 1,
+            );
+            put_varint(&mut value, new_stacks.len() as u64);
+            for (parent, string) in new_stacks {
+                put_varint(&mut value, parent);
+                put_varint(&mut value, string);
+            }
+            self.write_record(TAG_STACKS, &value)?;
+        }
+
+        let file = TokenWriter::<W>::encode_file(code_file, &stack_ids)?;
+        self.write_record(TAG_FILE, &file)
+    }
+
+    /// writes the end record, which tells readers the output is complete
+    pub fn finish(mut self) -> Result<W, String> {
+        self.write_record(TAG_END, &[])?;
+        self.writer.flush().map_err(|err| err.to_string())?;
+        Ok(self.writer)
+    }
+
+    fn encode_file(code_file: &CodeFile, stack_ids: &[u64]) -> Result<Vec<u8>, String> {
+        let mut file = vec![];
+        put_record(&mut file, TAG_PATH, code_file.path.as_bytes());
+        put_record(&mut file, TAG_NAME, code_file.name.as_bytes());
+        put_record(&mut file, TAG_KIND, &[kind_to_byte(code_file.kind)]);
+
+        let mut tokens = vec![];
+        put_varint(&mut tokens, code_file.elements.len() as u64);
+        let (mut line_num, mut end_index) = (0, 0);
+        for (element, stack) in code_file.elements.iter().zip(stack_ids) {
+            if element.line_num != line_num {
+                end_index = 0;
+            }
+            put_signed(&mut tokens, (element.line_num - line_num) as i64);
+            put_signed(&mut tokens, (element.start_index - end_index) as i64);
+            put_signed(
+                &mut tokens,
+                (element.end_index - element.start_index) as i64,
+            );
+            put_varint(&mut tokens, *stack);
+            put_str(&mut tokens, &element.value);
+
+            line_num = element.line_num;
+            end_index = element.end_index;
+        }
+        put_record(&mut file, TAG_TOKENS, &tokens);
+
+        let breaks = &code_file.line_breaks;
+        let mut value = vec![];
+        put_varint(&mut value, breaks.lines.max(0) as u64);
+        value.push(breaks.final_newline as u8);
+        put_ranges(&mut value, &breaks.crlf);
+        put_record(&mut file, TAG_LINE_BREAKS, &value);
+
+        if !code_file.test_ranges.is_empty() {
+            let mut value = vec![];
+            put_ranges(&mut value, &code_file.test_ranges);
+            put_record(&mut file, TAG_TEST_RANGES, &value);
+        }
+
+        if !code_file.code_elements.is_empty() {
+            let value =
+                serde_json::to_vec(&code_file.code_elements).map_err(|err| err.to_string())?;
+            put_record(&mut file, TAG_CODE_ELEMENTS, &value);
+        }
+
+        Ok(file)
+    }
+
+    fn write_record(&mut self, tag: u8, value: &[u8]) -> Result<(), String> {
+        let mut header = vec![tag];
+        put_varint(&mut header, value.len() as u64);
+        self.writer
+            .write_all(&header)
+            .and_then(|_| self.writer.write_all(value))
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// reads the output of `TokenWriter` file by file, the unknown records are skipped
+pub struct TokenReader<R: Read> {
+    reader: R,
+    strings: Vec<String>,
+    // the parent and the string of stacks, from the stack id 1
+    stacks: Vec<(u64, u64)>,
+    is_end: bool,
+}
+
+impl<R: Read> TokenReader<R> {
+    pub fn new(mut reader: R) -> Result<TokenReader<R>, String> {
+        let mut header = [0; 8];
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| "not a token file".to_string())?;
+        if &header[..7] != TOKEN_MAGIC {
+            return Err("not a token file".to_string());
+        }
+        if header[7] != TOKEN_VERSION {
+            return Err(format!("unsupported version {}", header[7]));
+        }
+
+        Ok(TokenReader {
+            reader,
+            strings: vec![],
+            stacks: vec![],
+            is_end: false,
+        })
+    }
+
+    pub fn next_file(&mut self) -> Result<Option<CodeFile>, String> {
+        while !self.is_end {
+            let mut tag = [0; 1];
+            match self.reader.read_exact(&mut tag) {
+                Ok(_) => {}
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => {
+                    return Err("truncated, the end record is missing".to_string())
+                }
+                Err(err) => return Err(err.to_string()),
+            }
+            let length = read_varint_from(&mut self.reader)?;
+            // the buffer grows by the read bytes, not by the length of a corrupt record
+            let mut value = vec![];
+            self.reader
+                .by_ref()
+                .take(length)
+                .read_to_end(&mut value)
+                .map_err(|err| err.to_string())?;
+            if (value.len() as u64) < length {
+                return Err("truncated, unexpected end of record".to_string());
+            }
+
+            let mut bytes = Bytes::new(&value);
+            match tag[0] {
+                TAG_END => self.is_end = true,
+                TAG_STRINGS => {
+                    TokenReader::<R>::check_first(&mut bytes, self.strings.len() as u64)?;
+                    for _ in 0..bytes.varint()? {
+                        self.strings.push(bytes.string()?);
+                    }
+                }
+                TAG_STACKS => {
+                    TokenReader::<R>::check_first(&mut bytes, self.stacks.len() as u64 + 1)?;
+                    for _ in 0..bytes.varint()? {
+                        let (parent, string) = (bytes.varint()?, bytes.varint()?);
+                        // the parents are written before, so the stacks have no cycles
+                        if parent > self.stacks.len() as u64 {
+                            return Err(format!(
+                                "invalid parent {} of scope stack {}",
+                                parent,
+                                self.stacks.len() + 1
+                            ));
+                        }
+                        self.stacks.push((parent, string));
+                    }
+                }
+                TAG_FILE => return self.decode_file(&mut bytes).map(Some),
+                _ => {}
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// the scopes of the stack, from the root
+    pub fn scopes(&self, stack: u64) -> Result<Vec<String>, String> {
+        let mut scopes = vec![];
+        let mut stack = stack;
+        while stack != 0 {
+            let (parent, string) = self
+                .stacks
+                .get(stack as usize - 1)
+                .ok_or_else(|| format!("unknown scope stack {}", stack))?;
+            let scope = self
+                .strings
+                .get(*string as usize)
+                .ok_or_else(|| format!("unknown string {}", string))?;
+            scopes.push(scope.clone());
+            stack = *parent;
+        }
+
+        scopes.reverse();
+        Ok(scopes)
+    }
+
+    fn check_first(bytes: &mut Bytes, expected: u64) -> Result<(), String> {
+        let first = bytes.varint()?;
+        if first != expected {
+            return Err(format!("table starts at {}, expected {}", first, expected));
+        }
+        Ok(())
+    }
+
+    fn decode_file(&self, bytes: &mut Bytes) -> Result<CodeFile, String> {
+        let mut file = CodeFile {
+            path: "".to_string(),
+            name: "".to_string(),
+            kind: CodeFileKind::Unknown,
+            elements: vec![],
+            test_ranges: vec![],
+            code_elements: vec![],
+            line_breaks: LineBreaks::default(),
+        };
+
+        while !bytes.is_empty() {
+            let tag = bytes.byte()?;
+            let length = bytes.varint()? as usize;
+            let mut value = Bytes::new(bytes.take(length)?);
+            match tag {
+                TAG_PATH => file.path = value.utf8(length)?,
+                TAG_NAME => file.name = value.utf8(length)?,
+                TAG_KIND => file.kind = byte_to_kind(value.byte()?),
+                TAG_TOKENS => file.elements = self.decode_tokens(&mut value)?,
+                TAG_LINE_BREAKS => {
+                    file.line_breaks = LineBreaks {
+                        lines: value.varint()? as i32,
+                        final_newline: value.byte()? != 0,
+                        crlf: value.ranges()?,
+                    }
+                }
+                TAG_TEST_RANGES => file.test_ranges = value.ranges()?,
+                TAG_CODE_ELEMENTS => {
+                    file.code_elements = serde_json::from_slice(value.take(length)?)
+                        .map_err(|err| err.to_string())?
+                }
+                _ => {}
+            }
+        }
+
+        Ok(file)
+    }
+
+    fn decode_tokens(&self, bytes: &mut Bytes) -> Result<Vec<TokenElement>, String> {
+        let count = bytes.varint()?;
+        // a token takes 5 bytes at least
+        if count > bytes.remaining() as u64 / 5 {
+            return Err(format!("{} tokens are out of record", count));
+        }
+        let mut elements = Vec::with_capacity(count as usize);
+        let mut scopes: HashMap<u64, Vec<String>> = HashMap::new();
+        let (mut line_num, mut end_index) = (0, 0);
+        for _ in 0..count {
+            let line_delta = bytes.signed()? as i32;
+            if line_delta != 0 {
+                end_index = 0;
+            }
+            line_num += line_delta;
+            let start_index = end_index + bytes.signed()? as i32;
+            end_index = start_index + bytes.signed()? as i32;
+
+            let stack = bytes.varint()?;
+            if let Entry::Vacant(entry) = scopes.entry(stack) {
+                entry.insert(self.scopes(stack)?);
+            }
+            let value = bytes.string()?;
+            elements.push(TokenElement::new(
+                line_num,
+                start_index,
+                end_index,
+                value,
+                scopes[&stack].clone(),
+            ));
+        }
+
+        Ok(elements)
+    }
+}
+
+impl<R: Read> Iterator for TokenReader<R> {
+    type Item = Result<CodeFile, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_file() {
+            Ok(file) => file.map(Ok),
+            Err(err) => {
+                // stops after errors
+                self.is_end = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+fn kind_to_byte(kind: CodeFileKind) -> u8 {
+    match kind {
+        CodeFileKind::Unknown => 0,
+        CodeFileKind::Source => 1,
+        CodeFileKind::Test => 2,
+        CodeFileKind::Generated => 3,
+        CodeFileKind::Resource => 4,
+    }
+}
+
+fn byte_to_kind(byte: u8) -> CodeFileKind {
+    match byte {
+        1 => CodeFileKind::Source,
+        2 => CodeFileKind::Test,
+        3 => CodeFileKind::Generated,
+        4 => CodeFileKind::Resource,
+        _ => CodeFileKind::Unknown,
+    }
+}
+
+/// unsigned LEB128
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// zigzag, so the small negative numbers are small too
+fn put_signed(out: &mut Vec<u8>, value: i64) {
+    put_varint(out, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+fn put_str(out: &mut Vec<u8>, value: &str) {
+    put_varint(out, value.len() as u64);
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn put_record(out: &mut Vec<u8>, tag: u8, value: &[u8]) {
+    out.push(tag);
+    put_varint(out, value.len() as u64);
+    out.extend_from_slice(value);
+}
+
+fn put_ranges(out: &mut Vec<u8>, ranges: &[LineRange]) {
+    put_varint(out, ranges.len() as u64);
+    for range in ranges {
+        put_varint(out, range.start.max(0) as u64);
+        put_varint(out, range.end.max(0) as u64);
+    }
+}
+
+fn read_varint_from<R: Read>(reader: &mut R) -> Result<u64, String> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0; 1];
+        reader
+            .read_exact(&mut byte)
+            .map_err(|err| err.to_string())?;
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err("varint is too long".to_string())
+}
+
+struct Bytes<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Bytes<'a> {
+    fn new(data: &'a [u8]) -> Bytes<'a> {
+        Bytes { data, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.position)
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], String> {
+        if self.position + length > self.data.len() {
+            return Err("unexpected end of record".to_string());
+        }
+        let data = &self.data[self.position..self.position + length];
+        self.position += length;
+        Ok(data)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err("varint is too long".to_string())
+    }
+
+    fn signed(&mut self) -> Result<i64, String> {
+        let value = self.varint()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    fn utf8(&mut self, length: usize) -> Result<String, String> {
+        String::from_utf8(self.take(length)?.to_vec()).map_err(|err| err.to_string())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let length = self.varint()? as usize;
+        self.utf8(length)
+    }
+
+    fn ranges(&mut self) -> Result<Vec<LineRange>, String> {
+        let mut ranges = vec![];
+        for _ in 0..self.varint()? {
+            ranges.push(LineRange::new(self.varint()? as i32, self.varint()? as i32));
+        }
+        Ok(ranges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::token_format::{
+        put_record, put_varint, TokenReader, TokenWriter, TAG_FILE, TAG_STACKS, TAG_STRINGS,
+        TAG_TOKENS, TOKEN_MAGIC, TOKEN_VERSION,
+    };
+    use scie_model::artifact::{
+        CodeElement, CodeElementKind, CodeFile, CodeFileKind, LineBreaks, LineRange, TokenElement,
+    };
+    use std::path::PathBuf;
+
+    fn java_file(path: &str, lines: i32) -> CodeFile {
+        let mut file = CodeFile::new(PathBuf::from(path));
+        file.kind = CodeFileKind::Source;
+        let root = "source.java".to_string();
+        let class = "meta.class.java".to_string();
+        for line_num in 1..=lines {
+            file.elements.push(TokenElement::new(
+                line_num,
+                4,
+                10,
+                "public".to_string(),
+                vec![
+                    root.clone(),
+                    class.clone(),
+                    "storage.modifier.java".to_string(),
+                ],
+            ));
+            file.elements.push(TokenElement::new(
+                line_num,
+                10,
+                11,
+                " ".to_string(),
+                vec![root.clone(), class.clone()],
+            ));
+            file.elements.push(TokenElement::new(
+                line_num,
+                11,
+                13,
+                "名字".to_string(),
+                vec![root.clone(), class.clone(), "entity.name.java".to_string()],
+            ));
+        }
+        file.elements
+            .push(TokenElement::new(lines + 1, 0, 0, "".to_string(), vec![]));
+
+        file.test_ranges = vec![LineRange::new(2, 3)];
+        file.code_elements = vec![CodeElement::new(CodeElementKind::Class, "Hello", 1)];
+        file.line_breaks = LineBreaks {
+            lines: lines + 1,
+            crlf: vec![LineRange::new(1, 2)],
+            final_newline: true,
+        };
+        file
+    }
+
+    fn encode(files: &[CodeFile]) -> Vec<u8> {
+        let mut writer = TokenWriter::new(vec![]).unwrap();
+        for file in files {
+            writer.write_file(file).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn should_read_what_written() {
+        let files = vec![java_file("A.java", 3), java_file("B.java", 2)];
+        let bytes = encode(&files);
+        assert_eq!(TOKEN_MAGIC, &bytes[..7]);
+
+        let decoded: Vec<CodeFile> = TokenReader::new(bytes.as_slice())
+            .unwrap()
+            .map(|file| file.unwrap())
+            .collect();
+
+        assert_eq!(2, decoded.len());
+        for (file, expected) in decoded.iter().zip(files.iter()) {
+            assert_eq!(expected.path, file.path);
+            assert_eq!(expected.name, file.name);
+            assert_eq!(expected.kind, file.kind);
+            assert_eq!(expected.elements, file.elements);
+            assert_eq!(expected.test_ranges, file.test_ranges);
+            assert_eq!(expected.code_elements, file.code_elements);
+            assert_eq!(expected.line_breaks, file.line_breaks);
+        }
+    }
+
+    #[test]
+    fn should_be_smaller_than_bincode() {
+        let files = vec![java_file("A.java", 100)];
+        let bincode = bincode::serialize(&files).unwrap();
+
+        assert!(encode(&files).len() * 4 < bincode.len());
+    }
+
+    #[test]
+    fn should_reject_truncated_and_other_files() {
+        assert!(TokenReader::new(b"SCIE-FRM".as_ref()).is_err());
+
+        let bytes = encode(&[java_file("A.java", 3)]);
+        // without the end record
+        let results: Vec<_> = TokenReader::new(&bytes[..bytes.len() - 2])
+            .unwrap()
+            .collect();
+        assert_eq!(2, results.len());
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+
+        let mut reader = TokenReader::new(&bytes[..bytes.len() - 3]).unwrap();
+        assert!(reader.next_file().is_err());
+    }
+
+    fn corrupt(tag: u8, value: &[u8]) -> Vec<u8> {
+        let mut bytes = TOKEN_MAGIC.to_vec();
+        bytes.push(TOKEN_VERSION);
+        put_record(&mut bytes, tag, value);
+        bytes
+    }
+
+    #[test]
+    fn should_reject_corrupt_lengths_and_cycles() {
+        // the length of record is larger than the file
+        let mut bytes = TOKEN_MAGIC.to_vec();
+        bytes.push(TOKEN_VERSION);
+        bytes.push(TAG_STRINGS);
+        put_varint(&mut bytes, u64::MAX >> 1);
+        assert!(TokenReader::new(bytes.as_slice())
+            .unwrap()
+            .next_file()
+            .is_err());
+
+        // the count of tokens is larger than the record
+        let mut tokens = vec![];
+        put_varint(&mut tokens, 1 << 40);
+        let mut file = vec![];
+        put_record(&mut file, TAG_TOKENS, &tokens);
+        let bytes = corrupt(TAG_FILE, &file);
+        assert!(TokenReader::new(bytes.as_slice())
+            .unwrap()
+            .next_file()
+            .is_err());
+
+        // the stack 1 is the parent of itself
+        let mut stacks = vec![];
+        for value in [1, 1, 1, 0].iter() {
+            put_varint(&mut stacks, *value);
+        }
+        let bytes = corrupt(TAG_STACKS, &stacks);
+        let error = TokenReader::new(bytes.as_slice())
+            .unwrap()
+            .next_file()
+            .unwrap_err();
+        assert_eq!("invalid parent 1 of scope stack 1", error);
+    }
+}
@@ -0,0 +1,445 @@
+use scie_model::artifact::CodeFile;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 8] = b"SCIE-VFS";
+const VERSION: u32 = 1;
+// magic, version and the offset of index
+const HEADER_SIZE: u64 = 8 + 4 + 8;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct VfsEntry {
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// the file tree of the code files by path, and the other data by name, like the detector result
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct VfsIndex {
+    pub files: BTreeMap<String, VfsEntry>,
+    pub data: BTreeMap<String, VfsEntry>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct VfsNode {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+/// a single file contains the analysis results, see ADR 0013:
+///
+/// | header | entries ... | index |
+///
+/// the header is `SCIE-VFS`, the version and the offset of index (little endian), the code files
+/// are encoded by bincode, and the data by JSON. Updates append the new entries and the new index
+/// after the index of header, then point the header to the new index, so the container is kept
+/// when the update is not finished. The replaced entries and indexes are left until `compact`.
+pub struct VfsContainer {
+    path: PathBuf,
+    file: File,
+    index: VfsIndex,
+    // the end of file, the new entries and the new index are written here
+    end: u64,
+    is_dirty: bool,
+}
+
+impl VfsContainer {
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<VfsContainer, String> {
+        let file = File::create(&path).map_err(|err| err.to_string())?;
+        let mut container = VfsContainer {
+            path: path.as_ref().to_path_buf(),
+            file,
+            index: VfsIndex::default(),
+            end: HEADER_SIZE,
+            is_dirty: true,
+        };
+        container.flush()?;
+        Ok(container)
+    }
+
+    /// opens as read only if the file is not writable
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<VfsContainer, String> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .or_else(|_| File::open(path))
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+
+        let mut header = [0; HEADER_SIZE as usize];
+        file.read_exact(&mut header)
+            .map_err(|_| format!("{}: not a vfs container", path.display()))?;
+        if &header[0..8] != MAGIC {
+            return Err(format!("{}: not a vfs container", path.display()));
+        }
+        let version = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+        if version != VERSION {
+            return Err(format!(
+                "{}: unsupported version {}",
+                path.display(),
+                version
+            ));
+        }
+        let mut offset = [0; 8];
+        offset.copy_from_slice(&header[12..20]);
+        let offset = u64::from_le_bytes(offset);
+
+        // the entries of an unfinished update may follow the index, they are ignored
+        let mut bytes = vec![];
+        let end = file
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_to_end(&mut bytes))
+            .map_err(|err| err.to_string())?;
+        let index = bincode::deserialize(&bytes).map_err(|err| err.to_string())?;
+
+        Ok(VfsContainer {
+            path: path.to_path_buf(),
+            file,
+            index,
+            end: offset + end as u64,
+            is_dirty: false,
+        })
+    }
+
+    pub fn index(&self) -> &VfsIndex {
+        &self.index
+    }
+
+    /// replaces the file of same path
+    pub fn put_file(&mut self, code_file: &CodeFile) -> Result<(), String> {
+        let bytes = bincode::serialize(code_file).map_err(|err| err.to_string())?;
+        let entry = self.append(&bytes)?;
+        self.index.files.insert(code_file.path.clone(), entry);
+        Ok(())
+    }
+
+    /// only reads the entry of the file
+    pub fn get_file(&mut self, path: &str) -> Result<Option<CodeFile>, String> {
+        let entry = match self.index.files.get(path) {
+            Some(entry) => entry.clone(),
+            None => return Ok(None),
+        };
+
+        let bytes = self.read(&entry)?;
+        bincode::deserialize(&bytes)
+            .map(Some)
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn remove_file(&mut self, path: &str) -> bool {
+        let is_removed = self.index.files.remove(path).is_some();
+        self.is_dirty |= is_removed;
+        is_removed
+    }
+
+    pub fn put_data<T: Serialize>(&mut self, name: &str, data: &T) -> Result<(), String> {
+        let bytes = serde_json::to_vec(data).map_err(|err| err.to_string())?;
+        let entry = self.append(&bytes)?;
+        self.index.data.insert(name.to_string(), entry);
+        Ok(())
+    }
+
+    pub fn get_data<T: DeserializeOwned>(&mut self, name: &str) -> Result<Option<T>, String> {
+        let entry = match self.index.data.get(name) {
+            Some(entry) => entry.clone(),
+            None => return Ok(None),
+        };
+
+        let bytes = self.read(&entry)?;
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|err| err.to_string())
+    }
+
+    /// the children of the dir in the file tree, `""` for the root
+    pub fn read_dir(&self, dir: &str) -> Vec<VfsNode> {
+        let dir = dir.trim_end_matches('/');
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", dir)
+        };
+
+        let mut nodes = BTreeSet::new();
+        for path in self.index.files.keys() {
+            if let Some(rest) = path.strip_prefix(&prefix) {
+                match rest.split_once('/') {
+                    Some((name, _)) => nodes.insert((name.to_string(), true)),
+                    None => nodes.insert((rest.to_string(), false)),
+                };
+            }
+        }
+
+        nodes
+            .into_iter()
+            .map(|(name, is_dir)| VfsNode { name, is_dir })
+            .collect()
+    }
+
+    /// writes the index after the entries, then the header which points to it, called when dropped
+    pub fn flush(&mut self) -> Result<(), String> {
+        if !self.is_dirty {
+            return Ok(());
+        }
+
+        let index = bincode::serialize(&self.index).map_err(|err| err.to_string())?;
+        let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&self.end.to_le_bytes());
+
+        // the new index is synced before the header, the header is written at once
+        self.file
+            .seek(SeekFrom::Start(self.end))
+            .and_then(|_| self.file.write_all(&index))
+            .and_then(|_| self.file.sync_data())
+            .and_then(|_| self.file.seek(SeekFrom::Start(0)))
+            .and_then(|_| self.file.write_all(&header))
+            .and_then(|_| self.file.sync_data())
+            .map_err(|err| err.to_string())?;
+
+        self.end += index.len() as u64;
+        self.is_dirty = false;
+        Ok(())
+    }
+
+    /// rewrites the file without the replaced and removed entries, by a new container which
+    /// replaces the file when it's finished
+    pub fn compact(&mut self) -> Result<(), String> {
+        let mut compacted = self.path.clone().into_os_string();
+        compacted.push(".compact");
+        let compacted = PathBuf::from(compacted);
+
+        {
+            let mut container = VfsContainer::create(&compacted)?;
+            let index = self.index.clone();
+            for (path, entry) in index.files.iter() {
+                let entry = container.append(&self.read(entry)?)?;
+                container.index.files.insert(path.clone(), entry);
+            }
+            for (name, entry) in index.data.iter() {
+                let entry = container.append(&self.read(entry)?)?;
+                container.index.data.insert(name.clone(), entry);
+            }
+            container.flush()?;
+        }
+
+        std::fs::rename(&compacted, &self.path).map_err(|err| err.to_string())?;
+        // the replaced file is not flushed
+        self.is_dirty = false;
+        *self = VfsContainer::open(&self.path)?;
+        Ok(())
+    }
+
+    fn append(&mut self, bytes: &[u8]) -> Result<VfsEntry, String> {
+        let entry = VfsEntry {
+            offset: self.end,
+            length: bytes.len() as u64,
+        };
+
+        self.file
+            .seek(SeekFrom::Start(self.end))
+            .and_then(|_| self.file.write_all(bytes))
+            .map_err(|err| err.to_string())?;
+
+        self.end += entry.length;
+        self.is_dirty = true;
+        Ok(entry)
+    }
+
+    fn read(&mut self, entry: &VfsEntry) -> Result<Vec<u8>, String> {
+        // the lengths of a broken index may be larger than the file
+        let entry_end = entry.offset.checked_add(entry.length);
+        if !matches!(entry_end, Some(entry_end) if entry_end <= self.end) {
+            return Err(format!("{}: broken entry", self.path.display()));
+        }
+
+        let mut bytes = vec![0; entry.length as usize];
+        self.file
+            .seek(SeekFrom::Start(entry.offset))
+            .and_then(|_| self.file.read_exact(&mut bytes))
+            .map_err(|err| err.to_string())?;
+        Ok(bytes)
+    }
+}
+
+impl Drop for VfsContainer {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vfs_container::{VfsContainer, VfsNode};
+    use scie_model::artifact::{CodeFile, TokenElement};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+
+    fn code_file(path: &str, value: &str) -> CodeFile {
+        let mut file = CodeFile::new(PathBuf::from(path));
+        file.elements = vec![TokenElement {
+            line_num: 1,
+            start_index: 0,
+            end_index: value.encode_utf16().count() as i32,
+            value: value.to_string(),
+            scopes: vec!["source.java".to_string()],
+            pos: "".to_string(),
+        }];
+        file
+    }
+
+    fn container_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("scie_{}.vfs", name))
+    }
+
+    #[test]
+    fn should_read_single_file_after_reopen() {
+        let path = container_path("reopen");
+        {
+            let mut container = VfsContainer::create(&path).unwrap();
+            container
+                .put_file(&code_file("src/Hello.java", "class"))
+                .unwrap();
+            container
+                .put_file(&code_file("src/World.java", "enum"))
+                .unwrap();
+            let mut detect = BTreeMap::new();
+            detect.insert("build_system", "gradle");
+            container.put_data("detect", &detect).unwrap();
+        }
+
+        let mut container = VfsContainer::open(&path).unwrap();
+        let file = container.get_file("src/World.java").unwrap().unwrap();
+        assert_eq!("enum", file.elements[0].value);
+        assert!(container.get_file("src/Other.java").unwrap().is_none());
+
+        let detect: BTreeMap<String, String> = container.get_data("detect").unwrap().unwrap();
+        assert_eq!("gradle", detect["build_system"]);
+    }
+
+    #[test]
+    fn should_list_file_tree() {
+        let path = container_path("tree");
+        let mut container = VfsContainer::create(&path).unwrap();
+        for file in ["src/main/App.java", "src/test/AppTest.java", "build.gradle"] {
+            container.put_file(&code_file(file, "")).unwrap();
+        }
+
+        let names = |nodes: Vec<VfsNode>| -> Vec<(String, bool)> {
+            nodes
+                .into_iter()
+                .map(|node| (node.name, node.is_dir))
+                .collect()
+        };
+        assert_eq!(
+            vec![
+                ("build.gradle".to_string(), false),
+                ("src".to_string(), true)
+            ],
+            names(container.read_dir(""))
+        );
+        assert_eq!(
+            vec![("main".to_string(), true), ("test".to_string(), true)],
+            names(container.read_dir("src/"))
+        );
+        assert_eq!(
+            vec![("App.java".to_string(), false)],
+            names(container.read_dir("src/main"))
+        );
+    }
+
+    #[test]
+    fn should_replace_entries_and_compact() {
+        let path = container_path("update");
+        {
+            let mut container = VfsContainer::create(&path).unwrap();
+            container.put_file(&code_file("A.java", "class")).unwrap();
+            container
+                .put_file(&code_file("B.java", "interface"))
+                .unwrap();
+        }
+
+        {
+            let mut container = VfsContainer::open(&path).unwrap();
+            container.put_file(&code_file("A.java", "enum")).unwrap();
+            assert!(container.remove_file("B.java"));
+        }
+        let size = std::fs::metadata(&path).unwrap().len();
+
+        let mut container = VfsContainer::open(&path).unwrap();
+        assert_eq!(1, container.index().files.len());
+        assert_eq!(
+            "enum",
+            container.get_file("A.java").unwrap().unwrap().elements[0].value
+        );
+
+        container.compact().unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() < size);
+        assert_eq!(
+            "enum",
+            container.get_file("A.java").unwrap().unwrap().elements[0].value
+        );
+    }
+
+    #[test]
+    fn should_keep_container_when_update_is_not_flushed() {
+        let path = container_path("unflushed");
+        {
+            let mut container = VfsContainer::create(&path).unwrap();
+            container.put_file(&code_file("A.java", "class")).unwrap();
+        }
+
+        // like a crash before `flush`, the new entry is after the index of header
+        let mut container = VfsContainer::open(&path).unwrap();
+        container
+            .put_file(&code_file("B.java", "interface"))
+            .unwrap();
+        std::mem::forget(container);
+
+        let mut container = VfsContainer::open(&path).unwrap();
+        assert_eq!(1, container.index().files.len());
+        assert_eq!(
+            "class",
+            container.get_file("A.java").unwrap().unwrap().elements[0].value
+        );
+
+        // the next update is appended after the unfinished one
+        container.put_file(&code_file("C.java", "enum")).unwrap();
+        container.flush().unwrap();
+        let mut container = VfsContainer::open(&path).unwrap();
+        assert_eq!(2, container.index().files.len());
+        assert_eq!(
+            "enum",
+            container.get_file("C.java").unwrap().unwrap().elements[0].value
+        );
+    }
+
+    #[test]
+    fn should_reject_entries_out_of_file() {
+        let path = container_path("broken");
+        {
+            let mut container = VfsContainer::create(&path).unwrap();
+            container.put_file(&code_file("A.java", "class")).unwrap();
+            container.put_file(&code_file("B.java", "enum")).unwrap();
+            container.index.files.get_mut("A.java").unwrap().length = u64::MAX - 4;
+            container.index.files.get_mut("B.java").unwrap().length = 1 << 40;
+        }
+
+        let mut container = VfsContainer::open(&path).unwrap();
+        assert!(container.get_file("A.java").is_err());
+        assert!(container.get_file("B.java").is_err());
+    }
+
+    #[test]
+    fn should_reject_other_files() {
+        let path = container_path("other");
+        std::fs::write(&path, "{}").unwrap();
+        assert!(VfsContainer::open(&path).is_err());
+    }
+}
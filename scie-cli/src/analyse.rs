@@ -0,0 +1,346 @@
+use crate::options::{CliError, FilterOpts, GlobalOpts, Output};
+use clap::Clap;
+use scie_bingen::bin_gen::BinGen;
+#[cfg(feature = "columnar")]
+use scie_bingen::columnar_gen::ColumnarFormat;
+use scie_bingen::frame_file::FrameWriter;
+use scie_core::analyser::{AnalyseOptions, Analyser};
+use scie_core::navigation::{LsifWriter, SymbolIndex};
+use scie_core::tags::{Ctags, Etags};
+use scie_detector::framework_detector::FrameworkDetector;
+use scie_model::artifact::CodeFile;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// analyse the tokens and the code elements of files, to the outputs of `--format`
+#[derive(Clap)]
+pub struct AnalyseCommand {
+    /// the dir or file to analyse
+    #[clap(default_value = ".")]
+    pub path: String,
+    /// by default the format of `scie.toml` or `bin`, `parquet` and `arrow` need the `columnar`
+    /// feature
+    #[clap(long, possible_values = &AnalyseFormat::NAMES)]
+    pub format: Option<String>,
+    /// by the `scie.toml` or the format, like `scie.bin` or `tags`, and `-` for stdout of the text
+    /// formats
+    #[clap(short, long)]
+    pub output: Option<String>,
+    /// output the `bin` file by file, without keeping all files in memory
+    #[clap(long)]
+    pub stream: bool,
+    #[clap(flatten)]
+    pub filter: FilterOpts,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AnalyseFormat {
+    Bin,
+    Json,
+    /// the compact binary format for other languages, see `docs/design/token-format.md`
+    Tokens,
+    /// a container which can read the tokens by file
+    Vfs,
+    /// a SQLite database which updates the changed files only on re-runs
+    Sqlite,
+    Parquet,
+    Arrow,
+    /// the symbols of Universal Ctags
+    Ctags,
+    /// the symbols of Emacs
+    Etags,
+    /// the definitions and references of names, in LSIF JSON lines
+    Lsif,
+}
+
+impl AnalyseFormat {
+    pub const NAMES: [&'static str; 10] = [
+        "bin", "json", "tokens", "vfs", "sqlite", "parquet", "arrow", "ctags", "etags", "lsif",
+    ];
+
+    pub fn from_name(name: &str) -> Option<AnalyseFormat> {
+        match name {
+            "bin" => Some(AnalyseFormat::Bin),
+            "json" => Some(AnalyseFormat::Json),
+            "tokens" => Some(AnalyseFormat::Tokens),
+            "vfs" => Some(AnalyseFormat::Vfs),
+            "sqlite" => Some(AnalyseFormat::Sqlite),
+            "parquet" => Some(AnalyseFormat::Parquet),
+            "arrow" => Some(AnalyseFormat::Arrow),
+            "ctags" => Some(AnalyseFormat::Ctags),
+            "etags" => Some(AnalyseFormat::Etags),
+            "lsif" => Some(AnalyseFormat::Lsif),
+            _ => None,
+        }
+    }
+
+    pub fn default_output(&self) -> &'static str {
+        match self {
+            AnalyseFormat::Bin => "scie.bin",
+            AnalyseFormat::Json => "scie.json",
+            AnalyseFormat::Tokens => "scie.tlv",
+            AnalyseFormat::Vfs => "scie.vfs",
+            AnalyseFormat::Sqlite => "scie.db",
+            AnalyseFormat::Parquet => "scie.parquet",
+            AnalyseFormat::Arrow => "scie.arrows",
+            AnalyseFormat::Ctags => "tags",
+            AnalyseFormat::Etags => "TAGS",
+            AnalyseFormat::Lsif => "dump.lsif",
+        }
+    }
+
+    /// the formats can be written to stdout
+    pub fn is_text(&self) -> bool {
+        matches!(
+            self,
+            AnalyseFormat::Json | AnalyseFormat::Ctags | AnalyseFormat::Etags | AnalyseFormat::Lsif
+        )
+    }
+}
+
+pub struct Analyse {}
+
+impl Analyse {
+    /// returns the exit code, the progress is printed with `-v`, and the debug logs with `-vv`
+    pub fn run(command: &AnalyseCommand, globals: &GlobalOpts) -> i32 {
+        CliError::exit(Analyse::execute(command, globals))
+    }
+
+    fn execute(command: &AnalyseCommand, globals: &GlobalOpts) -> Result<(), CliError> {
+        let verbose = globals.verbose;
+        let input = Output::input(&command.path)?;
+        let config = globals.config(&input)?;
+        let options = config.options(&command.filter)?;
+
+        // the flags are preferred to `scie.toml`
+        let name = config
+            .format(command.format.as_deref())
+            .unwrap_or_else(|| "bin".to_string());
+        let format = AnalyseFormat::from_name(&name)
+            .ok_or_else(|| CliError::Usage(format!("unknown format: {}", name)))?;
+        let output = config
+            .output(command.output.as_deref())
+            .unwrap_or_else(|| format.default_output().to_string());
+        if Output::is_stdout(Some(&output)) && !format.is_text() {
+            return Err(CliError::Usage(format!(
+                "the {} format can't be written to stdout",
+                name
+            )));
+        }
+        if command.stream && format != AnalyseFormat::Bin {
+            return Err(CliError::Usage(
+                "only the bin format can be streamed".to_string(),
+            ));
+        }
+
+        if !Output::is_stdout(Some(&output)) {
+            // like the `build/scie.json` of `scie.toml`
+            if let Some(parent) = Path::new(&output).parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|err| format!("failed to write {}: {}", output, err))?;
+            }
+            Output::clear_screen();
+            println!("Using input file: {}", command.path);
+        }
+
+        if command.stream {
+            let mut writer = FrameWriter::create(&output)?;
+            let mut error = None;
+            Analyser::analyse_with(
+                &input,
+                &mut HashMap::new(),
+                &options,
+                verbose > 1,
+                verbose > 0,
+                |file| {
+                    if let Err(err) = writer.write(&file) {
+                        error.get_or_insert(err);
+                    }
+                },
+            );
+            return match error.or_else(|| writer.finish().err()) {
+                Some(err) => Err(CliError::Failure(format!(
+                    "failed to write {}: {}",
+                    output, err
+                ))),
+                None => Ok(()),
+            };
+        }
+
+        let (detector, files) = Analyse::files(&input, &options, verbose);
+        Analyse::write(files, &detector, format, &output)
+    }
+
+    /// analyse the files of options, returns the detector and the analysed files
+    pub fn files(
+        input: &Path,
+        options: &AnalyseOptions,
+        verbose: i32,
+    ) -> (FrameworkDetector, Vec<CodeFile>) {
+        let mut files = vec![];
+        let detector = Analyser::analyse_with(
+            &input.to_path_buf(),
+            &mut HashMap::new(),
+            options,
+            verbose > 1,
+            verbose > 0,
+            |file| files.push(file),
+        );
+
+        (detector, files)
+    }
+
+    fn write(
+        files: Vec<CodeFile>,
+        detector: &FrameworkDetector,
+        format: AnalyseFormat,
+        output: &str,
+    ) -> Result<(), CliError> {
+        // the paths of symbols are relative to the current dir, where the editors find the tags
+        let current = std::env::current_dir().unwrap_or_default();
+        match format {
+            AnalyseFormat::Bin => BinGen::code_files(files, output)
+                .map_err(|err| CliError::Failure(format!("failed to write {}: {}", output, err)))?,
+            AnalyseFormat::Json => {
+                let json = serde_json::to_string_pretty(&files).map_err(|err| err.to_string())?;
+                Output::write(Some(output), &json)?
+            }
+            AnalyseFormat::Tokens => {
+                BinGen::tokens(files, output)
+                    .map_err(|err| format!("failed to write {}: {}", output, err))?;
+            }
+            AnalyseFormat::Vfs => BinGen::vfs(&files, &detector.result, output)
+                .map_err(|err| format!("failed to write {}: {}", output, err))?,
+            AnalyseFormat::Sqlite => {
+                let summary = BinGen::sqlite(&files, detector, output)
+                    .map_err(|err| format!("failed to write {}: {}", output, err))?;
+                println!(
+                    "{}: {} inserted, {} updated, {} unchanged, {} removed",
+                    output, summary.inserted, summary.updated, summary.unchanged, summary.removed
+                );
+            }
+            AnalyseFormat::Parquet | AnalyseFormat::Arrow => {
+                Analyse::write_columnar(files, format, output)?
+            }
+            AnalyseFormat::Ctags => {
+                Output::write(Some(output), &Ctags::generate(&files, &current))?
+            }
+            AnalyseFormat::Etags => {
+                Output::write(Some(output), &Etags::generate(&files, &current))?
+            }
+            AnalyseFormat::Lsif => {
+                let index = SymbolIndex::build(&files);
+                let mut writer = Output::writer(Some(output))?;
+                LsifWriter::write(&files, &index, &current, &mut writer)?;
+                writer
+                    .flush()
+                    .map_err(|err| format!("failed to write {}: {}", output, err))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "columnar")]
+    fn write_columnar(
+        files: Vec<CodeFile>,
+        format: AnalyseFormat,
+        output: &str,
+    ) -> Result<(), CliError> {
+        let columnar = match format {
+            AnalyseFormat::Arrow => ColumnarFormat::Arrow,
+            _ => ColumnarFormat::Parquet,
+        };
+        let rows = BinGen::columnar(files, columnar, output)
+            .map_err(|err| format!("failed to write {}: {}", output, err))?;
+        println!("{}: {} tokens", output, rows);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "columnar"))]
+    fn write_columnar(
+        _files: Vec<CodeFile>,
+        format: AnalyseFormat,
+        _output: &str,
+    ) -> Result<(), CliError> {
+        Err(CliError::Usage(format!(
+            "the format {:?} needs the `columnar` feature, like `cargo build -p scie-cli --features columnar`",
+            format
+        ).to_lowercase()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyse::{Analyse, AnalyseCommand};
+    use crate::options::{FilterOpts, GlobalOpts, EXIT_FAILURE, EXIT_USAGE};
+    use scie_bingen::result_reader::{ResultFormat, ResultReader};
+    use std::path::PathBuf;
+
+    fn command(format: &str, output: &str) -> AnalyseCommand {
+        AnalyseCommand {
+            path: PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .parent()
+                .unwrap()
+                .join("fixtures")
+                .join("projects")
+                .join("java")
+                .join("spring")
+                .display()
+                .to_string(),
+            format: Some(format.to_string()),
+            output: Some(output.to_string()),
+            stream: false,
+            filter: FilterOpts::default(),
+        }
+    }
+
+    #[test]
+    fn should_analyse_files_of_language_to_output() {
+        let output = std::env::temp_dir().join("scie-cli-analyse.json");
+        let mut command = command("json", &output.display().to_string());
+        command.filter.language = vec!["java".to_string()];
+
+        assert_eq!(0, Analyse::run(&command, &GlobalOpts::default()));
+        let files = ResultReader::read(&output).unwrap();
+        assert_eq!(1, files.len());
+        assert_eq!("Application.java", files[0].name);
+        let _ = std::fs::remove_file(output);
+    }
+
+    #[test]
+    fn should_analyse_to_compact_tokens() {
+        let output = std::env::temp_dir().join("scie-cli-analyse.tlv");
+        let command = command("tokens", &output.display().to_string());
+
+        assert_eq!(0, Analyse::run(&command, &GlobalOpts::default()));
+        assert_eq!(ResultFormat::Tokens, ResultReader::detect(&output).unwrap());
+        let files = ResultReader::read(&output).unwrap();
+        assert!(files.iter().any(|file| file.name == "Application.java"));
+        let _ = std::fs::remove_file(output);
+    }
+
+    #[test]
+    fn should_exit_with_usage_code_for_binary_stdout() {
+        let globals = GlobalOpts::default();
+        assert_eq!(EXIT_USAGE, Analyse::run(&command("bin", "-"), &globals));
+
+        let mut streamed = command("json", "scie.json");
+        streamed.stream = true;
+        assert_eq!(EXIT_USAGE, Analyse::run(&streamed, &globals));
+    }
+
+    #[test]
+    fn should_exit_with_failure_code_for_unwritable_output() {
+        // the missing parents are created, but a dir can't be written as a file
+        let output = std::env::temp_dir().join("scie-cli-output-dir");
+        std::fs::create_dir_all(&output).unwrap();
+
+        let command = command("bin", &output.display().to_string());
+        assert_eq!(EXIT_FAILURE, Analyse::run(&command, &GlobalOpts::default()));
+        assert!(output.is_dir());
+        let _ = std::fs::remove_dir(output);
+    }
+}
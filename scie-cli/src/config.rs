@@ -0,0 +1,238 @@
+use crate::options::{CliError, FilterOpts};
+use scie_core::analyser::AnalyseOptions;
+use scie_detector::rules::FrameworkRules;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const CONFIG_FILE: &str = "scie.toml";
+
+/// the output of `scie-cli analyse`, the flags of command are preferred
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct OutputConfig {
+    pub format: Option<String>,
+    pub path: Option<String>,
+}
+
+/// the language of the files match the glob, like `Jenkinsfile` to `groovy`
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct LanguageOverride {
+    pub glob: String,
+    pub language: String,
+}
+
+/// the project config `scie.toml`, the paths and the globs are relative to the dir of it
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct ScieConfig {
+    // the dirs of VS Code extensions, with the bundled ones
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    #[serde(default)]
+    pub output: OutputConfig,
+    // the first matched one is used
+    #[serde(default)]
+    pub overrides: Vec<LanguageOverride>,
+    // the seconds of tokenizing a file by grammar, by languages
+    #[serde(default)]
+    pub budgets: BTreeMap<String, f64>,
+    // the framework rules like `.scie/frameworks.toml`, in `[[detector.framework]]`
+    #[serde(default)]
+    pub detector: FrameworkRules,
+    #[serde(skip)]
+    pub root: Option<PathBuf>,
+}
+
+impl ScieConfig {
+    pub fn parse(code: &str) -> Result<ScieConfig, String> {
+        toml::from_str(code).map_err(|err| err.to_string())
+    }
+
+    /// the file of `--config`, or the `scie.toml` in the analysed dir or its parents, the default
+    /// config without both
+    pub fn load(config: Option<&str>, input: &Path) -> Result<ScieConfig, CliError> {
+        let path = match config {
+            Some(config) => PathBuf::from(config),
+            None => match ScieConfig::discover(input) {
+                Some(path) => path,
+                None => return Ok(ScieConfig::default()),
+            },
+        };
+
+        let code = fs::read_to_string(&path).map_err(|err| {
+            CliError::Usage(format!("invalid config {}: {}", path.display(), err))
+        })?;
+        let mut config = ScieConfig::parse(&code).map_err(|err| {
+            CliError::Usage(format!("invalid config {}: {}", path.display(), err))
+        })?;
+        let root = path.parent().unwrap_or_else(|| Path::new(""));
+        config.root = Some(root.canonicalize().unwrap_or_else(|_| root.to_path_buf()));
+
+        Ok(config)
+    }
+
+    /// walk up from the analysed dir, or the dir of the analysed file
+    pub fn discover(input: &Path) -> Option<PathBuf> {
+        let input = input.canonicalize().ok()?;
+        let dir = if input.is_file() {
+            input.parent()?
+        } else {
+            input.as_path()
+        };
+
+        dir.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// the options of config, with the filters of command
+    pub fn options(&self, filter: &FilterOpts) -> Result<AnalyseOptions, CliError> {
+        let dirs: Vec<PathBuf> = self.extensions.iter().map(|dir| self.path(dir)).collect();
+        let overrides: Vec<(String, String)> = self
+            .overrides
+            .iter()
+            .map(|it| (it.glob.clone(), it.language.clone()))
+            .collect();
+        let mut budgets = vec![];
+        for (language, seconds) in self.budgets.iter() {
+            // like the negative, NaN or too large seconds
+            let budget = Duration::try_from_secs_f64(*seconds).map_err(|err| {
+                CliError::Usage(format!(
+                    "invalid budget of {}: {}, {}",
+                    language, seconds, err
+                ))
+            })?;
+            budgets.push((language.clone(), budget));
+        }
+
+        let mut options = AnalyseOptions::default()
+            .with_extensions(&dirs)
+            .and_then(|options| options.with_globs(&filter.include, &filter.exclude))
+            .map_err(CliError::Usage)?
+            .with_languages(&filter.language)
+            .with_budgets(&budgets);
+        if let Some(root) = &self.root {
+            options = options
+                .with_project(root, &self.ignore, &overrides)
+                .map_err(CliError::Usage)?;
+        }
+
+        let options = options.resolve().map_err(CliError::Usage)?;
+        Ok(options.with_rules(self.detector.clone()))
+    }
+
+    /// the format of `analyse`, by the flag or the config
+    pub fn format(&self, flag: Option<&str>) -> Option<String> {
+        flag.map(|it| it.to_string())
+            .or_else(|| self.output.format.clone())
+    }
+
+    /// the output of `analyse`, by the flag or the config
+    pub fn output(&self, flag: Option<&str>) -> Option<String> {
+        match flag {
+            Some(flag) => Some(flag.to_string()),
+            None => self
+                .output
+                .path
+                .as_ref()
+                .map(|path| self.path(path).display().to_string()),
+        }
+    }
+
+    fn path(&self, path: &str) -> PathBuf {
+        match &self.root {
+            Some(root) => root.join(path),
+            None => PathBuf::from(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{ScieConfig, CONFIG_FILE};
+    use crate::options::{CliError, FilterOpts};
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_discover_config_in_parents() {
+        let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("scie-config");
+        let config = ScieConfig::load(None, &project.join("src").join("Main.java")).unwrap();
+        let root = project.canonicalize().unwrap();
+        assert_eq!(Some(root.clone()), config.root);
+
+        assert_eq!(vec!["generated/**"], config.ignore);
+        assert_eq!(Some(5.0), config.budgets.get("java").copied());
+        assert_eq!("Demo", config.detector.rules[0].name);
+
+        // the flags of command are preferred
+        assert_eq!(Some("json".to_string()), config.format(None));
+        assert_eq!(Some("bin".to_string()), config.format(Some("bin")));
+        assert_eq!(
+            Some(root.join("build").join("scie.json").display().to_string()),
+            config.output(None)
+        );
+        assert_eq!(Some("-".to_string()), config.output(Some("-")));
+
+        let options = config.options(&FilterOpts::default()).unwrap();
+        assert_eq!(
+            Some(".groovy"),
+            options.language_of(&project, &project.join("Jenkinsfile"))
+        );
+        assert!(!options.is_included(&project, &project.join("generated").join("Api.java")));
+    }
+
+    #[test]
+    fn should_report_invalid_config_as_usage() {
+        assert!(ScieConfig::parse("extensions = 1").is_err());
+        assert!(ScieConfig::load(Some("nothing.toml"), &PathBuf::from(".")).is_err());
+
+        let mut config = ScieConfig::default();
+        for seconds in [-1.0, f64::NAN, 1e20] {
+            config.budgets.insert("java".to_string(), seconds);
+            assert!(matches!(
+                config.options(&FilterOpts::default()),
+                Err(CliError::Usage(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn should_report_invalid_grammar_of_extensions_as_usage() {
+        let project = std::env::temp_dir().join("scie-config-grammar");
+        let extension = project.join("extensions").join("broken");
+        fs::create_dir_all(extension.join("syntaxes")).unwrap();
+        fs::write(
+            extension.join("package.json"),
+            r#"{"name": "broken", "contributes": {
+                "languages": [{"id": "broken", "extensions": [".broken"]}],
+                "grammars": [{"language": "broken", "scopeName": "source.broken",
+                    "path": "./syntaxes/broken.tmLanguage.json"}]}}"#,
+        )
+        .unwrap();
+        fs::write(
+            extension.join("syntaxes").join("broken.tmLanguage.json"),
+            "{",
+        )
+        .unwrap();
+        fs::write(project.join(CONFIG_FILE), r#"extensions = ["extensions"]"#).unwrap();
+
+        let config = ScieConfig::load(None, &project).unwrap();
+        match config.options(&FilterOpts::default()) {
+            Err(CliError::Usage(message)) => {
+                assert!(message.contains("broken.tmLanguage.json"), "{}", message)
+            }
+            _ => panic!("the broken grammar is a usage error"),
+        }
+
+        fs::remove_dir_all(&project).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+use crate::options::{CliError, FilterOpts, GlobalOpts, Output};
+use clap::Clap;
+use scie_core::analyser::AnalyseOptions;
+use scie_detector::framework_detector::{Framework, FrameworkDetector};
+use scie_infra::format_output::FormatOutput;
+use serde_json::json;
+use std::path::Path;
+
+/// list the dependencies which declared by the modules, like `build.gradle` and `go.mod`
+#[derive(Clap)]
+pub struct DepsCommand {
+    /// the dir of project
+    #[clap(default_value = ".")]
+    pub path: String,
+    #[clap(long, default_value = "table", possible_values = &["table", "json"])]
+    pub format: String,
+    /// the file to write, or stdout
+    #[clap(short, long)]
+    pub output: Option<String>,
+    /// the globs match the relative paths of modules, and the languages match the languages of
+    /// modules
+    #[clap(flatten)]
+    pub filter: FilterOpts,
+}
+
+pub struct Deps {}
+
+impl Deps {
+    pub fn run(command: &DepsCommand, globals: &GlobalOpts) -> i32 {
+        CliError::exit(Deps::execute(command, globals))
+    }
+
+    fn execute(command: &DepsCommand, globals: &GlobalOpts) -> Result<(), CliError> {
+        let input = Output::input(&command.path)?;
+        let options = globals.config(&input)?.options(&command.filter)?;
+
+        let detector = options.detect(&input);
+        let modules = Deps::modules(&detector, &options);
+
+        let content = match command.format.as_str() {
+            "json" => {
+                let modules: Vec<_> = modules
+                    .iter()
+                    .map(|module| {
+                        json!({
+                            "module": Deps::module_name(module),
+                            "build_tool": module.name,
+                            "language": module.language,
+                            "dependencies": module.dependencies,
+                        })
+                    })
+                    .collect();
+                serde_json::to_string_pretty(&modules).map_err(|err| err.to_string())?
+            }
+            _ => Deps::table(&modules),
+        };
+        Output::write(command.output.as_deref(), &content)
+    }
+
+    /// the modules of the languages, which relative paths are included
+    pub fn modules<'a>(
+        detector: &'a FrameworkDetector,
+        options: &AnalyseOptions,
+    ) -> Vec<&'a Framework> {
+        detector
+            .frameworks
+            .iter()
+            .filter(|module| options.is_included(Path::new(""), Path::new(&module.relative_path)))
+            .filter(|module| {
+                module.language.is_empty()
+                    || module
+                        .language
+                        .iter()
+                        .any(|language| options.has_language(language))
+            })
+            .collect()
+    }
+
+    fn table(modules: &[&Framework]) -> String {
+        let mut rows = vec![];
+        for module in modules {
+            for library in module.dependencies.iter() {
+                rows.push(vec![
+                    Deps::module_name(module),
+                    module.name.clone(),
+                    library.group.clone(),
+                    library.name.clone(),
+                    library.version.clone(),
+                    library.scope.clone(),
+                ]);
+            }
+        }
+
+        let headers = ["module", "build tool", "group", "name", "version", "scope"];
+        FormatOutput::table(&headers, &rows)
+    }
+
+    // the root module has an empty relative path
+    fn module_name(module: &Framework) -> String {
+        if module.relative_path.is_empty() {
+            ".".to_string()
+        } else {
+            module.relative_path.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deps::Deps;
+    use scie_core::analyser::AnalyseOptions;
+    use scie_detector::framework_detector::FrameworkDetector;
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_list_dependencies_of_modules() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("java")
+            .join("spring");
+        let mut detector = FrameworkDetector::new();
+        detector.run(path.display().to_string());
+
+        let modules = Deps::modules(&detector, &AnalyseOptions::default());
+        assert_eq!(1, modules.len());
+        assert!(!modules[0].dependencies.is_empty());
+        assert!(Deps::table(&modules).contains("spring-boot-starter"));
+
+        let options = AnalyseOptions::new(&[], &[], &["go".to_string()]).unwrap();
+        assert!(Deps::modules(&detector, &options).is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::options::{CliError, FilterOpts, GlobalOpts, Output};
+use clap::Clap;
+use scie_core::analyser::AnalyseOptions;
+use scie_detector::detect_result::DetectResult;
+use scie_infra::format_output::FormatOutput;
+use std::path::Path;
+
+/// detect the build systems, ecosystems and frameworks, without analysing the files
+#[derive(Clap)]
+pub struct DetectCommand {
+    /// the dir to detect
+    #[clap(default_value = ".")]
+    pub path: String,
+    #[clap(long, default_value = "json", possible_values = &["json", "table"])]
+    pub format: String,
+    /// the file to write, or stdout
+    #[clap(short, long)]
+    pub output: Option<String>,
+    /// the globs match the evidences, like `**/build.gradle`, and the languages match the
+    /// ecosystems
+    #[clap(flatten)]
+    pub filter: FilterOpts,
+}
+
+pub struct Detect {}
+
+impl Detect {
+    pub fn run(command: &DetectCommand, globals: &GlobalOpts) -> i32 {
+        CliError::exit(Detect::execute(command, globals))
+    }
+
+    fn execute(command: &DetectCommand, globals: &GlobalOpts) -> Result<(), CliError> {
+        let input = Output::input(&command.path)?;
+        let options = globals.config(&input)?.options(&command.filter)?;
+
+        let detector = options.detect(&input);
+        let result = Detect::filter(detector.result, &options);
+
+        let content = match command.format.as_str() {
+            "table" => Detect::table(&result),
+            _ => serde_json::to_string_pretty(&result).map_err(|err| err.to_string())?,
+        };
+        Output::write(command.output.as_deref(), &content)
+    }
+
+    /// the detections of the languages, which evidences are included
+    pub fn filter(mut result: DetectResult, options: &AnalyseOptions) -> DetectResult {
+        let root = Path::new("");
+        result.detections.retain_mut(|detection| {
+            let is_empty = detection.evidences.is_empty();
+            detection
+                .evidences
+                .retain(|evidence| options.is_included(root, Path::new(evidence)));
+            options.has_ecosystem(&detection.ecosystem)
+                && (is_empty || !detection.evidences.is_empty())
+        });
+        result
+            .frameworks
+            .retain(|framework| options.has_ecosystem(&framework.ecosystem));
+
+        result
+    }
+
+    fn table(result: &DetectResult) -> String {
+        let rows: Vec<Vec<String>> = result
+            .detections
+            .iter()
+            .map(|detection| {
+                vec![
+                    format!("{:?}", detection.ecosystem),
+                    detection
+                        .build_system
+                        .map(|it| format!("{:?}", it))
+                        .unwrap_or_default(),
+                    format!("{:?}", detection.confidence),
+                    detection.evidences.join(", "),
+                ]
+            })
+            .collect();
+        let headers = ["ecosystem", "build system", "confidence", "evidences"];
+        let mut output = FormatOutput::table(&headers, &rows);
+
+        if !result.frameworks.is_empty() {
+            let rows: Vec<Vec<String>> = result
+                .frameworks
+                .iter()
+                .map(|framework| {
+                    vec![
+                        framework.name.clone(),
+                        format!("{:?}", framework.ecosystem),
+                        framework.version.clone(),
+                        format!("{:?}", framework.confidence),
+                    ]
+                })
+                .collect();
+            let headers = ["framework", "ecosystem", "version", "confidence"];
+            output.push_str("\n\n");
+            output.push_str(&FormatOutput::table(&headers, &rows));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::detect::Detect;
+    use scie_core::analyser::AnalyseOptions;
+    use scie_detector::detect_result::Ecosystem;
+    use scie_detector::framework_detector::FrameworkDetector;
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_filter_detections_by_languages_and_evidences() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("java")
+            .join("spring");
+        let mut detector = FrameworkDetector::new();
+        detector.run(path.display().to_string());
+        assert!(!detector.result.detections.is_empty());
+
+        let options = AnalyseOptions::new(&[], &[], &["go".to_string()]).unwrap();
+        let result = Detect::filter(detector.result.clone(), &options);
+        assert!(result.detections.is_empty());
+        assert!(result.frameworks.is_empty());
+
+        let options = AnalyseOptions::new(&[], &["*.gradle".to_string()], &[]).unwrap();
+        let result = Detect::filter(detector.result.clone(), &options);
+        assert!(result.detections.iter().all(
+            |detection| detection.ecosystem != Ecosystem::Jvm || detection.evidences.is_empty()
+        ));
+
+        let result = Detect::filter(detector.result.clone(), &AnalyseOptions::default());
+        assert_eq!(detector.result, result);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::options::{CliError, FilterOpts, GlobalOpts, Output};
+use clap::Clap;
+use scie_bingen::grammar_gen::{GrammarGen, LangConfig};
+use scie_core::analyser::AnalyseOptions;
+use scie_infra::format_output::FormatOutput;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// list the grammars by the extensions, the bundled ones and the ones of `scie.toml`
+#[derive(Clap)]
+pub struct GrammarCommand {
+    #[clap(long, default_value = "table", possible_values = &["table", "json"])]
+    pub format: String,
+    /// the file to write, or stdout
+    #[clap(short, long)]
+    pub output: Option<String>,
+    /// the globs match the paths of grammar files, like `**/java/**`
+    #[clap(flatten)]
+    pub filter: FilterOpts,
+}
+
+pub struct GrammarList {}
+
+impl GrammarList {
+    pub fn run(command: &GrammarCommand, globals: &GlobalOpts) -> i32 {
+        CliError::exit(GrammarList::execute(command, globals))
+    }
+
+    fn execute(command: &GrammarCommand, globals: &GlobalOpts) -> Result<(), CliError> {
+        let options = globals.config(Path::new("."))?.options(&command.filter)?;
+        // the bundled grammars, with the grammars of the extensions dirs in `scie.toml`
+        let grammars = GrammarGen::grammar_map_of(&options.lang_ext());
+        let grammars = GrammarList::filter(grammars, &options);
+
+        let content = match command.format.as_str() {
+            "json" => serde_json::to_string_pretty(&grammars).map_err(|err| err.to_string())?,
+            _ => {
+                let rows: Vec<Vec<String>> = grammars
+                    .iter()
+                    .map(|(ext, config)| {
+                        vec![
+                            ext.clone(),
+                            config.lang.clone(),
+                            config.scope_name.clone(),
+                            config.path.clone(),
+                        ]
+                    })
+                    .collect();
+                FormatOutput::table(&["ext", "language", "scope", "path"], &rows)
+            }
+        };
+        Output::write(command.output.as_deref(), &content)
+    }
+
+    /// the grammars of the languages, which paths are included
+    pub fn filter(
+        grammars: BTreeMap<String, LangConfig>,
+        options: &AnalyseOptions,
+    ) -> BTreeMap<String, LangConfig> {
+        grammars
+            .into_iter()
+            .filter(|(ext, config)| options.has_ext(ext) || options.has_language(&config.lang))
+            .filter(|(_, config)| options.is_included(Path::new(""), Path::new(&config.path)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grammar::GrammarList;
+    use scie_bingen::grammar_gen::GrammarGen;
+    use scie_core::analyser::AnalyseOptions;
+
+    #[test]
+    fn should_filter_grammars_by_languages() {
+        let options = AnalyseOptions::new(&[], &[], &["go".to_string()]).unwrap();
+        let grammars = GrammarList::filter(GrammarGen::build_grammar_map(), &options);
+
+        assert_eq!(vec![".go"], grammars.keys().collect::<Vec<_>>());
+        assert_eq!("source.go", grammars[".go"].scope_name);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::analyse::Analyse;
+use crate::options::{CliError, FilterOpts, GlobalOpts, Output};
+use clap::Clap;
+use scie_infra::finder::Finder;
+use scie_model::artifact::{CodeFile, TokenElement};
+use std::collections::BTreeMap;
+
+/// the scope prefixes and the style names, the more specific ones are first
+const THEME: [(&str, &str); 14] = [
+    ("comment", "comment"),
+    ("string", "string"),
+    ("constant.numeric", "number"),
+    ("constant", "constant"),
+    ("keyword", "keyword"),
+    ("storage", "keyword"),
+    ("entity.name.function", "function"),
+    ("support.function", "function"),
+    ("entity.name.type", "type"),
+    ("entity.name.class", "type"),
+    ("support.type", "type"),
+    ("support.class", "type"),
+    ("entity.name.tag", "tag"),
+    ("variable", "variable"),
+];
+
+/// print the files with the colors of tokens, for the terminals or the pages
+#[derive(Clap)]
+pub struct HighlightCommand {
+    /// the file or dir to highlight
+    #[clap(default_value = ".")]
+    pub path: String,
+    /// `ansi` for terminals, `html` for pages
+    #[clap(long, default_value = "ansi", possible_values = &["ansi", "html"])]
+    pub format: String,
+    /// the file to write, or stdout
+    #[clap(short, long)]
+    pub output: Option<String>,
+    #[clap(flatten)]
+    pub filter: FilterOpts,
+}
+
+pub struct Highlight {}
+
+impl Highlight {
+    pub fn run(command: &HighlightCommand, globals: &GlobalOpts) -> i32 {
+        CliError::exit(Highlight::execute(command, globals))
+    }
+
+    fn execute(command: &HighlightCommand, globals: &GlobalOpts) -> Result<(), CliError> {
+        let input = Output::input(&command.path)?;
+        let options = globals.config(&input)?.options(&command.filter)?;
+        let (_, files) = Analyse::files(&input, &options, globals.verbose);
+
+        let is_html = command.format == "html";
+        let mut output = String::new();
+        if is_html {
+            output.push_str(&Highlight::html_style());
+        }
+        for file in files.iter() {
+            let code = Finder::read_code(&file.path.clone().into())?;
+            if is_html {
+                output.push_str(&format!(
+                    "<h3>{}</h3>\n<pre class=\"scie\"><code>{}</code></pre>\n",
+                    Highlight::escape(&file.path),
+                    Highlight::highlight(file, &code, Highlight::html)
+                ));
+            } else {
+                output.push_str(&format!(
+                    "\x1B[1m{}\x1B[0m\n{}\n",
+                    file.path,
+                    Highlight::highlight(file, &code, Highlight::ansi)
+                ));
+            }
+        }
+
+        Output::write(command.output.as_deref(), &output)
+    }
+
+    /// the style of token by the most specific scope of theme, like `comment`
+    pub fn style(token: &TokenElement) -> Option<&'static str> {
+        token.scopes.iter().rev().find_map(|scope| {
+            THEME
+                .iter()
+                .find(|(prefix, _)| {
+                    scope == prefix
+                        || (scope.starts_with(prefix) && scope[prefix.len()..].starts_with('.'))
+                })
+                .map(|(_, style)| *style)
+        })
+    }
+
+    /// the lines of code with the styled tokens, the texts out of tokens are kept as is
+    pub fn highlight(
+        file: &CodeFile,
+        code: &str,
+        paint: fn(&str, Option<&str>) -> String,
+    ) -> String {
+        let mut tokens: BTreeMap<i32, Vec<&TokenElement>> = BTreeMap::new();
+        for token in file.elements.iter() {
+            tokens.entry(token.line_num).or_default().push(token);
+        }
+
+        let mut lines = vec![];
+        for (index, line) in code.lines().enumerate() {
+            let chars: Vec<char> = line.chars().collect();
+            let mut text = String::new();
+            let mut offset = 0;
+            for token in tokens.get(&(index as i32 + 1)).into_iter().flatten() {
+                let start = (token.start_index as usize).clamp(offset, chars.len());
+                let end = (token.end_index as usize).clamp(start, chars.len());
+                let gap: String = chars[offset..start].iter().collect();
+                let value: String = chars[start..end].iter().collect();
+                text.push_str(&paint(&gap, None));
+                text.push_str(&paint(&value, Highlight::style(token)));
+                offset = end;
+            }
+            let rest: String = chars[offset..].iter().collect();
+            text.push_str(&paint(&rest, None));
+            lines.push(text);
+        }
+
+        lines.join("\n")
+    }
+
+    fn ansi(text: &str, style: Option<&str>) -> String {
+        let color = match style {
+            Some("comment") => "90",
+            Some("string") => "32",
+            Some("number") | Some("constant") => "36",
+            Some("keyword") => "35",
+            Some("function") => "34",
+            Some("type") | Some("tag") => "33",
+            Some("variable") => "31",
+            _ => return text.to_string(),
+        };
+        if text.is_empty() {
+            return String::new();
+        }
+
+        format!("\x1B[{}m{}\x1B[0m", color, text)
+    }
+
+    fn html(text: &str, style: Option<&str>) -> String {
+        match style {
+            Some(style) if !text.is_empty() => format!(
+                "<span class=\"scie-{}\">{}</span>",
+                style,
+                Highlight::escape(text)
+            ),
+            _ => Highlight::escape(text),
+        }
+    }
+
+    fn html_style() -> String {
+        let rules = [
+            ("comment", "#6a737d"),
+            ("string", "#22863a"),
+            ("number", "#005cc5"),
+            ("constant", "#005cc5"),
+            ("keyword", "#d73a49"),
+            ("function", "#6f42c1"),
+            ("type", "#e36209"),
+            ("tag", "#22863a"),
+            ("variable", "#24292e"),
+        ];
+        let rules: Vec<String> = rules
+            .iter()
+            .map(|(style, color)| format!(".scie-{} {{ color: {}; }}", style, color))
+            .collect();
+
+        format!("<style>\n{}\n</style>\n", rules.join("\n"))
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::highlight::Highlight;
+    use scie_model::artifact::{CodeFile, TokenElement};
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_highlight_tokens_by_scopes() {
+        let code = "int a = 1; // a < b";
+        let mut file = CodeFile::new(PathBuf::from("Demo.java"));
+        let scopes = |scope: &str| vec!["source.java".to_string(), scope.to_string()];
+        file.elements = vec![
+            TokenElement::new(
+                1,
+                0,
+                3,
+                "int".to_string(),
+                scopes("storage.type.primitive.java"),
+            ),
+            TokenElement::new(
+                1,
+                8,
+                9,
+                "1".to_string(),
+                scopes("constant.numeric.decimal.java"),
+            ),
+            TokenElement::new(
+                1,
+                11,
+                19,
+                "// a < b".to_string(),
+                scopes("comment.line.double-slash.java"),
+            ),
+        ];
+
+        assert_eq!(Some("keyword"), Highlight::style(&file.elements[0]));
+        assert_eq!(Some("number"), Highlight::style(&file.elements[1]));
+
+        let html = Highlight::highlight(&file, code, Highlight::html);
+        assert_eq!(
+            "<span class=\"scie-keyword\">int</span> a = <span class=\"scie-number\">1</span>; <span class=\"scie-comment\">// a &lt; b</span>",
+            html
+        );
+        let ansi = Highlight::highlight(&file, code, Highlight::ansi);
+        assert!(ansi.starts_with("\x1B[35mint\x1B[0m a = \x1B[36m1"));
+    }
+}
@@ -0,0 +1,323 @@
+use crate::options::{CliError, Output, EXIT_USAGE};
+use clap::Clap;
+use scie_bingen::result_reader::ResultReader;
+use scie_core::sima::TokenSelector;
+use scie_infra::format_output::FormatOutput;
+use scie_model::artifact::{CodeFile, TokenElement};
+use std::collections::BTreeMap;
+
+/// inspect the outputs of analysis, like `scie.bin` and `scie.json`
+#[derive(Clap)]
+pub enum InspectCommand {
+    /// list the files with the count of tokens and lines
+    List { result: String },
+    /// print the tokens of a file, or all files
+    Tokens {
+        result: String,
+        /// the path of file, or the end of it, like `src/main.rs`
+        file: Option<String>,
+        /// the scope selector, like `meta.import storage.modifier`
+        #[clap(long)]
+        scope: Option<String>,
+    },
+    /// print the added, removed and changed files of two results
+    Diff { left: String, right: String },
+}
+
+pub struct Inspect {}
+
+impl Inspect {
+    /// returns the exit code, `1` for the differences, and `2` for the errors of `diff` like the
+    /// `diff` command, the others exit by `CliError`
+    pub fn run(command: &InspectCommand) -> i32 {
+        let output = match command {
+            InspectCommand::List { result } => Inspect::list(result),
+            InspectCommand::Tokens {
+                result,
+                file,
+                scope,
+            } => Inspect::tokens(result, file.as_deref(), scope.as_deref()),
+            InspectCommand::Diff { left, right } => Inspect::diff(left, right),
+        };
+
+        match output {
+            Ok((output, code)) => {
+                println!("{}", output);
+                code
+            }
+            Err(err) => {
+                let code = match command {
+                    InspectCommand::Diff { .. } => EXIT_USAGE,
+                    _ => err.exit_code(),
+                };
+                CliError::exit(Err(err));
+                code
+            }
+        }
+    }
+
+    /// the missing results are usage errors, and the unreadable ones are failures
+    fn read(result: &str) -> Result<Vec<CodeFile>, CliError> {
+        Output::input(result)?;
+        ResultReader::read(result)
+            .map_err(|err| CliError::Failure(format!("failed to read {}: {}", result, err)))
+    }
+
+    fn list(result: &str) -> Result<(String, i32), CliError> {
+        let rows: Vec<Vec<String>> = Inspect::read(result)?
+            .iter()
+            .map(|file| {
+                vec![
+                    file.path.clone(),
+                    format!("{:?}", file.kind),
+                    file.elements.len().to_string(),
+                    Inspect::lines(file).to_string(),
+                ]
+            })
+            .collect();
+
+        let headers = ["path", "kind", "tokens", "lines"];
+        Ok((FormatOutput::table(&headers, &rows), 0))
+    }
+
+    fn tokens(
+        result: &str,
+        file: Option<&str>,
+        scope: Option<&str>,
+    ) -> Result<(String, i32), CliError> {
+        let files = match file {
+            Some(file) => {
+                Output::input(result)?;
+                let code_file = ResultReader::read_file(result, file).map_err(|err| {
+                    CliError::Failure(format!("failed to read {}: {}", result, err))
+                })?;
+                match code_file {
+                    Some(code_file) => vec![code_file],
+                    None => return Err(CliError::Usage(format!("{}: no file {}", result, file))),
+                }
+            }
+            None => Inspect::read(result)?,
+        };
+
+        let selector = scope.map(TokenSelector::new);
+        let mut lines = vec![];
+        for code_file in files.iter() {
+            for element in code_file.elements.iter() {
+                if let Some(selector) = &selector {
+                    if !selector.matches(element) {
+                        continue;
+                    }
+                }
+
+                let line = Inspect::token_line(element);
+                if files.len() > 1 {
+                    lines.push(format!("{}:{}", code_file.path, line));
+                } else {
+                    lines.push(line);
+                }
+            }
+        }
+
+        Ok((lines.join("\n"), 0))
+    }
+
+    fn diff(left: &str, right: &str) -> Result<(String, i32), CliError> {
+        let by_path = |files: Vec<CodeFile>| -> BTreeMap<String, CodeFile> {
+            files
+                .into_iter()
+                .map(|file| (file.path.clone(), file))
+                .collect()
+        };
+        let left = by_path(Inspect::read(left)?);
+        let right = by_path(Inspect::read(right)?);
+
+        let mut lines = vec![];
+        for (path, file) in left.iter() {
+            match right.get(path) {
+                None => lines.push(format!("- {}", path)),
+                Some(other) => {
+                    if let Some(change) = Inspect::diff_tokens(file, other) {
+                        lines.push(format!("~ {} {}", path, change));
+                    }
+                }
+            }
+        }
+        for path in right.keys() {
+            if !left.contains_key(path) {
+                lines.push(format!("+ {}", path));
+            }
+        }
+
+        if lines.is_empty() {
+            Ok(("no differences".to_string(), 0))
+        } else {
+            Ok((lines.join("\n"), 1))
+        }
+    }
+
+    /// the count of changed tokens and the first of them
+    fn diff_tokens(left: &CodeFile, right: &CodeFile) -> Option<String> {
+        let is_same = |a: &TokenElement, b: &TokenElement| {
+            a.line_num == b.line_num
+                && a.start_index == b.start_index
+                && a.end_index == b.end_index
+                && a.value == b.value
+                && a.scopes == b.scopes
+        };
+
+        let mut changed = left.elements.len().abs_diff(right.elements.len());
+        let mut first = None;
+        for (index, (a, b)) in left.elements.iter().zip(right.elements.iter()).enumerate() {
+            if !is_same(a, b) {
+                changed += 1;
+                first.get_or_insert(index);
+            }
+        }
+        if changed == 0 {
+            return None;
+        }
+
+        let index = first.unwrap_or_else(|| left.elements.len().min(right.elements.len()));
+        let token = |file: &CodeFile| {
+            file.elements
+                .get(index)
+                .map(Inspect::token_line)
+                .unwrap_or_else(|| "<none>".to_string())
+        };
+        Some(format!(
+            "({} tokens changed)\n    < {}\n    > {}",
+            changed,
+            token(left),
+            token(right)
+        ))
+    }
+
+    pub fn token_line(element: &TokenElement) -> String {
+        format!(
+            "{}:{}-{} {:?} {}",
+            element.line_num,
+            element.start_index,
+            element.end_index,
+            element.value,
+            element.scopes.join(" | ")
+        )
+    }
+
+    fn lines(file: &CodeFile) -> i32 {
+        file.line_breaks.lines.max(
+            file.elements
+                .last()
+                .map(|element| element.line_num)
+                .unwrap_or(0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::inspect::{Inspect, InspectCommand};
+    use crate::options::{CliError, EXIT_FAILURE, EXIT_USAGE};
+    use scie_bingen::bin_gen::BinGen;
+    use scie_model::artifact::{CodeFile, TokenElement};
+    use std::path::PathBuf;
+
+    fn code_file(path: &str, tokens: &[(&str, &str)]) -> CodeFile {
+        let mut file = CodeFile::new(PathBuf::from(path));
+        let mut start = 0;
+        for (value, scope) in tokens {
+            let end = start + value.len() as i32;
+            file.elements.push(TokenElement::new(
+                1,
+                start,
+                end,
+                value.to_string(),
+                vec!["source.java".to_string(), scope.to_string()],
+            ));
+            start = end;
+        }
+        file
+    }
+
+    fn write(name: &str, files: Vec<CodeFile>) -> String {
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_str().unwrap();
+        BinGen::jsonify(files, path);
+        path.to_string()
+    }
+
+    #[test]
+    fn should_filter_tokens_by_scope() {
+        let result = write(
+            "scie_inspect_tokens.json",
+            vec![code_file(
+                "/demo/Hello.java",
+                &[
+                    ("import", "keyword.other.import.java"),
+                    ("List", "storage.modifier.import.java"),
+                ],
+            )],
+        );
+
+        let (output, code) =
+            Inspect::tokens(&result, Some("Hello.java"), Some("storage.modifier")).unwrap();
+        assert_eq!(0, code);
+        assert_eq!(
+            "1:6-10 \"List\" source.java | storage.modifier.import.java",
+            output
+        );
+        assert!(matches!(
+            Inspect::tokens(&result, Some("World.java"), None),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn should_diff_results() {
+        let left = write(
+            "scie_inspect_left.json",
+            vec![
+                code_file("A.java", &[("class", "storage.type.java")]),
+                code_file("B.java", &[]),
+            ],
+        );
+        let right = write(
+            "scie_inspect_right.json",
+            vec![
+                code_file("A.java", &[("enum", "storage.type.java")]),
+                code_file("C.java", &[]),
+            ],
+        );
+
+        let (output, code) = Inspect::diff(&left, &right).unwrap();
+        assert_eq!(1, code);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!("~ A.java (1 tokens changed)", lines[0]);
+        assert_eq!("- B.java", lines[3]);
+        assert_eq!("+ C.java", lines[4]);
+
+        assert_eq!(0, Inspect::diff(&left, &left).unwrap().1);
+    }
+
+    #[test]
+    fn should_exit_with_error_codes_apart_from_differences() {
+        let result = write("scie_inspect_codes.json", vec![code_file("A.java", &[])]);
+        let broken = std::env::temp_dir().join("scie_inspect_broken.json");
+        std::fs::write(&broken, "[{").unwrap();
+        let broken = broken.display().to_string();
+        let missing = "scie_inspect_missing.json".to_string();
+
+        let list = |result: &str| InspectCommand::List {
+            result: result.to_string(),
+        };
+        assert_eq!(EXIT_USAGE, Inspect::run(&list(&missing)));
+        assert_eq!(EXIT_FAILURE, Inspect::run(&list(&broken)));
+
+        let diff = |right: &str| InspectCommand::Diff {
+            left: result.clone(),
+            right: right.to_string(),
+        };
+        assert_eq!(0, Inspect::run(&diff(&result)));
+        assert_eq!(EXIT_USAGE, Inspect::run(&diff(&missing)));
+        assert_eq!(EXIT_USAGE, Inspect::run(&diff(&broken)));
+    }
+}
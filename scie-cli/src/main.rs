@@ -1,45 +1,86 @@
-use crate::validate::Validate;
+use crate::analyse::{Analyse, AnalyseCommand};
+use crate::deps::{Deps, DepsCommand};
+use crate::detect::{Detect, DetectCommand};
+use crate::grammar::{GrammarCommand, GrammarList};
+use crate::highlight::{Highlight, HighlightCommand};
+use crate::inspect::{Inspect, InspectCommand};
+use crate::options::{GlobalOpts, EXIT_USAGE};
+use crate::serve::{Serve, ServeCommand};
+use crate::stats::{Stats, StatsCommand};
+use crate::tokenize::{Tokenize, TokenizeCommand};
 use clap::Clap;
-use scie_bingen::bin_gen::BinGen;
-use scie_core::analyser::Analyser;
-use std::path::Path;
 
+pub mod analyse;
+pub mod config;
+pub mod deps;
+pub mod detect;
+pub mod grammar;
+pub mod highlight;
+pub mod inspect;
+pub mod options;
+pub mod serve;
+pub mod stats;
+pub mod tokenize;
 pub mod validate;
 
 #[derive(Clap)]
 #[clap(version = "0.1", author = "Phodal HUANG<h@phodal.com>")]
 struct Opts {
-    #[clap(short, long, default_value = "default.conf")]
-    _config: String,
-    #[clap(long)]
-    json: bool,
-    #[clap(long, short)]
-    debug: bool,
-    #[clap(short, long, default_value = ".")]
-    path: String,
-    #[clap(short, long, parse(from_occurrences))]
-    _verbose: i32,
+    #[clap(flatten)]
+    globals: GlobalOpts,
+    #[clap(subcommand)]
+    command: SubCommand,
 }
 
-fn main() {
-    let opts: Opts = Opts::parse();
-
-    print!("\x1B[2J\x1B[1;1H"); //clear screen and move to first line
-                                // println!("Value for config: {}", opts.config);
-    println!("Using input file: {}", opts.path);
+#[derive(Clap)]
+enum SubCommand {
+    /// analyse the tokens and the code elements of files, to the outputs of `--format`
+    Analyse(AnalyseCommand),
+    /// detect the build systems, ecosystems and frameworks, without analysing the files
+    Detect(DetectCommand),
+    /// print the tokens of files, by the grammars or the lexical analysers
+    Tokenize(TokenizeCommand),
+    /// print the files with the colors of tokens, for the terminals or the pages
+    Highlight(HighlightCommand),
+    /// print the lines of code by language, directory and module
+    Stats(StatsCommand),
+    /// list the dependencies which declared by the modules, like `build.gradle` and `go.mod`
+    Deps(DepsCommand),
+    /// list the grammars by the extensions, the bundled ones and the ones of `scie.toml`
+    Grammar(GrammarCommand),
+    /// inspect the outputs of analysis, like `scie.bin` and `scie.json`
+    Inspect(InspectCommand),
+    /// serve the analyses on localhost, by HTTP endpoints and JSON-RPC, with the warm grammars
+    Serve(ServeCommand),
+}
 
-    if !Validate::is_valid_path(opts.path.clone()) {
-        println!("is invalid path: {:?}", opts.path.clone());
-        return;
-    }
+fn main() {
+    // the invalid usages exit with `2`, the help and version with `0`
+    let opts: Opts = match Opts::try_parse() {
+        Ok(opts) => opts,
+        Err(err) if err.use_stderr() => {
+            eprintln!("{}", err);
+            std::process::exit(EXIT_USAGE);
+        }
+        Err(err) => {
+            println!("{}", err);
+            std::process::exit(0);
+        }
+    };
 
-    let path = Path::new(&opts.path);
-    let files = Analyser::ident_by_dir(&path.to_path_buf(), opts.debug, true);
-    if opts.json {
-        BinGen::jsonify(files.clone(), "scie.json");
-    } else {
-        BinGen::code_files(files, "scie.bin");
-    }
+    let globals = &opts.globals;
+    let code = match &opts.command {
+        SubCommand::Analyse(command) => Analyse::run(command, globals),
+        SubCommand::Detect(command) => Detect::run(command, globals),
+        SubCommand::Tokenize(command) => Tokenize::run(command, globals),
+        SubCommand::Highlight(command) => Highlight::run(command, globals),
+        SubCommand::Stats(command) => Stats::run(command, globals),
+        SubCommand::Deps(command) => Deps::run(command, globals),
+        SubCommand::Grammar(command) => GrammarList::run(command, globals),
+        SubCommand::Inspect(command) => Inspect::run(command),
+        SubCommand::Serve(command) => Serve::run(command),
+    };
+    std::process::exit(code);
 }
 
 #[cfg(test)]
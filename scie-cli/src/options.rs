@@ -0,0 +1,166 @@
+use crate::config::ScieConfig;
+use clap::Clap;
+use std::fs;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// the exit code of the invalid usages, like unknown languages and missing paths, as clap
+pub const EXIT_USAGE: i32 = 2;
+/// the exit code of the failures, like the outputs can't be written
+pub const EXIT_FAILURE: i32 = 1;
+
+// the options of all commands, before the subcommand
+#[derive(Clap, Debug, Clone, Default)]
+pub struct GlobalOpts {
+    /// the project config, by default the `scie.toml` in the analysed dir or its parents
+    #[clap(short, long)]
+    pub config: Option<String>,
+    /// print the analysed files with `-v`, and the debug logs with `-vv`
+    #[clap(short, long, parse(from_occurrences))]
+    pub verbose: i32,
+}
+
+impl GlobalOpts {
+    /// the config of the analysed path
+    pub fn config(&self, input: &Path) -> Result<ScieConfig, CliError> {
+        ScieConfig::load(self.config.as_deref(), input)
+    }
+}
+
+/// the files of commands, by the globs of paths which are relative to the analysed dir, and by
+/// the languages
+#[derive(Clap, Debug, Clone, Default)]
+pub struct FilterOpts {
+    /// only the files match the glob, like `src/**/*.java`, can be repeated
+    #[clap(long, number_of_values = 1)]
+    pub include: Vec<String>,
+    /// skip the files match the glob, like `**/generated/**`, can be repeated
+    #[clap(long, number_of_values = 1)]
+    pub exclude: Vec<String>,
+    /// only the language by name or extension, like `java` or `.rs`, can be repeated
+    #[clap(short, long, number_of_values = 1)]
+    pub language: Vec<String>,
+}
+
+/// the errors of commands, which are printed to stderr with the exit code
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    Usage(String),
+    Failure(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::Failure(_) => EXIT_FAILURE,
+        }
+    }
+
+    /// print the error of command, returns the exit code
+    pub fn exit(result: Result<(), CliError>) -> i32 {
+        match result {
+            Ok(_) => 0,
+            Err(err) => {
+                match &err {
+                    CliError::Usage(msg) | CliError::Failure(msg) => eprintln!("{}", msg),
+                }
+                err.exit_code()
+            }
+        }
+    }
+}
+
+impl From<String> for CliError {
+    fn from(err: String) -> Self {
+        CliError::Failure(err)
+    }
+}
+
+pub struct Output {}
+
+impl Output {
+    /// the path to analyse, which must exist
+    pub fn input(path: &str) -> Result<PathBuf, CliError> {
+        let input = PathBuf::from(path);
+        if !input.exists() {
+            return Err(CliError::Usage(format!("is invalid path: {}", path)));
+        }
+
+        Ok(input)
+    }
+
+    /// stdout without `output` or by `-`
+    pub fn is_stdout(output: Option<&str>) -> bool {
+        matches!(output, None | Some("-"))
+    }
+
+    /// the file of `output`, or stdout
+    pub fn writer(output: Option<&str>) -> Result<Box<dyn Write>, CliError> {
+        match output {
+            Some(path) if !Output::is_stdout(output) => fs::File::create(path)
+                .map(|file| Box::new(BufWriter::new(file)) as Box<dyn Write>)
+                .map_err(|err| CliError::Failure(format!("failed to write {}: {}", path, err))),
+            _ => Ok(Box::new(io::stdout())),
+        }
+    }
+
+    /// write to the file of `output`, or stdout, ends with a new line
+    pub fn write(output: Option<&str>, content: &str) -> Result<(), CliError> {
+        let mut writer = Output::writer(output)?;
+        let result = writer.write_all(content.as_bytes()).and_then(|_| {
+            if content.ends_with('\n') {
+                Ok(())
+            } else {
+                writer.write_all(b"\n")
+            }
+        });
+
+        match result.and_then(|_| writer.flush()) {
+            // the pipes are closed by the readers, like `| head`
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result.map_err(|err| {
+                CliError::Failure(format!(
+                    "failed to write {}: {}",
+                    output.unwrap_or("stdout"),
+                    err
+                ))
+            }),
+        }
+    }
+
+    /// stdout is a terminal, not a pipe or a file
+    pub fn is_terminal() -> bool {
+        io::stdout().is_terminal()
+    }
+
+    /// clear screen and move to first line, only in the terminals
+    pub fn clear_screen() {
+        if Output::is_terminal() {
+            print!("\x1B[2J\x1B[1;1H");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::ScieConfig;
+    use crate::options::{CliError, FilterOpts, Output, EXIT_USAGE};
+
+    #[test]
+    fn should_exit_with_usage_code_for_invalid_options() {
+        let opts = FilterOpts {
+            language: vec!["nothing".to_string()],
+            ..FilterOpts::default()
+        };
+        let err = ScieConfig::default().options(&opts).unwrap_err();
+        assert_eq!(EXIT_USAGE, err.exit_code());
+        assert_eq!(
+            EXIT_USAGE,
+            CliError::exit(Output::input("nothing").map(|_| ()))
+        );
+
+        assert_eq!(1, CliError::from("failed".to_string()).exit_code());
+        assert_eq!(0, CliError::exit(Ok(())));
+    }
+}
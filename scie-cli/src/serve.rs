@@ -0,0 +1,577 @@
+use crate::validate::Validate;
+use clap::Clap;
+use scie_bingen::grammar_gen::{GrammarGen, LangConfig};
+use scie_bingen::language_gen::LangExtGen;
+use scie_core::analyser::{AnalyseOptions, Analyser, CodeStats, LexicalAnalyser};
+use scie_detector::framework_detector::FrameworkDetector;
+use scie_grammar::grammar::Grammar;
+use scie_model::artifact::CodeFile;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use tiny_http::{Header, Method, Response, Server};
+
+/// serve the analyses on localhost, by HTTP endpoints and JSON-RPC
+#[derive(Clap)]
+pub struct ServeCommand {
+    /// the port on `127.0.0.1`, `0` for any free port
+    #[clap(long, default_value = "7878")]
+    pub port: u16,
+    /// the count of requests which are processed concurrently
+    #[clap(long, default_value = "4")]
+    pub workers: usize,
+    /// the dir of the served projects, the `path` of requests must be in it
+    #[clap(long, default_value = ".")]
+    pub root: String,
+}
+
+/// the max size of request bodies, in bytes
+const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+/// the grammars and the configurations of languages, which are shared by the workers
+pub struct Languages {
+    configs: BTreeMap<String, LangConfig>,
+    lang_ext: LangExtGen,
+}
+
+impl Default for Languages {
+    fn default() -> Self {
+        Languages {
+            configs: GrammarGen::build_grammar_map(),
+            lang_ext: LangExtGen::default(),
+        }
+    }
+}
+
+impl Languages {
+    /// the extension of a language, which is an extension like `.java` or `java`, a name of
+    /// language like `java`, or a scope name like `source.java`
+    pub fn resolve(&self, language: &str) -> Option<String> {
+        let ext = if language.starts_with('.') {
+            language.to_string()
+        } else {
+            format!(".{}", language)
+        };
+        if self.configs.contains_key(&ext) || self.lang_ext.ext_map.contains_key(&ext) {
+            return Some(ext);
+        }
+
+        self.configs
+            .iter()
+            .find(|(_, config)| config.lang == language || config.scope_name == language)
+            .map(|(ext, _)| ext.clone())
+            .or_else(|| {
+                self.lang_ext
+                    .ext_map
+                    .iter()
+                    .filter(|(_, entry)| entry.name == language)
+                    .map(|(ext, _)| ext.clone())
+                    .min()
+            })
+    }
+
+    pub fn grammar(&self, ext: &str) -> Option<Grammar> {
+        let config = self.configs.get(ext)?;
+        Some(Grammar::from_file(&config.path))
+    }
+
+    pub fn lexer(&self, ext: &str) -> Option<LexicalAnalyser> {
+        LexicalAnalyser::by_ext(&self.lang_ext, ext)
+    }
+}
+
+/// the status code and the message of a failed request
+type ServeError = (u16, String);
+
+/// the endpoints, all of them take and return JSON:
+///
+/// | endpoint | body | result |
+/// |----------|------|--------|
+/// | `POST /tokenize` | `{"code": "...", "language": "java"}` or `"scope": "source.java"` | the tokens |
+/// | `POST /analyse` | `{"path": "..."}` | the detected result and the code files |
+/// | `POST /detect` | `{"path": "..."}` | the detected result |
+/// | `POST /stats` | `{"path": "..."}` | the lines of code, like `--stats` |
+/// | `POST /rpc` | JSON-RPC 2.0, the methods are the names of endpoints | |
+/// | `GET /health` | | `{"status": "ok"}` |
+///
+/// the relative paths are in the root, and only the requests of loopback hosts are served, against
+/// the DNS rebinding of browsers
+pub struct Serve {
+    languages: Languages,
+    root: PathBuf,
+}
+
+impl Serve {
+    /// returns the exit code, it serves until the process is killed
+    pub fn run(command: &ServeCommand) -> i32 {
+        let started = Serve::new(Path::new(&command.root))
+            .and_then(|serve| Serve::start(serve, command.port, command.workers));
+        let (server, workers) = match started {
+            Ok(started) => started,
+            Err(err) => {
+                eprintln!("{}", err);
+                return 1;
+            }
+        };
+
+        println!("scie serves on http://{}", server.server_addr());
+        for worker in workers {
+            let _ = worker.join();
+        }
+        0
+    }
+
+    pub fn new(root: &Path) -> Result<Serve, String> {
+        let root = root
+            .canonicalize()
+            .map_err(|err| format!("invalid root {}: {}", root.display(), err))?;
+        Ok(Serve {
+            languages: Languages::default(),
+            root,
+        })
+    }
+
+    /// the server and the workers, which stop by `Server::unblock` for each of them
+    pub fn start(
+        serve: Serve,
+        port: u16,
+        workers: usize,
+    ) -> Result<(Arc<Server>, Vec<JoinHandle<()>>), String> {
+        let server = Arc::new(Server::http(("127.0.0.1", port)).map_err(|err| err.to_string())?);
+        let serve = Arc::new(serve);
+
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let server = server.clone();
+                let serve = serve.clone();
+                thread::spawn(move || Worker::new(serve).listen(&server))
+            })
+            .collect();
+
+        Ok((server, workers))
+    }
+}
+
+/// a thread of `Serve`, the grammars are compiled once by the worker, and reused by the next
+/// requests of it, for the grammars can't be shared by threads
+pub struct Worker {
+    serve: Arc<Serve>,
+    grammars: HashMap<String, Grammar>,
+}
+
+impl Worker {
+    pub fn new(serve: Arc<Serve>) -> Worker {
+        Worker {
+            serve,
+            grammars: Default::default(),
+        }
+    }
+
+    fn listen(&mut self, server: &Server) {
+        for mut request in server.incoming_requests() {
+            let host = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("Host"))
+                .map(|header| header.value.as_str().to_string());
+            let mut body = String::new();
+            let (status, value) = if !host.is_some_and(|host| Worker::is_loopback(&host)) {
+                (
+                    403,
+                    json!({ "error": "only the loopback hosts are served" }),
+                )
+            } else if request.body_length().unwrap_or(0) > MAX_BODY_SIZE {
+                (413, json!({ "error": "request body is too large" }))
+            } else {
+                let mut reader = request.as_reader().take(MAX_BODY_SIZE as u64 + 1);
+                match reader.read_to_string(&mut body) {
+                    Ok(size) if size > MAX_BODY_SIZE => {
+                        (413, json!({ "error": "request body is too large" }))
+                    }
+                    Ok(_) => {
+                        let (method, url) = (request.method().clone(), request.url().to_string());
+                        panic::catch_unwind(AssertUnwindSafe(|| self.handle(&method, &url, &body)))
+                            .unwrap_or_else(|_| {
+                                (500, json!({ "error": "failed to process request" }))
+                            })
+                    }
+                    Err(err) => (400, json!({ "error": err.to_string() })),
+                }
+            };
+
+            let header =
+                Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+            // the notifications of JSON-RPC have no response
+            let text = match status {
+                204 => String::new(),
+                _ => value.to_string(),
+            };
+            let response = Response::from_string(text)
+                .with_status_code(status)
+                .with_header(header);
+            if let Err(err) = request.respond(response) {
+                eprintln!("failed to respond: {}", err);
+            }
+        }
+    }
+
+    /// the host of `Host` header, like `localhost:7878` or `[::1]`
+    fn is_loopback(host: &str) -> bool {
+        let name = match host.strip_prefix('[') {
+            Some(rest) => rest.split(']').next().unwrap_or(""),
+            None => host.split(':').next().unwrap_or(""),
+        };
+        name.eq_ignore_ascii_case("localhost") || name == "127.0.0.1" || name == "::1"
+    }
+
+    /// the status code and the body of response, `204` without body for the JSON-RPC notifications
+    pub fn handle(&mut self, method: &Method, url: &str, body: &str) -> (u16, Value) {
+        let path = url.split('?').next().unwrap_or("");
+        match (method, path) {
+            (Method::Get, "/health") => return (200, json!({ "status": "ok" })),
+            (Method::Post, "/rpc") => {
+                return match self.rpc(body) {
+                    Some(result) => (200, result),
+                    None => (204, Value::Null),
+                }
+            }
+            (Method::Post, _) => {}
+            (_, "/health")
+            | (_, "/rpc")
+            | (_, "/tokenize")
+            | (_, "/analyse")
+            | (_, "/detect")
+            | (_, "/stats") => return (405, json!({ "error": "method not allowed" })),
+            _ => {
+                return (
+                    404,
+                    json!({ "error": format!("unknown endpoint: {}", path) }),
+                )
+            }
+        }
+
+        let params: Value = match serde_json::from_str(body) {
+            Ok(params) => params,
+            Err(err) => return (400, json!({ "error": err.to_string() })),
+        };
+        match self.call(path.trim_start_matches('/'), &params) {
+            Ok(result) => (200, result),
+            Err((status, message)) => (status, json!({ "error": message })),
+        }
+    }
+
+    /// a JSON-RPC 2.0 request, or a batch of them, `None` if all of them are notifications
+    pub fn rpc(&mut self, body: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(body) {
+            Ok(request) => request,
+            Err(err) => return Some(Worker::rpc_error(Value::Null, -32700, &err.to_string())),
+        };
+
+        match request {
+            Value::Array(requests) if requests.is_empty() => {
+                Some(Worker::rpc_error(Value::Null, -32600, "invalid request"))
+            }
+            Value::Array(requests) => {
+                let results: Vec<Value> = requests
+                    .iter()
+                    .filter_map(|request| self.rpc_call(request))
+                    .collect();
+                if results.is_empty() {
+                    None
+                } else {
+                    Some(Value::Array(results))
+                }
+            }
+            request => self.rpc_call(&request),
+        }
+    }
+
+    /// the response of a request, and none of a notification, which is a request without `id`
+    fn rpc_call(&mut self, request: &Value) -> Option<Value> {
+        let id = request.get("id").cloned();
+        let method = match request.get("method").and_then(|method| method.as_str()) {
+            Some(method) => method,
+            None => {
+                let id = id.unwrap_or(Value::Null);
+                return Some(Worker::rpc_error(id, -32600, "invalid request"));
+            }
+        };
+
+        let params = request.get("params").cloned().unwrap_or_else(|| json!({}));
+        let result = self.call(method, &params);
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((status, message)) => {
+                let code = match status {
+                    404 => -32601,
+                    400 | 403 => -32602,
+                    _ => -32603,
+                };
+                Worker::rpc_error(id, code, &message)
+            }
+        })
+    }
+
+    fn rpc_error(id: Value, code: i32, message: &str) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+    }
+
+    fn call(&mut self, method: &str, params: &Value) -> Result<Value, ServeError> {
+        match method {
+            "tokenize" => self.tokenize(params),
+            "analyse" => {
+                let path = self.path(params)?;
+                let (detector, files) = self.analyse(&path);
+                Ok(json!({ "detect": detector.result, "files": files }))
+            }
+            "detect" => {
+                let path = self.path(params)?;
+                let mut detector = FrameworkDetector::new();
+                detector.run(path.display().to_string());
+                Ok(json!(detector.result))
+            }
+            "stats" => {
+                let path = self.path(params)?;
+                let (detector, files) = self.analyse(&path);
+                Ok(json!(CodeStats::from_files(&path, &files, &detector)))
+            }
+            _ => Err((404, format!("unknown method: {}", method))),
+        }
+    }
+
+    fn tokenize(&mut self, params: &Value) -> Result<Value, ServeError> {
+        let code = params
+            .get("code")
+            .and_then(|code| code.as_str())
+            .ok_or((400, "missing code".to_string()))?;
+        let language = params
+            .get("language")
+            .or_else(|| params.get("scope"))
+            .and_then(|language| language.as_str())
+            .ok_or((400, "missing language or scope".to_string()))?;
+        let languages = &self.serve.languages;
+        let ext = languages
+            .resolve(language)
+            .ok_or_else(|| (400, format!("unknown language: {}", language)))?;
+
+        if !self.grammars.contains_key(&ext) {
+            if let Some(grammar) = languages.grammar(&ext) {
+                self.grammars.insert(ext.clone(), grammar);
+            }
+        }
+        let tokens = self
+            .grammars
+            .get_mut(&ext)
+            .and_then(|grammar| Analyser::tokenize_by_grammar(grammar, code));
+        let tokens = match tokens {
+            Some(tokens) => tokens,
+            None => match languages.lexer(&ext) {
+                Some(lexer) => lexer.tokenize(code),
+                None => return Err((400, format!("no grammar of language: {}", language))),
+            },
+        };
+
+        Ok(json!({ "language": ext, "tokens": tokens }))
+    }
+
+    fn analyse(&mut self, path: &Path) -> (FrameworkDetector, Vec<CodeFile>) {
+        let mut files = vec![];
+        let detector = Analyser::analyse_with(
+            &path.to_path_buf(),
+            &mut self.grammars,
+            &AnalyseOptions::default(),
+            false,
+            false,
+            |file| files.push(file),
+        );
+
+        (detector, files)
+    }
+
+    /// the canonical path in the root, the relative paths are from the root
+    fn path(&self, params: &Value) -> Result<PathBuf, ServeError> {
+        let path = params
+            .get("path")
+            .and_then(|path| path.as_str())
+            .ok_or((400, "missing path".to_string()))?;
+        let root = &self.serve.root;
+        let full = root.join(path);
+        if !Validate::is_valid_path(full.display().to_string()) {
+            return Err((400, format!("is invalid path: {}", path)));
+        }
+
+        let full = full
+            .canonicalize()
+            .map_err(|err| (400, format!("is invalid path: {}, {}", path, err)))?;
+        if !full.starts_with(root) {
+            return Err((403, format!("path is out of root: {}", path)));
+        }
+
+        Ok(full)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::serve::{Serve, Worker};
+    use serde_json::{json, Value};
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpStream};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::thread;
+    use tiny_http::Method;
+
+    fn serve() -> Serve {
+        Serve::new(PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().unwrap()).unwrap()
+    }
+
+    fn project() -> String {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("java")
+            .join("simple")
+            .display()
+            .to_string()
+    }
+
+    #[test]
+    fn should_tokenize_snippet_by_language_or_scope() {
+        let mut serve = Worker::new(Arc::new(serve()));
+        let body = json!({ "language": "java", "code": "class Demo {}" }).to_string();
+        let (status, result) = serve.handle(&Method::Post, "/tokenize", &body);
+        assert_eq!(200, status);
+        assert_eq!(".java", result["language"]);
+        assert_eq!("source.java", result["tokens"][0]["scopes"][0]);
+
+        let body = json!({ "scope": "source.rust", "code": "fn main() {}" }).to_string();
+        let (status, result) = serve.handle(&Method::Post, "/tokenize", &body);
+        assert_eq!(200, status);
+        assert_eq!("fn", result["tokens"][0]["value"]);
+
+        let body = json!({ "language": "nothing", "code": "" }).to_string();
+        assert_eq!(400, serve.handle(&Method::Post, "/tokenize", &body).0);
+        assert_eq!(404, serve.handle(&Method::Post, "/unknown", "{}").0);
+        assert_eq!(405, serve.handle(&Method::Get, "/tokenize", "").0);
+    }
+
+    #[test]
+    fn should_handle_json_rpc_batch() {
+        let mut serve = Worker::new(Arc::new(serve()));
+        let body = json!([
+            { "jsonrpc": "2.0", "id": 1, "method": "analyse", "params": { "path": project() } },
+            { "jsonrpc": "2.0", "id": 2, "method": "stats", "params": { "path": project() } },
+            { "jsonrpc": "2.0", "id": 3, "method": "lint", "params": {} },
+            { "jsonrpc": "2.0", "id": 4, "method": "detect", "params": {} },
+        ]);
+
+        let (status, result) = serve.handle(&Method::Post, "/rpc", &body.to_string());
+        assert_eq!(200, status);
+        assert_eq!(3, result[0]["result"]["files"].as_array().unwrap().len());
+        assert!(result[1]["result"]["total"].is_object());
+        assert_eq!(-32601, result[2]["error"]["code"]);
+        assert_eq!(-32602, result[3]["error"]["code"]);
+        assert_eq!(-32700, serve.rpc("{").unwrap()["error"]["code"]);
+    }
+
+    #[test]
+    fn should_not_respond_json_rpc_notifications() {
+        let mut serve = Worker::new(Arc::new(serve()));
+        let notification = json!({ "jsonrpc": "2.0", "method": "lint", "params": {} });
+        let (status, _) = serve.handle(&Method::Post, "/rpc", &notification.to_string());
+        assert_eq!(204, status);
+
+        let body = json!([notification, { "jsonrpc": "2.0", "id": 1, "method": "lint" }]);
+        let (_, result) = serve.handle(&Method::Post, "/rpc", &body.to_string());
+        assert_eq!(1, result.as_array().unwrap().len());
+        assert_eq!(1, result[0]["id"]);
+    }
+
+    #[test]
+    fn should_reject_paths_out_of_root() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let mut serve = Worker::new(Arc::new(Serve::new(&root).unwrap()));
+        for path in [project(), "..".to_string(), "/".to_string()].iter() {
+            let body = json!({ "path": path }).to_string();
+            assert_eq!(403, serve.handle(&Method::Post, "/detect", &body).0);
+        }
+
+        let body = json!({ "path": "src" }).to_string();
+        assert_eq!(200, serve.handle(&Method::Post, "/detect", &body).0);
+    }
+
+    fn send(addr: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response);
+        response
+    }
+
+    #[test]
+    fn should_reject_foreign_hosts_and_large_bodies() {
+        let (server, workers) = Serve::start(serve(), 0, 1).unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+
+        let health = |host: &str| format!("GET /health HTTP/1.0\r\nHost: {}\r\n\r\n", host);
+        assert!(send(addr, &health("localhost:7878")).starts_with("HTTP/1.0 200"));
+        assert!(send(addr, &health("[::1]:7878")).starts_with("HTTP/1.0 200"));
+        assert!(send(addr, &health("evil.example:7878")).starts_with("HTTP/1.0 403"));
+        assert!(send(addr, "GET /health HTTP/1.0\r\n\r\n").starts_with("HTTP/1.0 403"));
+
+        let large = "POST /rpc HTTP/1.0\r\nHost: localhost\r\nContent-Length: 99999999\r\n\r\n";
+        assert!(send(addr, large).starts_with("HTTP/1.0 413"));
+
+        server.unblock();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn should_serve_requests_concurrently() {
+        let (server, workers) = Serve::start(serve(), 0, 2).unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+
+        let clients: Vec<_> = (0..2)
+            .map(|_| {
+                thread::spawn(move || {
+                    let body = json!({ "path": project() }).to_string();
+                    let mut stream = TcpStream::connect(addr).unwrap();
+                    write!(
+                        stream,
+                        "POST /detect HTTP/1.0\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                    .unwrap();
+                    let mut response = String::new();
+                    stream.read_to_string(&mut response).unwrap();
+                    response
+                })
+            })
+            .collect();
+
+        for client in clients {
+            let response = client.join().unwrap();
+            assert!(response.starts_with("HTTP/1.0 200"));
+            let body = response.split("\r\n\r\n").nth(1).unwrap();
+            assert!(serde_json::from_str::<Value>(body).unwrap().is_object());
+        }
+
+        for _ in workers.iter() {
+            server.unblock();
+        }
+        for worker in workers {
+            worker.join().unwrap();
+        }
+    }
+}
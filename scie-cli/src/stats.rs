@@ -0,0 +1,57 @@
+use crate::analyse::Analyse;
+use crate::options::{CliError, FilterOpts, GlobalOpts, Output};
+use clap::Clap;
+use scie_core::analyser::CodeStats;
+use scie_infra::format_output::FormatOutput;
+
+/// print the lines of code by language, directory and module
+#[derive(Clap)]
+pub struct StatsCommand {
+    /// the dir or file to count
+    #[clap(default_value = ".")]
+    pub path: String,
+    #[clap(long, default_value = "table", possible_values = &["table", "json"])]
+    pub format: String,
+    /// the file to write, or stdout
+    #[clap(short, long)]
+    pub output: Option<String>,
+    #[clap(flatten)]
+    pub filter: FilterOpts,
+}
+
+pub struct Stats {}
+
+impl Stats {
+    pub fn run(command: &StatsCommand, globals: &GlobalOpts) -> i32 {
+        CliError::exit(Stats::execute(command, globals))
+    }
+
+    fn execute(command: &StatsCommand, globals: &GlobalOpts) -> Result<(), CliError> {
+        let input = Output::input(&command.path)?;
+        let options = globals.config(&input)?.options(&command.filter)?;
+        let (detector, files) = Analyse::files(&input, &options, globals.verbose);
+        let stats = CodeStats::from_files(&input, &files, &detector);
+
+        let content = match command.format.as_str() {
+            "json" => serde_json::to_string_pretty(&stats).map_err(|err| err.to_string())?,
+            _ => Stats::table(&stats),
+        };
+        Output::write(command.output.as_deref(), &content)
+    }
+
+    fn table(stats: &CodeStats) -> String {
+        let tables: Vec<String> = [
+            ("language", &stats.languages),
+            ("directory", &stats.directories),
+            ("module", &stats.modules),
+        ]
+        .iter()
+        .map(|(name, group)| {
+            let rows = CodeStats::rows(group, &stats.total);
+            FormatOutput::table(&CodeStats::headers(name), &rows)
+        })
+        .collect();
+
+        tables.join("\n\n")
+    }
+}
@@ -0,0 +1,59 @@
+use crate::analyse::Analyse;
+use crate::inspect::Inspect;
+use crate::options::{CliError, FilterOpts, GlobalOpts, Output};
+use clap::Clap;
+use scie_model::artifact::CodeFile;
+use serde_json::json;
+
+/// print the tokens of files, by the grammars or the lexical analysers
+#[derive(Clap)]
+pub struct TokenizeCommand {
+    /// the file or dir to tokenize
+    #[clap(default_value = ".")]
+    pub path: String,
+    /// `text` prints a token per line, like `scie-cli inspect tokens`
+    #[clap(long, default_value = "text", possible_values = &["text", "json"])]
+    pub format: String,
+    /// the file to write, or stdout
+    #[clap(short, long)]
+    pub output: Option<String>,
+    #[clap(flatten)]
+    pub filter: FilterOpts,
+}
+
+pub struct Tokenize {}
+
+impl Tokenize {
+    pub fn run(command: &TokenizeCommand, globals: &GlobalOpts) -> i32 {
+        CliError::exit(Tokenize::execute(command, globals))
+    }
+
+    fn execute(command: &TokenizeCommand, globals: &GlobalOpts) -> Result<(), CliError> {
+        let input = Output::input(&command.path)?;
+        let options = globals.config(&input)?.options(&command.filter)?;
+        let (_, files) = Analyse::files(&input, &options, globals.verbose);
+
+        let content = match command.format.as_str() {
+            "json" => {
+                let files: Vec<_> = files
+                    .iter()
+                    .map(|file| json!({ "path": file.path, "tokens": file.elements }))
+                    .collect();
+                serde_json::to_string_pretty(&files).map_err(|err| err.to_string())?
+            }
+            _ => Tokenize::text(&files),
+        };
+        Output::write(command.output.as_deref(), &content)
+    }
+
+    fn text(files: &[CodeFile]) -> String {
+        let mut lines = vec![];
+        for file in files {
+            for element in file.elements.iter() {
+                lines.push(format!("{}:{}", file.path, Inspect::token_line(element)));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
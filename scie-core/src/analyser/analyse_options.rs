@@ -0,0 +1,431 @@
+use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
+use scie_bingen::grammar_gen::GrammarGen;
+use scie_bingen::language_gen::LangExtGen;
+use scie_detector::detect_result::Ecosystem;
+use scie_detector::framework_detector::FrameworkDetector;
+use scie_detector::rules::FrameworkRules;
+use scie_grammar::inter::IRawGrammar;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// the files to analyse, by the globs of paths which are relative to the analysed dir, and by
+/// the languages, with the settings of project like `scie.toml`. the languages of builders are
+/// resolved by `resolve`
+#[derive(Debug, Clone, Default)]
+pub struct AnalyseOptions {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    // the languages as given, like `java` and `.rs`, resolved with the extensions dirs
+    languages: Vec<String>,
+    // the languages of options, like `java` and `rs`
+    names: Vec<String>,
+    // the extensions of languages, like `.java`
+    exts: Vec<String>,
+    // the dir which the ignores and the overrides are relative to, like the dir of `scie.toml`
+    project: Option<PathBuf>,
+    ignore: Option<GlobSet>,
+    // the globs and the languages as given, like `Jenkinsfile` to `groovy`
+    override_languages: Vec<(GlobMatcher, String)>,
+    // the globs and the extensions of languages, the first matched one is used
+    overrides: Vec<(GlobMatcher, String)>,
+    // the languages of the extensions dirs out of the bundled ones
+    extensions: Option<LangExtGen>,
+    // the grammars of the extensions dirs by extensions, read by `with_extensions`
+    grammars: BTreeMap<String, IRawGrammar>,
+    // the bundled languages with the extensions dirs, scanned once by `resolve`
+    lang_ext: Option<LangExtGen>,
+    // the budgets as given, by the languages
+    budget_languages: Vec<(String, Duration)>,
+    // the time budgets of tokenizing a file by grammar, by extensions
+    budgets: HashMap<String, Duration>,
+    rules: FrameworkRules,
+}
+
+impl AnalyseOptions {
+    /// the languages are names or extensions, like `java`, `rust` and `.rs`
+    pub fn new(
+        include: &[String],
+        exclude: &[String],
+        languages: &[String],
+    ) -> Result<AnalyseOptions, String> {
+        AnalyseOptions::default()
+            .with_globs(include, exclude)?
+            .with_languages(languages)
+            .resolve()
+    }
+
+    pub fn with_globs(mut self, include: &[String], exclude: &[String]) -> Result<Self, String> {
+        self.include = AnalyseOptions::glob_set(include)?;
+        self.exclude = AnalyseOptions::glob_set(exclude)?;
+        Ok(self)
+    }
+
+    /// only the files of languages are analysed
+    pub fn with_languages(mut self, languages: &[String]) -> Self {
+        self.languages.extend_from_slice(languages);
+        self
+    }
+
+    /// the dirs of VS Code extensions, which languages and grammars are used with the bundled
+    /// ones, the invalid grammars are errors with the paths of them
+    pub fn with_extensions(mut self, dirs: &[PathBuf]) -> Result<Self, String> {
+        // not the `default` of `LangExtGen`, which are the bundled languages
+        let mut extensions = match self.extensions.take() {
+            Some(extensions) => extensions,
+            None => LangExtGen::new(),
+        };
+        for dir in dirs {
+            if !dir.is_dir() {
+                return Err(format!("is invalid extensions dir: {}", dir.display()));
+            }
+            let langs = LangExtGen::from_path(dir.clone());
+            for (ext, config) in GrammarGen::grammar_map_of(&langs) {
+                let grammar = GrammarGen::read_grammar(&config)
+                    .map_err(|err| format!("invalid grammar {}", err))?;
+                self.grammars.insert(ext, grammar);
+            }
+            extensions.merge(langs);
+        }
+
+        self.extensions = Some(extensions);
+        self.lang_ext = None;
+        Ok(self)
+    }
+
+    /// the globs of `ignore` and `overrides` are relative to `project`, the overrides are the
+    /// globs and the languages of the matched files, like `Jenkinsfile` to `groovy`
+    pub fn with_project(
+        mut self,
+        project: &Path,
+        ignore: &[String],
+        overrides: &[(String, String)],
+    ) -> Result<Self, String> {
+        self.project = Some(
+            project
+                .canonicalize()
+                .unwrap_or_else(|_| project.to_path_buf()),
+        );
+        self.ignore = AnalyseOptions::glob_set(ignore)?;
+
+        for (glob, language) in overrides {
+            let matcher = Glob::new(glob)
+                .map_err(|err| err.to_string())?
+                .compile_matcher();
+            self.override_languages.push((matcher, language.clone()));
+        }
+
+        Ok(self)
+    }
+
+    /// the budgets of the languages, instead of the default budget of tokenizing a file
+    pub fn with_budgets(mut self, budgets: &[(String, Duration)]) -> Self {
+        self.budget_languages.extend_from_slice(budgets);
+        self
+    }
+
+    /// the framework rules of project, which replace the rules with the same names
+    pub fn with_rules(mut self, rules: FrameworkRules) -> Self {
+        self.rules.merge(rules);
+        self
+    }
+
+    /// the path is relative to `root`, or the path itself out of it
+    pub fn is_included(&self, root: &Path, path: &Path) -> bool {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        if let Some(include) = &self.include {
+            if !include.is_match(relative) {
+                return false;
+            }
+        }
+        if let Some(ignore) = &self.ignore {
+            if ignore.is_match(self.project_path(root, path)) {
+                return false;
+            }
+        }
+
+        match &self.exclude {
+            Some(exclude) => !exclude.is_match(relative),
+            None => true,
+        }
+    }
+
+    /// the extension of the overridden language of file, like `.groovy` for `Jenkinsfile`
+    pub fn language_of(&self, root: &Path, path: &Path) -> Option<&str> {
+        if self.overrides.is_empty() {
+            return None;
+        }
+
+        let relative = self.project_path(root, path);
+        self.overrides
+            .iter()
+            .find(|(matcher, _)| matcher.is_match(&relative))
+            .map(|(_, ext)| ext.as_str())
+    }
+
+    /// the files of extension are analysed, like `.java`
+    pub fn has_ext(&self, ext: &str) -> bool {
+        self.exts.is_empty() || self.exts.iter().any(|it| it == ext)
+    }
+
+    /// the extension is in the languages of options, which are analysed even if the ecosystem is
+    /// not detected
+    pub fn is_requested(&self, ext: &str) -> bool {
+        self.exts.iter().any(|it| it == ext)
+    }
+
+    /// the name of language, like the `java` of modules
+    pub fn has_language(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.names.is_empty()
+            || self.names.contains(&name)
+            || self.exts.contains(&format!(".{}", name))
+    }
+
+    pub fn has_ecosystem(&self, ecosystem: &Ecosystem) -> bool {
+        self.exts.is_empty() || self.ecosystems().contains(ecosystem)
+    }
+
+    /// the ecosystems of the languages and the overridden languages
+    pub fn ecosystems(&self) -> Vec<Ecosystem> {
+        let exts = self
+            .exts
+            .iter()
+            .chain(self.overrides.iter().map(|(_, ext)| ext));
+        let mut ecosystems = vec![];
+        for ext in exts {
+            if let Some(ecosystem) = Ecosystem::from_extension(ext.trim_start_matches('.')) {
+                if !ecosystems.contains(&ecosystem) {
+                    ecosystems.push(ecosystem);
+                }
+            }
+        }
+
+        ecosystems
+    }
+
+    pub fn budget(&self, ext: &str) -> Option<Duration> {
+        self.budgets.get(ext).copied()
+    }
+
+    /// the bundled languages, with the languages of the extensions dirs
+    pub fn lang_ext(&self) -> LangExtGen {
+        match &self.lang_ext {
+            Some(lang_ext) => lang_ext.clone(),
+            None => self.scan(),
+        }
+    }
+
+    /// the grammars of the extensions dirs, by the extensions of the languages
+    pub fn extension_grammars(&self) -> BTreeMap<String, IRawGrammar> {
+        self.grammars
+            .iter()
+            .filter(|(ext, _)| self.has_ext(ext))
+            .map(|(ext, grammar)| (ext.clone(), grammar.clone()))
+            .collect()
+    }
+
+    /// detect the project with the framework rules of options
+    pub fn detect(&self, path: &Path) -> FrameworkDetector {
+        let mut detector = FrameworkDetector::new();
+        detector.run(path.display().to_string());
+        if !self.rules.rules.is_empty() {
+            detector.rules.merge(self.rules.clone());
+            detector.detect_frameworks(&[]);
+        }
+
+        detector
+    }
+
+    /// the extensions of the languages, the overrides and the budgets, with the languages of the
+    /// extensions dirs, after the builders which are in any order. the unknown languages are errors
+    pub fn resolve(mut self) -> Result<Self, String> {
+        if self.languages.is_empty()
+            && self.override_languages.is_empty()
+            && self.budget_languages.is_empty()
+        {
+            return Ok(self);
+        }
+
+        let lang_ext = match self.lang_ext.take() {
+            Some(lang_ext) => lang_ext,
+            None => self.scan(),
+        };
+
+        self.names.clear();
+        self.exts.clear();
+        for language in self.languages.iter() {
+            let exts = AnalyseOptions::exts_of(&lang_ext, language)?;
+            self.names
+                .push(language.trim_start_matches('.').to_lowercase());
+            self.exts.extend(exts);
+        }
+        self.exts.sort();
+        self.exts.dedup();
+
+        self.overrides.clear();
+        for (matcher, language) in self.override_languages.iter() {
+            let ext = AnalyseOptions::exts_of(&lang_ext, language)?.remove(0);
+            self.overrides.push((matcher.clone(), ext));
+        }
+
+        self.budgets.clear();
+        for (language, budget) in self.budget_languages.iter() {
+            for ext in AnalyseOptions::exts_of(&lang_ext, language)? {
+                self.budgets.insert(ext, *budget);
+            }
+        }
+
+        self.lang_ext = Some(lang_ext);
+        Ok(self)
+    }
+
+    // the bundled languages, with the languages of the extensions dirs
+    fn scan(&self) -> LangExtGen {
+        let mut lang_ext = LangExtGen::default();
+        if let Some(extensions) = &self.extensions {
+            lang_ext.merge(extensions.clone());
+        }
+
+        lang_ext
+    }
+
+    // the extensions of a language name or an extension, like `java` and `.rs`
+    fn exts_of(lang_ext: &LangExtGen, language: &str) -> Result<Vec<String>, String> {
+        let name = language.trim_start_matches('.').to_lowercase();
+        let mut exts: Vec<String> = lang_ext
+            .ext_map
+            .iter()
+            .filter(|(_, entry)| entry.name.to_lowercase() == name)
+            .map(|(ext, _)| ext.clone())
+            .collect();
+        let ext = format!(".{}", name);
+        if lang_ext.ext_map.contains_key(&ext) || Ecosystem::from_extension(&name).is_some() {
+            exts.retain(|it| it != &ext);
+            exts.insert(0, ext);
+        }
+        if exts.is_empty() {
+            return Err(format!("unknown language: {}", language));
+        }
+
+        Ok(exts)
+    }
+
+    // relative to the project, or to the analysed dir without project
+    fn project_path(&self, root: &Path, path: &Path) -> PathBuf {
+        if let Some(project) = &self.project {
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            if let Ok(relative) = canonical.strip_prefix(project) {
+                return relative.to_path_buf();
+            }
+        }
+
+        path.strip_prefix(root).unwrap_or(path).to_path_buf()
+    }
+
+    fn glob_set(globs: &[String]) -> Result<Option<GlobSet>, String> {
+        if globs.is_empty() {
+            return Ok(None);
+        }
+
+        let mut builder = GlobSetBuilder::new();
+        for glob in globs {
+            builder.add(Glob::new(glob).map_err(|err| err.to_string())?);
+        }
+        builder.build().map(Some).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyser::AnalyseOptions;
+    use scie_detector::detect_result::Ecosystem;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    #[test]
+    fn should_filter_files_by_globs_and_languages() {
+        let options = AnalyseOptions::new(
+            &["src/**".to_string()],
+            &["**/generated/**".to_string()],
+            &["java".to_string(), ".rs".to_string()],
+        )
+        .unwrap();
+
+        let root = Path::new("/demo");
+        assert!(options.is_included(root, Path::new("/demo/src/main/App.java")));
+        assert!(!options.is_included(root, Path::new("/demo/src/generated/Api.java")));
+        assert!(!options.is_included(root, Path::new("/demo/build.gradle")));
+
+        assert!(options.has_ext(".java"));
+        assert!(options.has_ext(".rs"));
+        assert!(!options.has_ext(".go"));
+        assert!(options.has_language("Java"));
+        assert!(options.has_ecosystem(&Ecosystem::Rust));
+        assert!(!options.has_ecosystem(&Ecosystem::Go));
+
+        assert!(AnalyseOptions::new(&[], &[], &["nothing".to_string()]).is_err());
+        assert!(AnalyseOptions::new(&["[".to_string()], &[], &[]).is_err());
+        assert!(AnalyseOptions::default().has_ext(".go"));
+    }
+
+    #[test]
+    fn should_match_project_globs_and_budgets() {
+        let project = std::env::temp_dir();
+        let options = AnalyseOptions::default()
+            .with_project(
+                &project,
+                &["vendor/**".to_string()],
+                &[("**/Jenkinsfile".to_string(), "groovy".to_string())],
+            )
+            .unwrap()
+            .with_budgets(&[("java".to_string(), Duration::from_secs(5))])
+            .resolve()
+            .unwrap();
+
+        let root = project.join("app");
+        assert!(!options.is_included(&root, &project.join("vendor").join("Lib.java")));
+        assert!(options.is_included(&root, &root.join("Main.java")));
+        assert_eq!(
+            Some(".groovy"),
+            options.language_of(&root, &root.join("Jenkinsfile"))
+        );
+        assert_eq!(None, options.language_of(&root, &root.join("Main.java")));
+        assert!(options.ecosystems().contains(&Ecosystem::Jvm));
+
+        assert_eq!(Some(Duration::from_secs(5)), options.budget(".java"));
+        assert_eq!(None, options.budget(".go"));
+    }
+
+    #[test]
+    fn should_resolve_languages_of_extensions_in_any_order() {
+        let extensions = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("scie-config")
+            .join("extensions");
+        let options = AnalyseOptions::default()
+            .with_languages(&["demo".to_string()])
+            .with_budgets(&[("demo".to_string(), Duration::from_secs(1))])
+            .with_extensions(&[extensions])
+            .unwrap()
+            .resolve()
+            .unwrap();
+
+        assert!(options.has_ext(".demo"));
+        assert!(!options.has_ext(".java"));
+        assert_eq!(Some(Duration::from_secs(1)), options.budget(".demo"));
+        // only the grammars of the extensions dirs, without the bundled ones
+        let grammars = options.extension_grammars();
+        assert_eq!(vec![".demo"], grammars.keys().collect::<Vec<_>>());
+        assert_eq!(
+            Some("source.demo".to_string()),
+            grammars[".demo"].scope_name
+        );
+
+        let unknown = AnalyseOptions::default().with_languages(&["demo".to_string()]);
+        assert!(unknown.resolve().is_err());
+    }
+}
@@ -1,11 +1,20 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+use crate::analyser::{
+    AnalyseOptions, ElementExtractor, LexicalAnalyser, TestClassifier, TestSummary,
+};
 use scie_bingen::grammar_gen::GrammarGen;
+use scie_bingen::language_gen::LangExtGen;
+use scie_detector::detect_result::Ecosystem;
 use scie_detector::framework_detector::FrameworkDetector;
 use scie_grammar::grammar::{Grammar, StackElement};
 use scie_infra::finder::Finder;
-use scie_model::artifact::{CodeFile, TokenElement};
+use scie_model::artifact::{CodeFile, LineBreaks, TokenElement};
+
+// the budget of tokenizing a file by grammar, some grammars are too slow for big or minified files
+const GRAMMAR_TIME_BUDGET: Duration = Duration::from_secs(10);
 
 pub struct Analyser {}
 
@@ -19,118 +28,296 @@ impl Analyser {
     }
 
     pub fn ident_by_dir(lang: &PathBuf, is_debug: bool, is_cli: bool) -> Vec<CodeFile> {
-        let mut detector = FrameworkDetector::new();
-        detector.run(lang.display().to_string());
+        Analyser::analyse(lang, is_debug, is_cli).1
+    }
 
-        let map = GrammarGen::build_output();
+    /// the detector is returned with the code files, for the frameworks and modules of project
+    pub fn analyse(
+        lang: &PathBuf,
+        is_debug: bool,
+        is_cli: bool,
+    ) -> (FrameworkDetector, Vec<CodeFile>) {
+        let mut code_files = vec![];
+        let detector = Analyser::analyse_each(lang, is_debug, is_cli, |file| code_files.push(file));
 
-        let mut grammar_map = HashMap::new();
-        if detector.tags.contains_key("workspace.java.gradle") {
-            let groovy_grammar = Grammar::new(map.grammar_map[".groovy"].clone());
-            grammar_map.insert(".gradle", groovy_grammar.clone());
-            grammar_map.insert(".groovy", groovy_grammar);
+        (detector, code_files)
+    }
 
-            let java_grammar = Grammar::new(map.grammar_map[".java"].clone());
-            grammar_map.insert(".java", java_grammar);
-        }
+    /// like `analyse`, but the code files are passed to `on_file` once analysed, and not kept, for
+    /// the streaming output of big repositories
+    pub fn analyse_each<F: FnMut(CodeFile)>(
+        lang: &PathBuf,
+        is_debug: bool,
+        is_cli: bool,
+        on_file: F,
+    ) -> FrameworkDetector {
+        Analyser::analyse_with(
+            lang,
+            &mut HashMap::new(),
+            &AnalyseOptions::default(),
+            is_debug,
+            is_cli,
+            on_file,
+        )
+    }
+
+    /// like `analyse_each`, the grammars by extensions are taken from `grammars`, and the missing
+    /// ones are compiled and added to it, so the next analyses reuse them, like `scie serve`.
+    /// only the files of `options` are analysed
+    pub fn analyse_with<F: FnMut(CodeFile)>(
+        lang: &PathBuf,
+        grammars: &mut HashMap<String, Grammar>,
+        options: &AnalyseOptions,
+        is_debug: bool,
+        is_cli: bool,
+        mut on_file: F,
+    ) -> FrameworkDetector {
+        let mut detector = options.detect(lang);
 
-        if detector.tags.contains_key("workspace.rust.cargo") {
-            let rust_grammar = Grammar::new(map.grammar_map[".rs"].clone());
-            grammar_map.insert(".rs", rust_grammar);
+        let files: Vec<PathBuf> = Finder::walk_filter_files(&lang)
+            .into_iter()
+            .filter(|path| options.is_included(lang, path))
+            .collect();
+        let mut ecosystems = detector.result.ecosystems();
+        // nothing detected when analyse a single file or a plain dir, guess it by the files
+        if ecosystems.is_empty() {
+            ecosystems = Analyser::ecosystems_by_files(&files);
+        }
+        for ecosystem in options.ecosystems() {
+            if !ecosystems.contains(&ecosystem) {
+                ecosystems.push(ecosystem);
+            }
         }
 
-        if detector.tags.contains_key("workspace.go") {
-            let c_grammar = Grammar::new(map.grammar_map[".go"].clone());
-            grammar_map.insert(".go", c_grammar);
+        let grammar_exts: Vec<(&str, &str)> = ecosystems
+            .iter()
+            .flat_map(Analyser::grammar_exts)
+            .filter(|(ext, _)| options.has_ext(ext))
+            .copied()
+            .collect();
+        let mut map = None;
+        // only the grammars of detected ecosystems are used, the others are kept in `grammars`
+        let mut grammar_map: HashMap<String, Grammar> = HashMap::new();
+        for (ext, grammar_ext) in grammar_exts {
+            let grammar = grammars.remove(ext).unwrap_or_else(|| {
+                let map = map.get_or_insert_with(GrammarGen::build_output);
+                Grammar::new(map.grammar_map[grammar_ext].clone())
+            });
+            grammar_map.insert(ext.to_string(), grammar);
+        }
+        // the grammars of the extensions dirs replace the bundled ones, and are not kept
+        let extension_grammars = options.extension_grammars();
+        for (ext, grammar) in extension_grammars.iter() {
+            if let Some(bundled) = grammar_map.insert(ext.clone(), Grammar::new(grammar.clone())) {
+                grammars.insert(ext.clone(), bundled);
+            }
         }
 
-        if detector.tags.contains_key("workspace.c") {
-            let c_grammar = Grammar::new(map.grammar_map[".c"].clone());
-            grammar_map.insert(".c", c_grammar);
+        // the logs go to stderr, the stdout may be the output, like `--output -`
+        if is_debug && !detector.result.is_empty() {
+            eprintln!("{:?}", detector.result);
         }
 
-        let files = Finder::walk_filter_files(&lang);
+        let mut summary = TestSummary::default();
+        Analyser::process_files(
+            lang,
+            &mut detector,
+            &ecosystems,
+            &mut grammar_map,
+            options,
+            files,
+            is_debug,
+            is_cli,
+            &mut |file| {
+                summary.add_file(&file);
+                on_file(file);
+            },
+        );
+        grammars.extend(
+            grammar_map
+                .into_iter()
+                .filter(|(ext, _)| !extension_grammars.contains_key(ext)),
+        );
+        detector.detect_frameworks_by_matched();
         if is_debug {
-            if !detector.tags.is_empty() {
-                println!("{:?}", detector.tags);
+            eprintln!("{:?}", detector.result.frameworks);
+        }
+        if is_debug || is_cli {
+            eprintln!("{:?}", summary);
+        }
+
+        detector
+    }
+
+    /// the extensions and the grammars of them, by the ecosystem
+    fn grammar_exts(ecosystem: &Ecosystem) -> &'static [(&'static str, &'static str)] {
+        match ecosystem {
+            Ecosystem::Jvm => &[
+                (".gradle", ".groovy"),
+                (".groovy", ".groovy"),
+                (".java", ".java"),
+            ],
+            Ecosystem::Rust => &[(".rs", ".rs")],
+            Ecosystem::Go => &[(".go", ".go")],
+            Ecosystem::C => &[(".c", ".c")],
+            Ecosystem::Python => &[(".py", ".py")],
+            Ecosystem::JavaScript => &[
+                (".js", ".js"),
+                (".jsx", ".jsx"),
+                (".ts", ".ts"),
+                (".tsx", ".tsx"),
+            ],
+        }
+    }
+
+    fn ecosystems_by_files(files: &[PathBuf]) -> Vec<Ecosystem> {
+        let mut ecosystems = vec![];
+        for path in files {
+            let ecosystem = match path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(Ecosystem::from_extension)
+            {
+                Some(ecosystem) => ecosystem,
+                None => continue,
+            };
+            if !ecosystems.contains(&ecosystem) {
+                ecosystems.push(ecosystem);
             }
         }
 
-        Analyser::process_files(&mut grammar_map, files, is_debug, is_cli)
+        ecosystems
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process_files(
-        grammar_map: &mut HashMap<&str, Grammar>,
+        root: &Path,
+        detector: &mut FrameworkDetector,
+        ecosystems: &[Ecosystem],
+        grammar_map: &mut HashMap<String, Grammar>,
+        options: &AnalyseOptions,
         files: Vec<PathBuf>,
         _is_debug: bool,
         is_cli: bool,
-    ) -> Vec<CodeFile> {
-        let mut parsed_files = vec![];
+        on_file: &mut dyn FnMut(CodeFile),
+    ) {
+        let mut lang_ext: Option<LangExtGen> = None;
+        let mut lexers: HashMap<String, Option<LexicalAnalyser>> = HashMap::new();
         for path in files {
-            if path.extension().is_none() {
+            let overridden = options.language_of(root, &path);
+            if (path.extension().is_none() && overridden.is_none()) || detector.is_excluded(&path) {
                 continue;
             }
 
-            if is_cli {
-                // todo: add clear current line & set value http://rosettacode.org/wiki/Terminal_control
-                println!("analyses: {:?}", path);
+            let lang = match overridden {
+                Some(ext) => ext.to_string(),
+                None => Analyser::get_lang_by_path(path.clone()),
+            };
+            if !options.has_ext(&lang) {
+                continue;
             }
 
-            let lang = Analyser::get_lang_by_path(path.clone());
-            let lang_grammar = grammar_map.get_mut(lang.as_str());
-            if lang_grammar.is_none() {
+            // the source files of detected ecosystems, which grammar is not loaded, like `.kt`,
+            // and of the languages in options
+            let is_fallback = !grammar_map.contains_key(lang.as_str())
+                && (options.is_requested(&lang)
+                    || overridden.is_some()
+                    || path
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .and_then(Ecosystem::from_extension)
+                        .map(|ecosystem| ecosystems.contains(&ecosystem))
+                        .unwrap_or(false));
+            if !grammar_map.contains_key(lang.as_str()) && !is_fallback {
                 continue;
             }
 
-            let grammar = lang_grammar.unwrap();
-            let mut code_file = CodeFile::new(path.clone());
-            let code;
-            match Finder::read_code(&path) {
-                Ok(str) => {
-                    code = str;
-                }
-                Err(_) => {
-                    continue;
-                }
+            if is_cli {
+                // todo: add clear current line & set value http://rosettacode.org/wiki/Terminal_control
+                eprintln!("analyses: {:?}", path);
             }
-            let mut rule_stack = Some(StackElement::null());
-
-            let mut line_num = 1;
-            for line in code.lines() {
-                let result = grammar.tokenize_line(line, &mut rule_stack);
-                for token in result.tokens {
-                    let start = token.start_index;
-                    let end = token.end_index;
-                    let text: String = String::from(line)
-                        .chars()
-                        .skip(start as usize)
-                        .take((end - start) as usize)
-                        .collect();
-
-                    code_file.elements.push(TokenElement::new(
-                        line_num,
-                        start,
-                        end,
-                        text,
-                        token.scopes,
-                    ));
+
+            let mut code_file = CodeFile::new(path.clone());
+            code_file.kind = detector.classify(&path);
+            let code = match Finder::read_code(&path) {
+                Ok(str) => str,
+                Err(_) => continue,
+            };
+
+            let budget = options.budget(&lang).unwrap_or(GRAMMAR_TIME_BUDGET);
+            let elements = grammar_map
+                .get_mut(lang.as_str())
+                .and_then(|grammar| Analyser::tokenize_within(grammar, &code, budget));
+            code_file.elements = match elements {
+                Some(elements) => elements,
+                None => {
+                    let lang_ext = lang_ext.get_or_insert_with(|| options.lang_ext());
+                    let lexer = lexers
+                        .entry(lang.clone())
+                        .or_insert_with(|| LexicalAnalyser::by_ext(lang_ext, &lang));
+                    match lexer {
+                        Some(lexer) => lexer.tokenize(&code),
+                        None => continue,
+                    }
                 }
-                rule_stack = result.rule_stack;
-                line_num = line_num + 1;
-            }
+            };
+
+            code_file.line_breaks = LineBreaks::from_code(&code);
 
-            parsed_files.push(code_file);
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            TestClassifier::classify(&mut code_file, relative);
+            code_file.code_elements = ElementExtractor::extract(&code_file.elements);
+
+            detector.match_file(&code_file);
+            on_file(code_file);
+        }
+    }
+
+    /// returns none when it takes longer than `GRAMMAR_TIME_BUDGET`, then use `LexicalAnalyser`
+    pub fn tokenize_by_grammar(grammar: &mut Grammar, code: &str) -> Option<Vec<TokenElement>> {
+        Analyser::tokenize_within(grammar, code, GRAMMAR_TIME_BUDGET)
+    }
+
+    /// like `tokenize_by_grammar`, with the budget of language, like the budgets of `scie.toml`
+    pub fn tokenize_within(
+        grammar: &mut Grammar,
+        code: &str,
+        budget: Duration,
+    ) -> Option<Vec<TokenElement>> {
+        let deadline = Instant::now() + budget;
+        let mut elements = vec![];
+        let mut rule_stack = Some(StackElement::null());
+
+        for (index, line) in code.lines().enumerate() {
+            let line_num = index as i32 + 1;
+            // the budget is checked by the matches of a line too, for the minified lines
+            let result = grammar.tokenize_line_until(line, &mut rule_stack, deadline)?;
+            let units: Vec<u16> = line.encode_utf16().collect();
+            for token in result.tokens {
+                let start = token.start_index;
+                let end = token.end_index;
+                let text = TokenElement::utf16_value(&units, start, end);
+
+                elements.push(TokenElement::new(line_num, start, end, text, token.scopes));
+            }
+            rule_stack = result.rule_stack;
         }
 
-        parsed_files
+        Some(elements)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::path::PathBuf;
+    use std::time::{Duration, Instant};
 
-    use crate::analyser::Analyser;
+    use crate::analyser::{AnalyseOptions, Analyser};
+    use scie_bingen::frame_file::{FrameReader, FrameWriter};
+    use scie_detector::rules::FrameworkRules;
+    use scie_grammar::grammar::Grammar;
+    use scie_model::artifact::CodeFileKind;
 
     #[test]
     fn should_identify_path() {
@@ -148,6 +335,245 @@ mod tests {
         assert_eq!(3, files.len())
     }
 
+    #[test]
+    fn should_reuse_grammars_of_previous_analyses() {
+        let lang = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("java")
+            .join("simple");
+
+        let mut grammars = HashMap::new();
+        let mut count = 0;
+        let options = AnalyseOptions::default();
+        Analyser::analyse_with(&lang, &mut grammars, &options, false, false, |_| count += 1);
+        assert_eq!(3, count);
+        assert!(grammars.contains_key(".java"));
+        assert!(grammars.contains_key(".gradle"));
+
+        let mut count = 0;
+        Analyser::analyse_with(&lang, &mut grammars, &options, false, false, |_| count += 1);
+        assert_eq!(3, count);
+        assert_eq!(3, grammars.len());
+    }
+
+    #[test]
+    fn should_analyse_files_of_options() {
+        let lang = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("java")
+            .join("spring");
+
+        let options = AnalyseOptions::new(&[], &[], &["java".to_string()]).unwrap();
+        let mut names = vec![];
+        let mut grammars = HashMap::new();
+        Analyser::analyse_with(&lang, &mut grammars, &options, false, false, |file| {
+            names.push(file.name)
+        });
+        assert_eq!(vec!["Application.java"], names);
+        assert!(!grammars.contains_key(".gradle"));
+
+        let options = AnalyseOptions::new(&[], &["src/**".to_string()], &[]).unwrap();
+        let mut names = vec![];
+        Analyser::analyse_with(&lang, &mut grammars, &options, false, false, |file| {
+            names.push(file.name)
+        });
+        assert_eq!(vec!["build.gradle"], names);
+    }
+
+    #[test]
+    fn should_extract_code_elements_of_python() {
+        let lang = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("python")
+            .join("poetry");
+
+        let (_, files) = Analyser::analyse(&lang, false, false);
+        let main = files.iter().find(|file| file.name == "main.py").unwrap();
+        assert!(main
+            .code_elements
+            .iter()
+            .any(|element| element.name == "read_root"));
+    }
+
+    #[test]
+    fn should_analyse_with_project_settings() {
+        let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("scie-config");
+        let rules = FrameworkRules::parse(
+            r#"[[framework]]
+name = "Demo"
+ecosystem = "Jvm"
+tokens = ["@EnableDemo"]"#,
+        )
+        .unwrap();
+        let options = AnalyseOptions::default()
+            .with_extensions(&[project.join("extensions")])
+            .unwrap()
+            .with_project(
+                &project,
+                &["generated/**".to_string()],
+                &[("Jenkinsfile".to_string(), "groovy".to_string())],
+            )
+            .unwrap()
+            .with_rules(rules)
+            .resolve()
+            .unwrap();
+
+        let mut files = vec![];
+        let mut grammars = HashMap::new();
+        let detector =
+            Analyser::analyse_with(&project, &mut grammars, &options, false, false, |file| {
+                files.push(file)
+            });
+
+        let mut names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        names.sort();
+        assert_eq!(vec!["Jenkinsfile", "Main.java", "hello.demo"], names);
+
+        let demo = files.iter().find(|file| file.name == "hello.demo").unwrap();
+        let say = demo.elements.iter().find(|it| it.value == "say").unwrap();
+        assert_eq!("keyword.control.demo", say.scopes[1]);
+        let jenkins = files
+            .iter()
+            .find(|file| file.name == "Jenkinsfile")
+            .unwrap();
+        assert_eq!("source.groovy", jenkins.elements[0].scopes[0]);
+
+        assert!(detector.result.has_framework("Demo"));
+        // the grammars of extensions dirs are not reused by other analyses
+        assert!(!grammars.contains_key(".demo"));
+    }
+
+    #[test]
+    fn should_tag_code_file_kind_by_content_root() {
+        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).to_path_buf();
+        let lang = root_dir
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("java")
+            .join("source-sets");
+
+        let files = Analyser::ident_by_dir(&lang, false, false);
+        let kind_of = |name: &str| files.iter().find(|file| file.name == name).unwrap().kind;
+
+        assert_eq!(CodeFileKind::Source, kind_of("Main.java"));
+        assert_eq!(CodeFileKind::Test, kind_of("MainTest.java"));
+        assert_eq!(CodeFileKind::Unknown, kind_of("build.gradle"));
+    }
+
+    #[test]
+    fn should_fallback_to_lexical_analyser_without_grammar() {
+        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).to_path_buf();
+        let lang = root_dir
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("java")
+            .join("maven");
+
+        // the grammars are only loaded for the ecosystems, like `.java` of Jvm
+        let options = AnalyseOptions::new(&[], &[], &["xml".to_string()]).unwrap();
+        let mut files = vec![];
+        Analyser::analyse_with(&lang, &mut HashMap::new(), &options, false, false, |file| {
+            files.push(file)
+        });
+        let pom = files.iter().find(|file| file.name == "pom.xml").unwrap();
+
+        assert_eq!("source.xml", pom.elements[0].scopes[0]);
+    }
+
+    #[test]
+    fn should_stream_files_as_analyse() {
+        let lang = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("java")
+            .join("spring");
+        let output = std::env::temp_dir().join("scie_stream.bin");
+
+        let mut writer = FrameWriter::create(&output).unwrap();
+        let detector = Analyser::analyse_each(&lang, false, false, |file| {
+            writer.write(&file).unwrap();
+        });
+        writer.finish().unwrap();
+
+        let (expected, files) = Analyser::analyse(&lang, false, false);
+        assert!(!expected.result.frameworks.is_empty());
+        assert_eq!(expected.result.frameworks, detector.result.frameworks);
+
+        let mut reader = FrameReader::open(&output).unwrap();
+        let streamed: Vec<String> = reader.files().map(|file| file.unwrap().path).collect();
+        let paths: Vec<String> = files.iter().map(|file| file.path.clone()).collect();
+        assert_eq!(paths, streamed);
+    }
+
+    #[test]
+    fn should_stop_tokenizing_a_long_line_by_budget() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("extensions")
+            .join("java")
+            .join("syntaxes")
+            .join("java.tmLanguage.json");
+        let mut grammar = Grammar::from_file(path.to_str().unwrap());
+        let elements =
+            Analyser::tokenize_within(&mut grammar, "int a = 1;", Duration::from_secs(10));
+        assert_eq!("int", elements.unwrap()[0].value);
+
+        // a minified line, which takes minutes without the budget
+        let code = "int a = 1; ".repeat(20000);
+        let begin = Instant::now();
+        let elements = Analyser::tokenize_within(&mut grammar, &code, Duration::from_millis(200));
+        assert!(elements.is_none());
+        assert!(begin.elapsed() < Duration::from_secs(5));
+
+        // the grammar is reused after the budget
+        let elements =
+            Analyser::tokenize_within(&mut grammar, "int a = 1;", Duration::from_secs(10));
+        assert_eq!("int", elements.unwrap()[0].value);
+    }
+
+    #[test]
+    fn should_cut_token_values_after_emoji() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("extensions")
+            .join("java")
+            .join("syntaxes")
+            .join("java.tmLanguage.json");
+        let mut grammar = Grammar::from_file(path.to_str().unwrap());
+        let code = "String s = \"🌏\"; int names = 1;";
+        let elements = Analyser::tokenize_by_grammar(&mut grammar, code).unwrap();
+
+        let emoji = elements.iter().position(|it| it.value == "🌏").unwrap();
+        assert_eq!("\"", elements[emoji + 1].value);
+        assert_eq!(";", elements[emoji + 2].value);
+        let names = elements.iter().find(|it| it.value == "names").unwrap();
+        assert_eq!(21, names.start_index);
+        let last = elements.iter().rev().find(|it| !it.value.is_empty());
+        assert_eq!(";", last.unwrap().value);
+    }
+
     #[test]
     fn should_identify_self_grammar() {
         let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).to_path_buf();
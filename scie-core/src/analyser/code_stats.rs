@@ -0,0 +1,228 @@
+use scie_detector::framework_detector::FrameworkDetector;
+use scie_model::artifact::CodeFile;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct LineStats {
+    pub files: usize,
+    pub lines: usize,
+    pub code: usize,
+    pub comments: usize,
+    pub blanks: usize,
+}
+
+impl LineStats {
+    /// like tokei and cloc, a line is comment when all of its tokens (except whitespaces) are
+    /// `comment.*`, so `int a; // comment` is a code line
+    pub fn from_file(file: &CodeFile) -> LineStats {
+        let mut lines: BTreeMap<i32, Option<bool>> = BTreeMap::new();
+        for element in file.elements.iter() {
+            let line = lines.entry(element.line_num).or_insert(None);
+            if element.value.trim().is_empty() {
+                continue;
+            }
+
+            let is_comment = element
+                .scopes
+                .iter()
+                .any(|scope| scope.starts_with("comment"));
+            *line = Some(line.unwrap_or(true) && is_comment);
+        }
+
+        let mut stats = LineStats {
+            files: 1,
+            ..Default::default()
+        };
+        // the trailing blank lines have no tokens, the lines of file are by the line breaks
+        let last_line = lines
+            .keys()
+            .last()
+            .cloned()
+            .unwrap_or(0)
+            .max(file.line_breaks.lines);
+        for line_num in 1..=last_line {
+            match lines.get(&line_num).cloned().flatten() {
+                None => stats.blanks += 1,
+                Some(true) => stats.comments += 1,
+                Some(false) => stats.code += 1,
+            }
+        }
+        stats.lines = last_line as usize;
+
+        stats
+    }
+
+    pub fn add(&mut self, other: &LineStats) {
+        self.files += other.files;
+        self.lines += other.lines;
+        self.code += other.code;
+        self.comments += other.comments;
+        self.blanks += other.blanks;
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct CodeStats {
+    pub total: LineStats,
+    pub languages: BTreeMap<String, LineStats>,
+    pub directories: BTreeMap<String, LineStats>,
+    pub modules: BTreeMap<String, LineStats>,
+}
+
+impl CodeStats {
+    /// the directories and modules are relative to `root`, modules are the content roots of detector
+    pub fn from_files(root: &Path, files: &[CodeFile], detector: &FrameworkDetector) -> CodeStats {
+        let mut stats = CodeStats::default();
+        for file in files {
+            let file_stats = LineStats::from_file(file);
+            let path = Path::new(&file.path);
+
+            let directory = path
+                .parent()
+                .map(|dir| CodeStats::relative(root, dir))
+                .unwrap_or_else(|| ".".to_string());
+            let module = match detector.content_root_of(path) {
+                Some(content_root) => CodeStats::relative(root, Path::new(content_root.root())),
+                None => ".".to_string(),
+            };
+
+            stats.total.add(&file_stats);
+            let language = CodeStats::language(file);
+            stats
+                .languages
+                .entry(language)
+                .or_default()
+                .add(&file_stats);
+            stats
+                .directories
+                .entry(directory)
+                .or_default()
+                .add(&file_stats);
+            stats.modules.entry(module).or_default().add(&file_stats);
+        }
+
+        stats
+    }
+
+    /// the rows of `FormatOutput::table`, with the total at the end
+    pub fn rows(group: &BTreeMap<String, LineStats>, total: &LineStats) -> Vec<Vec<String>> {
+        let mut rows: Vec<Vec<String>> = group
+            .iter()
+            .map(|(name, stats)| CodeStats::row(name, stats))
+            .collect();
+        rows.push(CodeStats::row("Total", total));
+        rows
+    }
+
+    pub fn headers(name: &str) -> Vec<&str> {
+        vec![name, "files", "lines", "code", "comments", "blanks"]
+    }
+
+    fn row(name: &str, stats: &LineStats) -> Vec<String> {
+        vec![
+            name.to_string(),
+            stats.files.to_string(),
+            stats.lines.to_string(),
+            stats.code.to_string(),
+            stats.comments.to_string(),
+            stats.blanks.to_string(),
+        ]
+    }
+
+    /// by the root scope of grammar, like `source.java` and `source.rust`
+    fn language(file: &CodeFile) -> String {
+        let scope = file
+            .elements
+            .first()
+            .and_then(|element| element.scopes.first())
+            .map(|scope| scope.as_str())
+            .unwrap_or("unknown");
+
+        scope
+            .trim_start_matches("source.")
+            .trim_start_matches("text.")
+            .to_string()
+    }
+
+    fn relative(root: &Path, dir: &Path) -> String {
+        let relative = dir.strip_prefix(root).unwrap_or(dir).display().to_string();
+        if relative.is_empty() {
+            ".".to_string()
+        } else {
+            relative
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyser::code_stats::{CodeStats, LineStats};
+    use crate::analyser::Identify;
+    use scie_detector::framework_detector::FrameworkDetector;
+    use scie_model::artifact::{CodeFile, LineBreaks};
+    use std::path::{Path, PathBuf};
+
+    fn java_file(path: &str, code: &str) -> CodeFile {
+        let lang = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("extensions")
+            .join("java")
+            .join("syntaxes")
+            .join("java.tmLanguage.json");
+
+        let mut file = CodeFile::new(PathBuf::from(path));
+        file.elements = Identify::identify_file(lang, code.to_string());
+        file.line_breaks = LineBreaks::from_code(code);
+        file
+    }
+
+    #[test]
+    fn should_count_code_comment_and_blank_lines() {
+        let code = "/**
+ * Hello
+ */
+class Hello {
+
+    // entry
+    int a = 1; // inline
+}";
+        let stats = LineStats::from_file(&java_file("Hello.java", code));
+
+        assert_eq!(8, stats.lines);
+        assert_eq!(3, stats.code);
+        assert_eq!(4, stats.comments);
+        assert_eq!(1, stats.blanks);
+    }
+
+    #[test]
+    fn should_count_trailing_blank_lines() {
+        let mut file = java_file("Hello.java", "class Hello {}\n\n\n");
+        // like the lexical analyser, which has no tokens of the blank lines
+        file.elements.retain(|element| !element.value.is_empty());
+        let stats = LineStats::from_file(&file);
+
+        assert_eq!(3, stats.lines);
+        assert_eq!(1, stats.code);
+        assert_eq!(2, stats.blanks);
+    }
+
+    #[test]
+    fn should_group_by_language_and_directory() {
+        let files = vec![
+            java_file("demo/src/A.java", "class A {}"),
+            java_file("demo/src/b/B.java", "// b\nclass B {}"),
+        ];
+        let detector = FrameworkDetector::new();
+        let stats = CodeStats::from_files(Path::new("demo"), &files, &detector);
+
+        assert_eq!(2, stats.total.files);
+        assert_eq!(2, stats.languages["java"].code);
+        assert_eq!(1, stats.directories["src/b"].comments);
+        assert_eq!(3, stats.modules["."].lines);
+
+        let rows = CodeStats::rows(&stats.languages, &stats.total);
+        assert_eq!(vec!["Total", "2", "3", "2", "1", "0"], rows[1]);
+    }
+}
@@ -0,0 +1,831 @@
+use scie_model::artifact::{CodeElement, CodeElementKind, TokenElement};
+use std::collections::BTreeMap;
+
+const MODIFIERS: [&str; 23] = [
+    "public",
+    "private",
+    "protected",
+    "internal",
+    "static",
+    "final",
+    "abstract",
+    "synchronized",
+    "native",
+    "transient",
+    "volatile",
+    "default",
+    "sealed",
+    "override",
+    "readonly",
+    "export",
+    "async",
+    "pub",
+    "unsafe",
+    "extern",
+    "const",
+    "inline",
+    "virtual",
+];
+
+// the scopes of names which are definitions without keyword, like methods of Java and functions of C
+const DEFINITION_SCOPES: [&str; 4] = [
+    "meta.method.identifier",
+    "meta.function.definition",
+    "meta.definition.method",
+    "meta.definition.function",
+];
+
+/// the import like statements, which name is the text until the end of statement
+struct Statement {
+    kind: CodeElementKind,
+    line_num: i32,
+    name: String,
+    // the content of strings, like `'fs'` of `import * as fs from 'fs'`
+    source: String,
+    modifiers: Vec<String>,
+    is_name_done: bool,
+    // the names are scoped by grammar, like `entity.name.import.go`
+    is_named_by_token: bool,
+}
+
+struct Entry {
+    element: CodeElement,
+    has_body: bool,
+    parent: Option<usize>,
+}
+
+#[derive(Default)]
+struct ExtractState {
+    entries: Vec<Entry>,
+    depth: usize,
+    parens: usize,
+    // the entries and the brace depth of their bodies
+    bodies: Vec<(usize, usize)>,
+    // the entries which are waiting for `{` or `;`
+    awaiting: Vec<usize>,
+    pending: Option<CodeElementKind>,
+    modifiers: Vec<String>,
+    receiver: Option<String>,
+    statement: Option<Statement>,
+    line_has_code: bool,
+    is_python: bool,
+    is_go: bool,
+    is_c: bool,
+}
+
+/// extracts `CodeElement`s from the scopes of tokens, like `entity.name.type.class`,
+/// `entity.name.function` and `meta.import`. The ranges are from the braces, or from the
+/// indentation of Python.
+pub struct ElementExtractor {}
+
+impl ElementExtractor {
+    pub fn extract(elements: &[TokenElement]) -> Vec<CodeElement> {
+        let root = elements
+            .first()
+            .and_then(|element| element.scopes.first())
+            .map(|scope| scope.as_str())
+            .unwrap_or("");
+        let mut state = ExtractState {
+            is_python: root == "source.python",
+            is_go: root == "source.go",
+            is_c: root == "source.c" || root == "source.cpp",
+            ..Default::default()
+        };
+
+        let mut indents: BTreeMap<i32, usize> = BTreeMap::new();
+        let mut current_line = elements
+            .first()
+            .map(|element| element.line_num)
+            .unwrap_or(0);
+        for element in elements {
+            if element.line_num != current_line {
+                state.end_line();
+                current_line = element.line_num;
+            }
+
+            if ElementExtractor::is_code(element) && !element.value.trim().is_empty() {
+                let leading = element.value.len() - element.value.trim_start().len();
+                indents
+                    .entry(element.line_num)
+                    .or_insert(element.start_index as usize + leading);
+            }
+            state.process(element);
+        }
+        state.end_line();
+
+        let last_line = elements.last().map(|element| element.line_num).unwrap_or(0);
+        state.finish(last_line, &indents)
+    }
+
+    /// not comments, or the continuation lines of strings
+    fn is_code(element: &TokenElement) -> bool {
+        !ElementExtractor::has(element, "comment")
+            && (!ElementExtractor::has(element, "string")
+                || ElementExtractor::has(element, "punctuation.definition.string.begin"))
+    }
+
+    pub(crate) fn has(element: &TokenElement, prefix: &str) -> bool {
+        element
+            .scopes
+            .iter()
+            .skip(1)
+            .flat_map(|scope| scope.split_whitespace())
+            .any(|scope| scope.starts_with(prefix))
+    }
+
+    fn is_keyword(element: &TokenElement) -> bool {
+        ElementExtractor::has(element, "keyword") || ElementExtractor::has(element, "storage")
+    }
+
+    fn keyword_kind(keyword: &str) -> Option<CodeElementKind> {
+        match keyword {
+            "mod" | "module" | "namespace" => Some(CodeElementKind::Module),
+            "class" => Some(CodeElementKind::Class),
+            "interface" => Some(CodeElementKind::Interface),
+            "enum" => Some(CodeElementKind::Enum),
+            "struct" | "union" => Some(CodeElementKind::Struct),
+            "trait" => Some(CodeElementKind::Trait),
+            "impl" => Some(CodeElementKind::Impl),
+            "type" => Some(CodeElementKind::Type),
+            "fn" | "func" | "def" | "function" => Some(CodeElementKind::Function),
+            _ => None,
+        }
+    }
+
+    fn statement_kind(element: &TokenElement, keyword: &str) -> Option<CodeElementKind> {
+        match keyword {
+            "package" => Some(CodeElementKind::Package),
+            "import" | "use" | "include" => Some(CodeElementKind::Import),
+            // `from x import y` of Python, not `raise x from y`
+            "from" if ElementExtractor::has(element, "keyword.control.import") => {
+                Some(CodeElementKind::Import)
+            }
+            _ => None,
+        }
+    }
+
+    fn identifier(text: &str) -> Option<String> {
+        let identifier: String = text
+            .trim_start()
+            .chars()
+            .take_while(|char| char.is_alphanumeric() || *char == '_')
+            .collect();
+        if identifier.is_empty() {
+            None
+        } else {
+            Some(identifier)
+        }
+    }
+}
+
+impl ExtractState {
+    fn process(&mut self, element: &TokenElement) {
+        if ElementExtractor::has(element, "comment") {
+            return;
+        }
+
+        if ElementExtractor::has(element, "string") {
+            if ElementExtractor::has(element, "entity.name.import") {
+                self.add_import(element);
+            }
+            if let Some(statement) = self.statement.as_mut() {
+                statement.source.push_str(&element.value);
+            }
+            return;
+        }
+
+        if self.statement.is_some() {
+            self.process_statement(element);
+            return;
+        }
+
+        let value = element.value.trim();
+        if ElementExtractor::is_keyword(element) {
+            if let Some(kind) = ElementExtractor::statement_kind(element, value) {
+                self.statement = Some(Statement {
+                    kind,
+                    line_num: element.line_num,
+                    name: String::new(),
+                    source: String::new(),
+                    modifiers: std::mem::take(&mut self.modifiers),
+                    is_name_done: false,
+                    is_named_by_token: false,
+                });
+                return;
+            }
+
+            if value == "constructor" {
+                self.create(CodeElementKind::Method, value, element.line_num);
+                return;
+            }
+
+            if let Some(kind) = ElementExtractor::keyword_kind(value) {
+                if !self.refine_type(kind, element.line_num) && !self.line_has_code {
+                    self.pending = Some(kind);
+                }
+                return;
+            }
+
+            if MODIFIERS.contains(&value) {
+                self.modifiers.push(value.to_string());
+                return;
+            }
+        }
+
+        if let Some(kind) = self.name_kind(element) {
+            self.create(kind, value, element.line_num);
+        } else if let Some(kind) = self.fallback_name_kind(element) {
+            if let Some(name) = ElementExtractor::identifier(&element.value) {
+                self.create(kind, &name, element.line_num);
+            }
+        } else if self.pending == Some(CodeElementKind::Function) && self.parens > 0 {
+            // the receiver of methods in Go, like `func (s *Server) Start()`
+            if let Some(name) = ElementExtractor::identifier(&element.value) {
+                self.receiver = Some(name);
+            }
+        } else if ExtractState::is_field(element) && self.parens == 0 {
+            self.create(CodeElementKind::Field, value, element.line_num);
+        }
+
+        if !ElementExtractor::is_keyword(element) && value.chars().any(char::is_alphanumeric) {
+            self.line_has_code = true;
+        }
+        self.process_punctuations(element);
+    }
+
+    /// the kind of names which are scoped by grammar
+    fn name_kind(&self, element: &TokenElement) -> Option<CodeElementKind> {
+        let is_name = ElementExtractor::has(element, "entity.name.type")
+            || ElementExtractor::has(element, "entity.name.function");
+        if !is_name
+            || self.parens > 0
+            || ElementExtractor::has(element, "meta.function-call")
+            || ElementExtractor::has(element, "meta.method-call")
+        {
+            return None;
+        }
+
+        if self.pending.is_some() {
+            return self.pending;
+        }
+
+        let is_definition = ElementExtractor::has(element, "entity.name.function")
+            && DEFINITION_SCOPES
+                .iter()
+                .any(|scope| ElementExtractor::has(element, scope));
+        if is_definition {
+            Some(CodeElementKind::Function)
+        } else {
+            None
+        }
+    }
+
+    /// the names which are not scoped by some grammars, like `mod tests` of Rust, `struct point`
+    /// of C and `def __init__` of Python
+    fn fallback_name_kind(&self, element: &TokenElement) -> Option<CodeElementKind> {
+        let is_plain =
+            element.scopes.len() <= 1 || ElementExtractor::has(element, "support.function");
+        if self.parens == 0 && is_plain {
+            self.pending
+        } else {
+            None
+        }
+    }
+
+    fn is_field(element: &TokenElement) -> bool {
+        (ElementExtractor::has(element, "meta.definition.variable")
+            && ElementExtractor::has(element, "variable.other.definition"))
+            || ElementExtractor::has(element, "meta.definition.property")
+    }
+
+    /// `type Server struct` of Go
+    fn refine_type(&mut self, kind: CodeElementKind, line_num: i32) -> bool {
+        if kind != CodeElementKind::Struct && kind != CodeElementKind::Interface {
+            return false;
+        }
+
+        match self.awaiting.last() {
+            Some(index) => {
+                let element = &mut self.entries[*index].element;
+                if element.kind == CodeElementKind::Type && element.range.start == line_num {
+                    element.kind = kind;
+                    true
+                } else {
+                    false
+                }
+            }
+            None => false,
+        }
+    }
+
+    fn process_statement(&mut self, element: &TokenElement) {
+        let value = element.value.trim();
+        let statement = self.statement.as_mut().unwrap();
+        if ElementExtractor::is_keyword(element) && MODIFIERS.contains(&value) {
+            statement.modifiers.push(value.to_string());
+            return;
+        }
+        if ElementExtractor::is_keyword(element) && (value == "import" || value == "from") {
+            statement.is_name_done = !statement.name.trim().is_empty();
+            return;
+        }
+
+        let (text, is_end) = match element.value.find(';') {
+            Some(index) => (&element.value[..index], true),
+            None => (element.value.as_str(), false),
+        };
+        if !statement.is_name_done {
+            statement.name.push_str(text);
+        }
+        if is_end {
+            self.end_statement();
+        }
+    }
+
+    fn end_statement(&mut self) {
+        let statement = match self.statement.take() {
+            Some(statement) => statement,
+            None => return,
+        };
+        if statement.is_named_by_token {
+            return;
+        }
+
+        let name = if statement.source.trim().is_empty() {
+            statement.name
+        } else {
+            statement.source
+        };
+        let name = name
+            .trim()
+            .trim_matches(|char| "\"'<>(".contains(char))
+            .trim();
+        if name.is_empty() {
+            return;
+        }
+
+        let mut code_element = CodeElement::new(statement.kind, name, statement.line_num);
+        code_element.modifiers = statement.modifiers;
+        self.entries.push(Entry {
+            element: code_element,
+            has_body: false,
+            parent: None,
+        });
+    }
+
+    fn add_import(&mut self, element: &TokenElement) {
+        if let Some(statement) = self.statement.as_mut() {
+            statement.is_named_by_token = true;
+        }
+        let name = element.value.trim().trim_matches('"');
+        self.entries.push(Entry {
+            element: CodeElement::new(CodeElementKind::Import, name, element.line_num),
+            has_body: false,
+            parent: None,
+        });
+    }
+
+    fn create(&mut self, kind: CodeElementKind, name: &str, line_num: i32) {
+        self.close_awaiting();
+
+        let mut element = CodeElement::new(kind, name, line_num);
+        element.modifiers = std::mem::take(&mut self.modifiers);
+        if let Some(receiver) = self.receiver.take() {
+            element.kind = CodeElementKind::Method;
+            element.parent = Some(receiver);
+        }
+
+        self.entries.push(Entry {
+            element,
+            has_body: false,
+            parent: None,
+        });
+        if !self.is_python {
+            self.awaiting.push(self.entries.len() - 1);
+        }
+        self.pending = None;
+        self.line_has_code = true;
+    }
+
+    fn process_punctuations(&mut self, element: &TokenElement) {
+        for char in element.value.chars() {
+            match char {
+                '(' => self.parens += 1,
+                ')' => self.parens = self.parens.saturating_sub(1),
+                '{' if self.parens == 0 => {
+                    self.depth += 1;
+                    for index in self.awaiting.drain(..) {
+                        self.entries[index].has_body = true;
+                        self.bodies.push((index, self.depth));
+                    }
+                    self.pending = None;
+                    self.modifiers.clear();
+                }
+                '}' if self.parens == 0 => {
+                    self.close_awaiting();
+                    self.depth = self.depth.saturating_sub(1);
+                    while let Some((index, depth)) = self.bodies.last().cloned() {
+                        if depth <= self.depth {
+                            break;
+                        }
+                        self.entries[index].element.range.end = element.line_num;
+                        self.bodies.pop();
+                    }
+                }
+                ';' if self.parens == 0 => {
+                    for index in self.awaiting.drain(..) {
+                        self.entries[index].element.range.end = element.line_num;
+                    }
+                    self.pending = None;
+                    self.modifiers.clear();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// the declarations which end without body or `;`, like `type Id int` of Go
+    fn close_awaiting(&mut self) {
+        self.awaiting.clear();
+    }
+
+    fn end_line(&mut self) {
+        self.end_statement();
+        self.pending = None;
+        self.modifiers.clear();
+        self.receiver = None;
+        self.line_has_code = false;
+
+        // the open brace must be at the same line in Go, and the types and fields end at the line
+        // when there is no `;`, like `type Id = string` of TypeScript
+        if self.is_go {
+            self.close_awaiting();
+        } else {
+            let entries = &self.entries;
+            self.awaiting.retain(|index| {
+                let kind = entries[*index].element.kind;
+                kind != CodeElementKind::Type && kind != CodeElementKind::Field
+            });
+        }
+    }
+
+    fn finish(mut self, last_line: i32, indents: &BTreeMap<i32, usize>) -> Vec<CodeElement> {
+        for (index, _) in self.bodies.drain(..) {
+            self.entries[index].element.range.end = last_line;
+        }
+
+        if self.is_python {
+            for entry in self.entries.iter_mut() {
+                let kind = entry.element.kind;
+                if kind != CodeElementKind::Import && kind != CodeElementKind::Package {
+                    entry.element.range.end =
+                        ExtractState::indent_end(entry.element.range.start, indents);
+                }
+            }
+        }
+
+        // the innermost element which contains it
+        for index in 0..self.entries.len() {
+            if self.entries[index].element.parent.is_some() {
+                continue;
+            }
+            let range = self.entries[index].element.range.clone();
+            let parent = (0..index).rev().find(|parent| {
+                let candidate = &self.entries[*parent].element;
+                let is_container = !matches!(
+                    candidate.kind,
+                    CodeElementKind::Package | CodeElementKind::Import | CodeElementKind::Field
+                );
+                is_container
+                    && candidate.range.start <= range.start
+                    && candidate.range.end >= range.end
+                    && (candidate.range.start < range.start || self.entries[*parent].has_body)
+            });
+            self.entries[index].parent = parent;
+        }
+
+        let mut code_elements = vec![];
+        for index in 0..self.entries.len() {
+            let parent = self.entries[index]
+                .parent
+                .map(|parent| &self.entries[parent]);
+            let parent_kind = parent.map(|parent| parent.element.kind);
+            let entry = &self.entries[index];
+            let mut element = entry.element.clone();
+            if let Some(parent) = parent {
+                element.parent = Some(parent.element.name.clone());
+            }
+
+            let is_type_member = parent_kind.map(|kind| kind.is_type()).unwrap_or(false);
+            match element.kind {
+                CodeElementKind::Function if is_type_member => {
+                    element.kind = CodeElementKind::Method
+                }
+                // the local variables
+                CodeElementKind::Field if !is_type_member => continue,
+                // the usages of types in C, like `struct point p;`
+                CodeElementKind::Struct | CodeElementKind::Enum if self.is_c && !entry.has_body => {
+                    continue
+                }
+                _ => {}
+            }
+            code_elements.push(element);
+        }
+
+        code_elements
+    }
+
+    /// the last line before the first line which indentation is not greater than the start
+    fn indent_end(start: i32, indents: &BTreeMap<i32, usize>) -> i32 {
+        let indent = match indents.get(&start) {
+            Some(indent) => *indent,
+            None => return start,
+        };
+
+        let mut end = start;
+        for (line_num, line_indent) in indents.range(start + 1..) {
+            if *line_indent <= indent {
+                break;
+            }
+            end = *line_num;
+        }
+        end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyser::{ElementExtractor, Identify};
+    use scie_model::artifact::{CodeElement, CodeElementKind, LineRange};
+    use std::path::PathBuf;
+
+    fn extract(grammar: &str, code: &str) -> Vec<CodeElement> {
+        let lang = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("extensions")
+            .join(grammar);
+
+        ElementExtractor::extract(&Identify::identify_file(lang, code.to_string()))
+    }
+
+    fn find<'a>(elements: &'a [CodeElement], name: &str) -> &'a CodeElement {
+        elements
+            .iter()
+            .find(|element| element.name == name)
+            .unwrap_or_else(|| panic!("{} not found in {:?}", name, elements))
+    }
+
+    #[test]
+    fn should_extract_java_elements() {
+        let code = "package com.phodal.demo;
+
+import java.util.List;
+import static org.junit.Assert.*;
+
+public abstract class Demo<T> extends Base implements Runnable {
+    private static final int MAX = 10;
+
+    public Demo(String name) {
+        int local = 1;
+    }
+
+    @Override
+    public synchronized List<String> getNames(int limit) throws IOException {
+        return names;
+    }
+
+    abstract void run();
+
+    interface Inner {
+        void call();
+    }
+
+    enum Color { RED, GREEN }
+}";
+        let elements = extract("java/syntaxes/java.tmLanguage.json", code);
+
+        assert_eq!(CodeElementKind::Package, elements[0].kind);
+        assert_eq!("com.phodal.demo", elements[0].name);
+        assert_eq!("java.util.List", elements[1].name);
+        assert_eq!(vec!["static"], elements[2].modifiers);
+
+        let class = find(&elements, "Demo");
+        assert_eq!(CodeElementKind::Class, class.kind);
+        assert_eq!(LineRange::new(6, 25), class.range);
+        assert_eq!(vec!["public", "abstract"], class.modifiers);
+
+        let field = find(&elements, "MAX");
+        assert_eq!(CodeElementKind::Field, field.kind);
+        assert_eq!(vec!["private", "static", "final"], field.modifiers);
+        assert!(elements.iter().all(|element| element.name != "local"));
+
+        let method = find(&elements, "getNames");
+        assert_eq!(CodeElementKind::Method, method.kind);
+        assert_eq!(LineRange::new(14, 16), method.range);
+        assert_eq!(Some("Demo".to_string()), method.parent);
+        assert_eq!(LineRange::new(18, 18), find(&elements, "run").range);
+
+        assert_eq!(CodeElementKind::Interface, find(&elements, "Inner").kind);
+        assert_eq!(Some("Inner".to_string()), find(&elements, "call").parent);
+        assert_eq!(LineRange::new(24, 24), find(&elements, "Color").range);
+    }
+
+    #[test]
+    fn should_extract_go_elements() {
+        let code = "package main
+
+import (
+\t\"fmt\"
+)
+
+type Server struct {
+\tName string
+}
+
+type Id int
+
+func (s *Server) Start(port int) error {
+\tfmt.Println(\"start\")
+\treturn nil
+}
+
+func main() {
+}";
+        let elements = extract("go/syntaxes/go.tmLanguage.json", code);
+
+        assert_eq!(CodeElementKind::Package, elements[0].kind);
+        assert_eq!("main", elements[0].name);
+        assert_eq!(CodeElementKind::Import, elements[1].kind);
+        assert_eq!("fmt", elements[1].name);
+
+        let server = find(&elements, "Server");
+        assert_eq!(CodeElementKind::Struct, server.kind);
+        assert_eq!(LineRange::new(7, 9), server.range);
+        assert_eq!(LineRange::new(11, 11), find(&elements, "Id").range);
+
+        let start = find(&elements, "Start");
+        assert_eq!(CodeElementKind::Method, start.kind);
+        assert_eq!(Some("Server".to_string()), start.parent);
+        assert_eq!(LineRange::new(13, 16), start.range);
+        assert_eq!(CodeElementKind::Function, elements.last().unwrap().kind);
+    }
+
+    #[test]
+    fn should_extract_rust_elements() {
+        let code = "use std::collections::HashMap;
+
+pub struct Server {
+    name: String,
+}
+
+pub trait Handler {
+    fn serve(&self) -> bool;
+}
+
+impl Server {
+    pub fn new(name: &str) -> Server {
+        Server { name: name.to_string() }
+    }
+}
+
+mod tests {
+    fn helper() {}
+}";
+        let elements = extract("rust/syntaxes/rust.tmLanguage.json", code);
+
+        assert_eq!("std::collections::HashMap", elements[0].name);
+        assert_eq!(LineRange::new(3, 5), find(&elements, "Server").range);
+        assert_eq!(CodeElementKind::Trait, find(&elements, "Handler").kind);
+        assert_eq!(CodeElementKind::Method, find(&elements, "serve").kind);
+
+        let new = find(&elements, "new");
+        assert_eq!(CodeElementKind::Method, new.kind);
+        assert_eq!(vec!["pub"], new.modifiers);
+        assert_eq!(LineRange::new(12, 14), new.range);
+        assert!(elements.iter().all(|element| element.name != "to_string"));
+
+        let tests = find(&elements, "tests");
+        assert_eq!(CodeElementKind::Module, tests.kind);
+        assert_eq!(LineRange::new(17, 19), tests.range);
+        let helper = find(&elements, "helper");
+        assert_eq!(CodeElementKind::Function, helper.kind);
+        assert_eq!(Some("tests".to_string()), helper.parent);
+    }
+
+    #[test]
+    fn should_extract_c_elements() {
+        let code = "#include <stdio.h>
+
+struct point {
+    int x;
+};
+
+typedef struct point Point;
+
+static int add(int a, int b) {
+    return a + b;
+}
+
+int main(void) {
+    struct point p;
+    return add(1, 2);
+}";
+        let elements = extract("cpp/syntaxes/c.tmLanguage.json", code);
+
+        assert_eq!("stdio.h", elements[0].name);
+        assert_eq!(LineRange::new(3, 5), find(&elements, "point").range);
+        assert_eq!(
+            1,
+            elements
+                .iter()
+                .filter(|element| element.name == "point")
+                .count()
+        );
+
+        let add = find(&elements, "add");
+        assert_eq!(CodeElementKind::Function, add.kind);
+        assert_eq!(vec!["static"], add.modifiers);
+        assert_eq!(LineRange::new(9, 11), add.range);
+        assert_eq!(LineRange::new(13, 16), find(&elements, "main").range);
+    }
+
+    #[test]
+    fn should_extract_python_elements() {
+        let code = "import os
+from typing import List
+
+class Server(Base):
+    def __init__(self, name):
+        self.name = name
+
+    @staticmethod
+    async def start(port: int) -> None:
+        pass
+
+def main():
+    pass
+";
+        let elements = extract("python/syntaxes/MagicPython.tmLanguage.json", code);
+
+        assert_eq!("os", elements[0].name);
+        assert_eq!("typing", elements[1].name);
+        assert_eq!(LineRange::new(4, 10), find(&elements, "Server").range);
+
+        let init = find(&elements, "__init__");
+        assert_eq!(CodeElementKind::Method, init.kind);
+        assert_eq!(LineRange::new(5, 6), init.range);
+
+        let start = find(&elements, "start");
+        assert_eq!(Some("Server".to_string()), start.parent);
+        assert_eq!(vec!["async"], start.modifiers);
+        assert_eq!(LineRange::new(12, 13), find(&elements, "main").range);
+        assert_eq!(None, find(&elements, "main").parent);
+    }
+
+    #[test]
+    fn should_extract_typescript_elements() {
+        let code = "import { Component } from '@angular/core';
+
+export interface Shape {
+    area(): number;
+}
+
+export abstract class Circle implements Shape {
+    private radius: number;
+
+    constructor(radius: number) {
+        this.radius = radius;
+    }
+
+    public async area(): Promise<number> {
+        return 1;
+    }
+}
+
+export function main(args: string[]): void {
+}";
+        let elements = extract(
+            "typescript-basics/syntaxes/TypeScript.tmLanguage.json",
+            code,
+        );
+
+        assert_eq!("@angular/core", elements[0].name);
+        assert_eq!(CodeElementKind::Interface, find(&elements, "Shape").kind);
+
+        let circle = find(&elements, "Circle");
+        assert_eq!(vec!["export", "abstract"], circle.modifiers);
+        assert_eq!(LineRange::new(7, 17), circle.range);
+        assert_eq!(CodeElementKind::Field, find(&elements, "radius").kind);
+        assert_eq!(LineRange::new(10, 12), find(&elements, "constructor").range);
+
+        let area = elements
+            .iter()
+            .find(|element| element.name == "area" && element.parent == Some("Circle".to_string()))
+            .unwrap();
+        assert_eq!(CodeElementKind::Method, area.kind);
+        assert_eq!(vec!["public", "async"], area.modifiers);
+        assert!(elements.iter().all(|element| element.name != "Promise"));
+        assert_eq!(CodeElementKind::Function, find(&elements, "main").kind);
+    }
+}
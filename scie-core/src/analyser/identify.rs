@@ -14,14 +14,11 @@ impl Identify {
         let mut line_num = 1;
         for line in code.lines() {
             let result = grammar.tokenize_line(line, &mut rule_stack);
+            let units: Vec<u16> = line.encode_utf16().collect();
             for token in result.tokens {
                 let start = token.start_index;
                 let end = token.end_index;
-                let text: String = String::from(line)
-                    .chars()
-                    .skip(start as usize)
-                    .take((end - start) as usize)
-                    .collect();
+                let text = TokenElement::utf16_value(&units, start, end);
 
                 let element = TokenElement::new(line_num, start, end, text, token.scopes);
                 elements.push(element);
@@ -0,0 +1,363 @@
+use scie_bingen::language_gen::LangExtGen;
+use scie_model::artifact::TokenElement;
+use scie_model::LanguageConfig;
+use std::fs;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct BracketMismatch {
+    pub bracket: String,
+    pub line_num: i32,
+    pub start_index: i32,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct LexicalSummary {
+    pub line_comments: usize,
+    pub block_comments: usize,
+    pub comment_lines: usize,
+    pub max_depth: usize,
+    // the bracket nesting depth at the start of each line
+    pub line_depths: Vec<usize>,
+    pub unclosed: Vec<BracketMismatch>,
+    pub unexpected: Vec<BracketMismatch>,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum LexicalKind {
+    Code,
+    LineComment,
+    BlockComment,
+    Str,
+    Bracket,
+}
+
+/// the fallback of grammar, only comments, strings and brackets from `language-configuration.json`
+/// are known, so the scopes of tokens are `comment.line.*`, `string.quoted.*` and so on.
+pub struct LexicalAnalyser {
+    lang: String,
+    line_comment: Option<Vec<char>>,
+    block_comment: Option<(Vec<char>, Vec<char>)>,
+    brackets: Vec<(Vec<char>, Vec<char>)>,
+    quotes: Vec<char>,
+}
+
+impl LexicalAnalyser {
+    pub fn new(lang: &str, config: &LanguageConfig) -> LexicalAnalyser {
+        let chars = |text: &str| text.chars().collect::<Vec<char>>();
+        LexicalAnalyser {
+            lang: lang.to_string(),
+            line_comment: config.line_comment().map(chars),
+            block_comment: config
+                .block_comment()
+                .map(|(open, close)| (chars(open), chars(close))),
+            brackets: config
+                .bracket_pairs()
+                .into_iter()
+                .filter(|(open, close)| open != close)
+                .map(|(open, close)| (chars(open), chars(close)))
+                .collect(),
+            quotes: config.string_quotes(),
+        }
+    }
+
+    /// by the `language-configuration.json` of extension which contributes the `ext`, like `.java`
+    pub fn by_ext(lang_ext: &LangExtGen, ext: &str) -> Option<LexicalAnalyser> {
+        let entry = lang_ext.ext_map.get(ext)?;
+        let code = fs::read_to_string(lang_ext.language_config_path(ext)?).ok()?;
+        let config = LanguageConfig::parse(&code).ok()?;
+
+        Some(LexicalAnalyser::new(&entry.name, &config))
+    }
+
+    pub fn tokenize(&self, code: &str) -> Vec<TokenElement> {
+        self.scan(code).0
+    }
+
+    pub fn analyse(&self, code: &str) -> LexicalSummary {
+        self.scan(code).1
+    }
+
+    fn scan(&self, code: &str) -> (Vec<TokenElement>, LexicalSummary) {
+        let mut elements = vec![];
+        let mut summary = LexicalSummary::default();
+        // the index of brackets, line and start of the open bracket
+        let mut stack: Vec<(usize, i32, i32)> = vec![];
+        let mut in_block = false;
+        let mut quote: Option<char> = None;
+
+        for (index, line) in code.lines().enumerate() {
+            let line_num = index as i32 + 1;
+            let chars: Vec<char> = line.chars().collect();
+            let mut segments: Vec<(usize, usize, LexicalKind)> = vec![];
+            let mut has_code = false;
+            let mut has_comment = false;
+            summary.line_depths.push(stack.len());
+
+            let mut i = 0;
+            while i < chars.len() {
+                if in_block {
+                    let close = &self.block_comment.as_ref().unwrap().1;
+                    let end = match LexicalAnalyser::find(&chars, i, close) {
+                        Some(found) => {
+                            in_block = false;
+                            found + close.len()
+                        }
+                        None => chars.len(),
+                    };
+                    LexicalAnalyser::push(&mut segments, i, end, LexicalKind::BlockComment);
+                    has_comment = true;
+                    i = end;
+                    continue;
+                }
+
+                if let Some(current) = quote {
+                    let (end, is_closed) = LexicalAnalyser::string_end(&chars, i, current);
+                    if is_closed {
+                        quote = None;
+                    }
+                    LexicalAnalyser::push(&mut segments, i, end, LexicalKind::Str);
+                    i = end;
+                    continue;
+                }
+
+                if let Some((open, _)) = &self.block_comment {
+                    if LexicalAnalyser::starts_with(&chars, i, open) {
+                        in_block = true;
+                        summary.block_comments += 1;
+                        let end = i + open.len();
+                        LexicalAnalyser::push(&mut segments, i, end, LexicalKind::BlockComment);
+                        has_comment = true;
+                        i = end;
+                        continue;
+                    }
+                }
+
+                if let Some(line_comment) = &self.line_comment {
+                    if LexicalAnalyser::starts_with(&chars, i, line_comment) {
+                        summary.line_comments += 1;
+                        LexicalAnalyser::push(
+                            &mut segments,
+                            i,
+                            chars.len(),
+                            LexicalKind::LineComment,
+                        );
+                        has_comment = true;
+                        break;
+                    }
+                }
+
+                if self.quotes.contains(&chars[i]) {
+                    quote = Some(chars[i]);
+                    LexicalAnalyser::push(&mut segments, i, i + 1, LexicalKind::Str);
+                    has_code = true;
+                    i += 1;
+                    continue;
+                }
+
+                if let Some(end) = self.match_bracket(&chars, i, line_num, &mut stack, &mut summary)
+                {
+                    LexicalAnalyser::push(&mut segments, i, end, LexicalKind::Bracket);
+                    has_code = true;
+                    i = end;
+                    continue;
+                }
+
+                has_code = has_code || !chars[i].is_whitespace();
+                LexicalAnalyser::push(&mut segments, i, i + 1, LexicalKind::Code);
+                i += 1;
+            }
+
+            // only the template strings of javascript can be multiple lines
+            if quote.is_some() && quote != Some('`') {
+                quote = None;
+            }
+            if has_comment && !has_code {
+                summary.comment_lines += 1;
+            }
+
+            for (start, end, kind) in segments {
+                let value: String = chars[start..end].iter().collect();
+                elements.push(TokenElement::new(
+                    line_num,
+                    start as i32,
+                    end as i32,
+                    value,
+                    self.scopes(kind),
+                ));
+            }
+        }
+
+        for (bracket, line_num, start_index) in stack {
+            summary.unclosed.push(BracketMismatch {
+                bracket: self.brackets[bracket].0.iter().collect(),
+                line_num,
+                start_index,
+            });
+        }
+
+        (elements, summary)
+    }
+
+    /// returns the end of bracket if there is a bracket at `index`; a close bracket closes the
+    /// nearest open one of the same pair, the open brackets between them are unclosed
+    fn match_bracket(
+        &self,
+        chars: &[char],
+        index: usize,
+        line_num: i32,
+        stack: &mut Vec<(usize, i32, i32)>,
+        summary: &mut LexicalSummary,
+    ) -> Option<usize> {
+        for (bracket, (open, close)) in self.brackets.iter().enumerate() {
+            if LexicalAnalyser::starts_with(chars, index, open) {
+                stack.push((bracket, line_num, index as i32));
+                summary.max_depth = summary.max_depth.max(stack.len());
+                return Some(index + open.len());
+            }
+
+            if LexicalAnalyser::starts_with(chars, index, close) {
+                match stack.iter().rposition(|(open, _, _)| *open == bracket) {
+                    Some(position) => {
+                        for (skipped, line, start) in stack.drain(position..).skip(1) {
+                            summary.unclosed.push(BracketMismatch {
+                                bracket: self.brackets[skipped].0.iter().collect(),
+                                line_num: line,
+                                start_index: start,
+                            });
+                        }
+                    }
+                    None => summary.unexpected.push(BracketMismatch {
+                        bracket: close.iter().collect(),
+                        line_num,
+                        start_index: index as i32,
+                    }),
+                }
+                return Some(index + close.len());
+            }
+        }
+
+        None
+    }
+
+    fn scopes(&self, kind: LexicalKind) -> Vec<String> {
+        let mut scopes = vec![format!("source.{}", self.lang)];
+        let scope = match kind {
+            LexicalKind::Code => return scopes,
+            LexicalKind::LineComment => "comment.line",
+            LexicalKind::BlockComment => "comment.block",
+            LexicalKind::Str => "string.quoted",
+            LexicalKind::Bracket => "punctuation.bracket",
+        };
+        scopes.push(format!("{}.{}", scope, self.lang));
+        scopes
+    }
+
+    /// merge with the previous segment of the same kind, except brackets
+    fn push(
+        segments: &mut Vec<(usize, usize, LexicalKind)>,
+        start: usize,
+        end: usize,
+        kind: LexicalKind,
+    ) {
+        if let Some(last) = segments.last_mut() {
+            if last.2 == kind && last.1 == start && kind != LexicalKind::Bracket {
+                last.1 = end;
+                return;
+            }
+        }
+        segments.push((start, end, kind));
+    }
+
+    /// the index after the closing quote, or the end of line; escaped quotes are skipped
+    fn string_end(chars: &[char], start: usize, quote: char) -> (usize, bool) {
+        let mut index = start;
+        while index < chars.len() {
+            if chars[index] == '\\' {
+                index += 2;
+                continue;
+            }
+            if chars[index] == quote {
+                return (index + 1, true);
+            }
+            index += 1;
+        }
+
+        (chars.len(), false)
+    }
+
+    fn starts_with(chars: &[char], index: usize, pattern: &[char]) -> bool {
+        !pattern.is_empty()
+            && chars.len() >= index + pattern.len()
+            && chars[index..index + pattern.len()] == *pattern
+    }
+
+    fn find(chars: &[char], from: usize, pattern: &[char]) -> Option<usize> {
+        (from..chars.len()).find(|index| LexicalAnalyser::starts_with(chars, *index, pattern))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyser::code_stats::LineStats;
+    use crate::analyser::LexicalAnalyser;
+    use scie_bingen::language_gen::LangExtGen;
+    use scie_model::artifact::CodeFile;
+    use std::path::PathBuf;
+
+    fn rust_analyser() -> LexicalAnalyser {
+        LexicalAnalyser::by_ext(&LangExtGen::default(), ".rs").unwrap()
+    }
+
+    #[test]
+    fn should_count_comments_and_nesting_depth() {
+        let code = "/* header
+ * comment */
+fn main() {
+    // the url is not a comment
+    let url = \"http://phodal.com\"; /* inline */
+    if true { println!(\"{}\", [1, 2][0]); }
+}";
+        let summary = rust_analyser().analyse(code);
+
+        assert_eq!(1, summary.line_comments);
+        assert_eq!(2, summary.block_comments);
+        assert_eq!(3, summary.comment_lines);
+        assert_eq!(4, summary.max_depth);
+        assert_eq!(vec![0, 0, 0, 1, 1, 1, 1], summary.line_depths);
+        assert!(summary.unclosed.is_empty() && summary.unexpected.is_empty());
+    }
+
+    #[test]
+    fn should_report_mismatched_brackets() {
+        let summary = rust_analyser().analyse("fn main() {\n    foo(1;\n}\n)");
+
+        assert_eq!(1, summary.unclosed.len());
+        assert_eq!("(", summary.unclosed[0].bracket);
+        assert_eq!(2, summary.unclosed[0].line_num);
+        assert_eq!(1, summary.unexpected.len());
+        assert_eq!(4, summary.unexpected[0].line_num);
+    }
+
+    #[test]
+    fn should_tokenize_with_comment_scopes_for_stats() {
+        let mut file = CodeFile::new(PathBuf::from("main.rs"));
+        file.elements = rust_analyser().tokenize("// main\n\nfn main() {}\n");
+        let stats = LineStats::from_file(&file);
+
+        assert_eq!("source.rust", file.elements[0].scopes[0]);
+        assert_eq!("comment.line.rust", file.elements[0].scopes[1]);
+        assert_eq!(1, stats.comments);
+        assert_eq!(1, stats.code);
+        assert_eq!(1, stats.blanks);
+    }
+
+    #[test]
+    fn should_parse_all_language_configurations() {
+        let lang_ext = LangExtGen::default();
+        for ext in lang_ext.ext_map.keys() {
+            if lang_ext.language_config_path(ext).is_some() {
+                assert!(LexicalAnalyser::by_ext(&lang_ext, ext).is_some(), "{}", ext);
+            }
+        }
+    }
+}
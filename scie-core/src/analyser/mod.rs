@@ -1,5 +1,15 @@
+pub mod analyse_options;
 pub mod analyser;
+pub mod code_stats;
+pub mod element_extractor;
 pub mod identify;
+pub mod lexical_analyser;
+pub mod test_classifier;
 
+pub use analyse_options::AnalyseOptions;
 pub use analyser::Analyser;
+pub use code_stats::{CodeStats, LineStats};
+pub use element_extractor::ElementExtractor;
 pub use identify::Identify;
+pub use lexical_analyser::{LexicalAnalyser, LexicalSummary};
+pub use test_classifier::{TestClassifier, TestSummary};
@@ -0,0 +1,406 @@
+use scie_detector::content_root::ContentRoot;
+use scie_model::artifact::{CodeFile, CodeFileKind, LineRange, TokenElement};
+use std::path::Path;
+
+// file name conventions of the test frameworks, see `ContentRoot::wildcard_match`
+const TEST_FILE_PATTERNS: [&str; 30] = [
+    "*_test.go",
+    "test_*.py",
+    "*_test.py",
+    "*.spec.*",
+    "*.test.*",
+    "*Test.java",
+    "*Tests.java",
+    "*IT.java",
+    "*Test.kt",
+    "*Tests.kt",
+    "*Test.groovy",
+    "*Spec.groovy",
+    "*Test.scala",
+    "*Spec.scala",
+    "*Test.cs",
+    "*Tests.cs",
+    "*_spec.rb",
+    "*_test.rb",
+    "*Test.php",
+    "*Tests.swift",
+    "*Test.swift",
+    "*_test.c",
+    "*_test.cc",
+    "*_test.cpp",
+    "*_unittest.cc",
+    "test_*.c",
+    "*.Tests.ps1",
+    "*_spec.lua",
+    "*_test.lua",
+    "*.t",
+];
+
+const TEST_DIR_NAMES: [&str; 5] = ["test", "tests", "__tests__", "spec", "specs"];
+
+// annotations and attributes of JUnit, TestNG, NUnit, xUnit and MSTest
+const TEST_ANNOTATIONS: [&str; 14] = [
+    "Test",
+    "ParameterizedTest",
+    "RepeatedTest",
+    "TestFactory",
+    "TestTemplate",
+    "RunWith",
+    "ExtendWith",
+    "SpringBootTest",
+    "Fact",
+    "Theory",
+    "TestCase",
+    "TestFixture",
+    "TestMethod",
+    "TestClass",
+];
+
+const JS_TEST_CALLS: [&str; 6] = [
+    "describe",
+    "it",
+    "test",
+    "expect",
+    "beforeEach",
+    "afterEach",
+];
+
+pub struct TestClassifier {}
+
+impl TestClassifier {
+    /// classify by the path conventions first, then by the tokens; the content root kind from
+    /// detector always wins. Files which are not tests may still contain inline tests.
+    pub fn classify(code_file: &mut CodeFile, relative: &Path) {
+        match code_file.kind {
+            CodeFileKind::Test | CodeFileKind::Generated | CodeFileKind::Resource => return,
+            _ => {}
+        }
+
+        if TestClassifier::is_test_path(relative)
+            || TestClassifier::has_test_signal(&code_file.elements)
+        {
+            code_file.kind = CodeFileKind::Test;
+            return;
+        }
+
+        code_file.test_ranges = TestClassifier::inline_test_ranges(&code_file.elements);
+    }
+
+    /// the `relative` path should be relative to the project, or the parent dirs of the project
+    /// like `/home/test` will be matched
+    pub fn is_test_path(relative: &Path) -> bool {
+        let file_name = relative
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+        if TEST_FILE_PATTERNS
+            .iter()
+            .any(|pattern| ContentRoot::wildcard_match(pattern, file_name))
+        {
+            return true;
+        }
+
+        relative
+            .parent()
+            .map(|parent| {
+                parent.components().any(|component| {
+                    let name = component.as_os_str().to_str().unwrap_or("");
+                    TEST_DIR_NAMES.contains(&name)
+                })
+            })
+            .unwrap_or(false)
+    }
+
+    pub fn has_test_signal(elements: &[TokenElement]) -> bool {
+        match TestClassifier::language_scope(elements) {
+            "source.go" => {
+                TestClassifier::imports(elements, "testing")
+                    && TestClassifier::any_function(elements, TestClassifier::is_go_test_name)
+            }
+            "source.python" => {
+                TestClassifier::imports(elements, "pytest")
+                    || TestClassifier::imports(elements, "unittest")
+                    || TestClassifier::any_function(elements, |name| name.starts_with("test_"))
+            }
+            "source.js" | "source.ts" | "source.tsx" | "source.jsx" => {
+                elements.iter().any(|element| {
+                    TestClassifier::has_scope(element, "meta.function-call")
+                        && TestClassifier::has_scope(element, "entity.name.function")
+                        && JS_TEST_CALLS.contains(&element.value.as_str())
+                })
+            }
+            "source.rust" => false,
+            _ => elements
+                .iter()
+                .enumerate()
+                .any(|(index, _)| TestClassifier::is_test_annotation(elements, index)),
+        }
+    }
+
+    /// the `#[cfg(test)]` modules and `#[test]` functions in rust
+    pub fn inline_test_ranges(elements: &[TokenElement]) -> Vec<LineRange> {
+        let mut ranges = vec![];
+        if TestClassifier::language_scope(elements) != "source.rust" {
+            return ranges;
+        }
+
+        let mut index = 0;
+        while index < elements.len() {
+            if !TestClassifier::has_scope(&elements[index], "meta.attribute") {
+                index += 1;
+                continue;
+            }
+
+            let start = elements[index].line_num;
+            let mut attribute = String::new();
+            while index < elements.len()
+                && TestClassifier::has_scope(&elements[index], "meta.attribute")
+            {
+                attribute.push_str(&elements[index].value);
+                index += 1;
+            }
+            if !TestClassifier::is_rust_test_attribute(&attribute) {
+                continue;
+            }
+
+            let end = TestClassifier::item_end_line(&elements[index..])
+                .unwrap_or_else(|| elements[elements.len() - 1].line_num);
+            ranges.push(LineRange::new(start, end));
+            while index < elements.len() && elements[index].line_num <= end {
+                index += 1;
+            }
+        }
+
+        ranges
+    }
+
+    fn is_rust_test_attribute(attribute: &str) -> bool {
+        let inner: String = attribute
+            .trim()
+            .trim_start_matches("#[")
+            .trim_end_matches(']')
+            .chars()
+            .filter(|char| !char.is_whitespace())
+            .collect();
+        let name = inner.split('(').next().unwrap_or("");
+
+        inner == "cfg(test)" || name == "test" || name.ends_with("::test")
+    }
+
+    /// the line of the matched `}`, or the `;` for items like `mod tests;`
+    fn item_end_line(elements: &[TokenElement]) -> Option<i32> {
+        let mut depth = 0;
+        for element in elements {
+            if TestClassifier::has_scope(element, "string")
+                || TestClassifier::has_scope(element, "comment")
+            {
+                continue;
+            }
+
+            for char in element.value.chars() {
+                match char {
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(element.line_num);
+                        }
+                    }
+                    ';' if depth == 0 => return Some(element.line_num),
+                    _ => {}
+                }
+            }
+        }
+
+        None
+    }
+
+    /// `@Test` in JVM languages, or `[Fact]` in C#
+    fn is_test_annotation(elements: &[TokenElement], index: usize) -> bool {
+        let element = &elements[index];
+        if !TEST_ANNOTATIONS.contains(&element.value.as_str()) {
+            return false;
+        }
+
+        if TestClassifier::has_scope(element, "annotation") {
+            return true;
+        }
+
+        index > 0 && elements[index - 1].value.trim_end().ends_with('[')
+    }
+
+    fn is_go_test_name(name: &str) -> bool {
+        ["Test", "Benchmark", "Fuzz", "Example"]
+            .iter()
+            .any(|prefix| {
+                name.strip_prefix(prefix)
+                    .map(|rest| rest.is_empty() || !rest.starts_with(char::is_lowercase))
+                    .unwrap_or(false)
+            })
+    }
+
+    fn any_function(elements: &[TokenElement], predicate: impl Fn(&str) -> bool) -> bool {
+        elements.iter().any(|element| {
+            TestClassifier::has_scope(element, "entity.name.function")
+                && !TestClassifier::has_scope(element, "meta.function-call")
+                && predicate(&element.value)
+        })
+    }
+
+    /// `import "testing"` in go is a `entity.name.import` token, python's `import pytest` is plain
+    fn imports(elements: &[TokenElement], module: &str) -> bool {
+        elements.iter().enumerate().any(|(index, element)| {
+            if element.value.trim() != module {
+                return false;
+            }
+
+            if TestClassifier::has_scope(element, "import") {
+                return true;
+            }
+
+            index > 0 && {
+                let keyword = elements[index - 1].value.trim();
+                keyword == "import" || keyword == "from"
+            }
+        })
+    }
+
+    fn has_scope(element: &TokenElement, scope: &str) -> bool {
+        element.scopes.iter().any(|name| name.contains(scope))
+    }
+
+    fn language_scope(elements: &[TokenElement]) -> &str {
+        elements
+            .first()
+            .and_then(|element| element.scopes.first())
+            .map(|scope| scope.as_str())
+            .unwrap_or("")
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct TestSummary {
+    pub test_files: usize,
+    pub production_files: usize,
+    pub test_lines: usize,
+    pub production_lines: usize,
+}
+
+impl TestSummary {
+    /// generated and resource files are neither test nor production code
+    pub fn from_files(files: &[CodeFile]) -> TestSummary {
+        let mut summary = TestSummary::default();
+        for file in files {
+            summary.add_file(file);
+        }
+
+        summary
+    }
+
+    pub fn add_file(&mut self, file: &CodeFile) {
+        let lines = file
+            .elements
+            .last()
+            .map(|element| element.line_num as usize)
+            .unwrap_or(0);
+
+        match file.kind {
+            CodeFileKind::Generated | CodeFileKind::Resource => {}
+            CodeFileKind::Test => {
+                self.test_files += 1;
+                self.test_lines += lines;
+            }
+            _ => {
+                let test_lines: usize = file.test_ranges.iter().map(|range| range.len()).sum();
+                self.production_files += 1;
+                self.test_lines += test_lines;
+                self.production_lines += lines.saturating_sub(test_lines);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyser::Identify;
+    use crate::analyser::TestClassifier;
+    use scie_model::artifact::{CodeFile, CodeFileKind, LineRange};
+    use std::path::{Path, PathBuf};
+
+    fn grammar_path(lang: &str, file: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("extensions")
+            .join(lang)
+            .join("syntaxes")
+            .join(file)
+    }
+
+    fn classify(name: &str, lang: PathBuf, code: &str) -> CodeFile {
+        let mut code_file = CodeFile::new(PathBuf::from(name));
+        code_file.elements = Identify::identify_file(lang, code.to_string());
+        TestClassifier::classify(&mut code_file, Path::new(name));
+        code_file
+    }
+
+    #[test]
+    fn should_match_test_path_conventions() {
+        assert!(TestClassifier::is_test_path(Path::new("pkg/main_test.go")));
+        assert!(TestClassifier::is_test_path(Path::new("app/test_views.py")));
+        assert!(TestClassifier::is_test_path(Path::new("src/app.spec.ts")));
+        assert!(TestClassifier::is_test_path(Path::new(
+            "src/__tests__/app.js"
+        )));
+        assert!(TestClassifier::is_test_path(Path::new("tests/cli.rs")));
+        assert!(!TestClassifier::is_test_path(Path::new("src/main.go")));
+        assert!(!TestClassifier::is_test_path(Path::new("src/testing.rs")));
+    }
+
+    #[test]
+    fn should_classify_java_by_annotation() {
+        let lang = grammar_path("java", "java.tmLanguage.json");
+        let code = "class Demo {
+    @Test
+    void should_work() {}
+}";
+        let file = classify("Demo.java", lang.clone(), code);
+        assert_eq!(CodeFileKind::Test, file.kind);
+
+        let file = classify(
+            "Demo.java",
+            lang,
+            "class Demo {\n  @Override\n  void run() {}\n}",
+        );
+        assert_eq!(CodeFileKind::Unknown, file.kind);
+    }
+
+    #[test]
+    fn should_classify_python_test_functions() {
+        let lang = grammar_path("python", "MagicPython.tmLanguage.json");
+        let file = classify("checks.py", lang, "def test_demo():\n    assert 1 == 1\n");
+
+        assert_eq!(CodeFileKind::Test, file.kind);
+    }
+
+    #[test]
+    fn should_find_rust_inline_test_module() {
+        let lang = grammar_path("rust", "rust.tmLanguage.json");
+        let code = "fn main() {
+    println!(\"{}\", \"}\");
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn should_work() {
+        assert!(true);
+    }
+}
+";
+        let file = classify("main.rs", lang, code);
+
+        assert_eq!(CodeFileKind::Unknown, file.kind);
+        assert_eq!(vec![LineRange::new(5, 11)], file.test_ranges);
+    }
+}
@@ -0,0 +1,405 @@
+use crate::generator::Emitter;
+use handlebars::{handlebars_helper, no_escape, Handlebars};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+const TEMPLATE_EXT: &str = "hbs";
+
+/// a handlebars template with the front matter like hygen, `to` is the path of output, `each` is
+/// the array in data to render one file per item, and the item is skipped when `when` is rendered
+/// to empty or `false`:
+///
+/// ```text
+/// ---
+/// to: src/test/java/{{package_path package}}/{{name}}Test.java
+/// each: classes
+/// when: {{has_modifier this "public"}}
+/// ---
+/// class {{name}}Test {}
+/// ```
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct GenTemplate {
+    pub name: String,
+    pub to: String,
+    pub each: Option<String>,
+    pub when: Option<String>,
+    pub body: String,
+}
+
+impl GenTemplate {
+    pub fn parse(name: &str, source: &str) -> Result<GenTemplate, String> {
+        let mut template = GenTemplate {
+            name: name.to_string(),
+            to: String::new(),
+            each: None,
+            when: None,
+            body: String::new(),
+        };
+
+        let mut lines = source.split_inclusive('\n');
+        if lines.next().map(|line| line.trim()) != Some("---") {
+            return Err(format!("{}: missing front matter", name));
+        }
+        let mut is_closed = false;
+        for line in lines.by_ref() {
+            if line.trim() == "---" {
+                is_closed = true;
+                break;
+            }
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim().to_string()),
+                None => continue,
+            };
+            match key {
+                "to" => template.to = value,
+                "each" => template.each = Some(value),
+                "when" => template.when = Some(value),
+                _ => return Err(format!("{}: unknown key `{}`", name, key)),
+            }
+        }
+
+        if !is_closed {
+            return Err(format!("{}: front matter is not closed", name));
+        }
+        if template.to.is_empty() {
+            return Err(format!("{}: missing `to`", name));
+        }
+        template.body = lines.collect();
+        Ok(template)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct GeneratedFile {
+    pub path: String,
+    pub content: String,
+}
+
+handlebars_helper!(camel_case: |text: str| {
+    let pascal = CodeGenerator::pascal(text);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect::<String>(),
+        None => String::new(),
+    }
+});
+handlebars_helper!(pascal_case: |text: str| CodeGenerator::pascal(text));
+handlebars_helper!(snake_case: |text: str| CodeGenerator::words(text).join("_"));
+handlebars_helper!(kebab_case: |text: str| CodeGenerator::words(text).join("-"));
+handlebars_helper!(package_path: |package: str| package.replace('.', "/"));
+handlebars_helper!(has_modifier: |element: Json, modifier: str| {
+    element["modifiers"]
+        .as_array()
+        .map(|modifiers| modifiers.iter().any(|value| value.as_str() == Some(modifier)))
+        .unwrap_or(false)
+});
+handlebars_helper!(has_annotation: |element: Json, annotation: str| {
+    element["annotations"]
+        .as_array()
+        .map(|annotations| {
+            annotations
+                .iter()
+                .any(|value| value["name"].as_str() == Some(annotation))
+        })
+        .unwrap_or(false)
+});
+
+/// renders the structural model, like `GenerateContext`, by the templates, see ADR 0014
+pub struct CodeGenerator {
+    registry: Handlebars<'static>,
+    templates: Vec<GenTemplate>,
+}
+
+impl Default for CodeGenerator {
+    fn default() -> Self {
+        CodeGenerator::new()
+    }
+}
+
+impl CodeGenerator {
+    pub fn new() -> CodeGenerator {
+        let mut registry = Handlebars::new();
+        registry.register_escape_fn(no_escape);
+        registry.register_helper("camel_case", Box::new(camel_case));
+        registry.register_helper("pascal_case", Box::new(pascal_case));
+        registry.register_helper("snake_case", Box::new(snake_case));
+        registry.register_helper("kebab_case", Box::new(kebab_case));
+        registry.register_helper("package_path", Box::new(package_path));
+        registry.register_helper("has_modifier", Box::new(has_modifier));
+        registry.register_helper("has_annotation", Box::new(has_annotation));
+
+        CodeGenerator {
+            registry,
+            templates: vec![],
+        }
+    }
+
+    pub fn add_template(&mut self, name: &str, source: &str) -> Result<(), String> {
+        let template = GenTemplate::parse(name, source)?;
+        self.register(&template.name, &template.body)?;
+        self.register(&format!("{}#to", name), &template.to)?;
+        if let Some(when) = &template.when {
+            self.register(&format!("{}#when", name), when)?;
+        }
+
+        self.templates.push(template);
+        Ok(())
+    }
+
+    /// all `.hbs` files in the dir, by the order of file names
+    pub fn load_dir(&mut self, dir: &Path) -> Result<usize, String> {
+        let mut paths: Vec<_> = fs::read_dir(dir)
+            .map_err(|err| err.to_string())?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .map(|ext| ext == TEMPLATE_EXT)
+                    .unwrap_or(false)
+            })
+            .collect();
+        paths.sort();
+
+        for path in paths.iter() {
+            let name = path.file_stem().unwrap().to_str().unwrap();
+            let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+            self.add_template(name, &source)?;
+        }
+
+        Ok(paths.len())
+    }
+
+    pub fn generate<T: Serialize>(&self, data: &T) -> Result<Vec<GeneratedFile>, String> {
+        let data = serde_json::to_value(data).map_err(|err| err.to_string())?;
+        let mut files = vec![];
+        for template in self.templates.iter() {
+            let items = match &template.each {
+                Some(each) => CodeGenerator::lookup(&data, each)
+                    .and_then(|value| value.as_array())
+                    .ok_or_else(|| format!("{}: `{}` is not an array", template.name, each))?
+                    .clone(),
+                None => vec![data.clone()],
+            };
+
+            for item in items.iter() {
+                if template.when.is_some() {
+                    let when = self.render(&format!("{}#when", template.name), item)?;
+                    let when = when.trim();
+                    if when.is_empty() || when == "false" {
+                        continue;
+                    }
+                }
+
+                files.push(GeneratedFile {
+                    path: self
+                        .render(&format!("{}#to", template.name), item)?
+                        .trim()
+                        .to_string(),
+                    content: self.render(&template.name, item)?,
+                });
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// returns the count of emitted files
+    pub fn generate_to<T: Serialize>(
+        &self,
+        data: &T,
+        emitter: &mut dyn Emitter,
+    ) -> Result<usize, String> {
+        let files = self.generate(data)?;
+        for file in files.iter() {
+            emitter.emit(file)?;
+        }
+        Ok(files.len())
+    }
+
+    fn register(&mut self, name: &str, source: &str) -> Result<(), String> {
+        self.registry
+            .register_template_string(name, source)
+            .map_err(|err| format!("{}: {}", name, err))
+    }
+
+    fn render(&self, name: &str, data: &Value) -> Result<String, String> {
+        self.registry
+            .render(name, data)
+            .map_err(|err| format!("{}: {}", name, err))
+    }
+
+    /// by the dotted path, like `classes` or `project.modules`
+    fn lookup<'a>(data: &'a Value, path: &str) -> Option<&'a Value> {
+        path.split('.').try_fold(data, |value, key| value.get(key))
+    }
+
+    /// the lowercase words of identifiers, like `user` and `name` of `userName` and `user_name`
+    fn words(text: &str) -> Vec<String> {
+        let mut words: Vec<String> = vec![];
+        let mut previous: Option<char> = None;
+        for char in text.chars() {
+            if !char.is_alphanumeric() {
+                previous = None;
+                continue;
+            }
+
+            let is_boundary = match previous {
+                None => true,
+                Some(previous) => char.is_uppercase() && !previous.is_uppercase(),
+            };
+            if is_boundary {
+                words.push(String::new());
+            }
+            words.last_mut().unwrap().extend(char.to_lowercase());
+            previous = Some(char);
+        }
+
+        words
+    }
+
+    fn pascal(text: &str) -> String {
+        CodeGenerator::words(text)
+            .iter()
+            .map(|word| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                    None => String::new(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyser::Analyser;
+    use crate::generator::{
+        CodeGenerator, Emitter, FileEmitter, GenTemplate, GenerateContext, GeneratedFile,
+        MemoryEmitter,
+    };
+    use std::path::PathBuf;
+
+    fn fixtures() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+    }
+
+    fn controller_context() -> GenerateContext {
+        let path = fixtures()
+            .join("projects")
+            .join("java")
+            .join("sima")
+            .join("UserController.java");
+
+        GenerateContext::from_files(&Analyser::ident_by_dir(&path, false, false))
+    }
+
+    #[test]
+    fn should_parse_front_matter() {
+        let template = GenTemplate::parse(
+            "test",
+            "---\nto: {{name}}Test.java\neach: classes\n---\nclass {{name}}Test {}\n",
+        )
+        .unwrap();
+
+        assert_eq!("{{name}}Test.java", template.to);
+        assert_eq!(Some("classes".to_string()), template.each);
+        assert_eq!("class {{name}}Test {}\n", template.body);
+        assert!(GenTemplate::parse("test", "class A {}").is_err());
+        assert!(GenTemplate::parse("test", "---\neach: classes\n---\n").is_err());
+    }
+
+    #[test]
+    fn should_convert_cases() {
+        let mut generator = CodeGenerator::new();
+        generator
+            .add_template(
+                "cases",
+                "---\nto: cases.txt\n---\n{{camel_case name}} {{pascal_case name}} {{snake_case name}} {{kebab_case name}} {{package_path package}}",
+            )
+            .unwrap();
+
+        let data = serde_json::json!({ "name": "user_controller", "package": "com.phodal" });
+        let files = generator.generate(&data).unwrap();
+        assert_eq!(
+            "userController UserController user_controller user-controller com/phodal",
+            files[0].content
+        );
+        assert_eq!(
+            vec!["get", "user", "name"],
+            CodeGenerator::words("getUserName")
+        );
+    }
+
+    #[test]
+    fn should_generate_from_fixture_templates() {
+        let mut generator = CodeGenerator::new();
+        let count = generator.load_dir(&fixtures().join("templates")).unwrap();
+        assert_eq!(3, count);
+
+        let mut emitter = MemoryEmitter::default();
+        generator
+            .generate_to(&controller_context(), &mut emitter)
+            .unwrap();
+
+        let client = &emitter.files["src/main/java/com/phodal/demo/UserControllerClient.java"];
+        assert!(client.contains("public List<User> find(String id, int... rest)"));
+
+        let mapper = &emitter.files["src/main/java/com/phodal/demo/UserControllerMapper.java"];
+        assert!(mapper.contains("target.setCache(source.getCache());"));
+
+        // the api client is only for the controllers
+        assert!(!emitter.files.keys().any(|path| path.contains("ApiClient")));
+        let test = &emitter.files["src/test/java/com/phodal/demo/UserControllerTest.java"];
+        assert!(test.contains("void should_find()"));
+        assert!(test.contains("void should_convert()"));
+        assert!(!emitter
+            .files
+            .keys()
+            .any(|path| path.ends_with("ApiTest.java")));
+    }
+
+    #[test]
+    fn should_skip_existing_files_unless_overwrite() {
+        let root = std::env::temp_dir().join("scie_generator");
+        let _ = std::fs::remove_dir_all(&root);
+        let file = GeneratedFile {
+            path: "demo/Hello.java".to_string(),
+            content: "class Hello {}".to_string(),
+        };
+
+        let mut emitter = FileEmitter::new(root.clone(), false);
+        emitter.emit(&file).unwrap();
+        emitter.emit(&file).unwrap();
+        assert_eq!(1, emitter.written.len());
+        assert_eq!(1, emitter.skipped.len());
+
+        let mut emitter = FileEmitter::new(root.clone(), true);
+        emitter.emit(&file).unwrap();
+        assert_eq!(1, emitter.written.len());
+        assert_eq!(
+            "class Hello {}",
+            std::fs::read_to_string(root.join("demo").join("Hello.java")).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_reject_paths_out_of_root() {
+        let root = std::env::temp_dir().join("scie_generator_escape");
+        let mut emitter = FileEmitter::new(root.clone(), true);
+        for path in ["../Hello.java", "demo/../../Hello.java", "/tmp/Hello.java"].iter() {
+            let file = GeneratedFile {
+                path: path.to_string(),
+                content: "class Hello {}".to_string(),
+            };
+            assert!(emitter.emit(&file).is_err(), "{}", path);
+        }
+
+        assert!(emitter.written.is_empty());
+        assert!(!root.parent().unwrap().join("Hello.java").exists());
+    }
+}
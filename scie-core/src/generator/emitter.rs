@@ -0,0 +1,67 @@
+use crate::generator::GeneratedFile;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// the output of `CodeGenerator`, like `SimaWriter` for the analysed files
+pub trait Emitter {
+    fn emit(&mut self, file: &GeneratedFile) -> Result<(), String>;
+}
+
+/// writes files under the `root`, the existing files are skipped unless `overwrite`, so the
+/// generated code which edited by hand is kept. the absolute paths and `..` are errors
+pub struct FileEmitter {
+    root: PathBuf,
+    overwrite: bool,
+    pub written: Vec<PathBuf>,
+    pub skipped: Vec<PathBuf>,
+}
+
+impl FileEmitter {
+    pub fn new(root: PathBuf, overwrite: bool) -> FileEmitter {
+        FileEmitter {
+            root,
+            overwrite,
+            written: vec![],
+            skipped: vec![],
+        }
+    }
+}
+
+impl Emitter for FileEmitter {
+    fn emit(&mut self, file: &GeneratedFile) -> Result<(), String> {
+        // the paths of templates never write out of the root
+        let is_relative = Path::new(&file.path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if !is_relative {
+            return Err(format!("invalid path of generated file: {}", file.path));
+        }
+
+        let path = self.root.join(&file.path);
+        if path.exists() && !self.overwrite {
+            self.skipped.push(path);
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        fs::write(&path, &file.content).map_err(|err| err.to_string())?;
+        self.written.push(path);
+        Ok(())
+    }
+}
+
+/// keeps the files by path, for dry run and tests
+#[derive(Default)]
+pub struct MemoryEmitter {
+    pub files: BTreeMap<String, String>,
+}
+
+impl Emitter for MemoryEmitter {
+    fn emit(&mut self, file: &GeneratedFile) -> Result<(), String> {
+        self.files.insert(file.path.clone(), file.content.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use crate::analyser::ElementExtractor;
+use crate::sima::java_sima::{JavaClass, JavaSima};
+use scie_model::artifact::{CodeElement, CodeFile};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ClassContext {
+    pub package: String,
+    pub path: String,
+    #[serde(flatten)]
+    pub class: JavaClass,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct FileContext {
+    pub path: String,
+    pub name: String,
+    // the modules, classes and functions of all languages
+    pub elements: Vec<CodeElement>,
+}
+
+/// the data of templates, `classes` are from `JavaSima`, and `files` are from `ElementExtractor`
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct GenerateContext {
+    pub classes: Vec<ClassContext>,
+    pub files: Vec<FileContext>,
+}
+
+impl GenerateContext {
+    pub fn from_files(files: &[CodeFile]) -> GenerateContext {
+        let mut context = GenerateContext::default();
+        for file in files {
+            if file.name.ends_with(".java") {
+                let sima = JavaSima::from_file(file);
+                for class in sima.classes {
+                    context.classes.push(ClassContext {
+                        package: sima.package.clone(),
+                        path: sima.path.clone(),
+                        class,
+                    });
+                }
+            }
+
+            let elements = if file.code_elements.is_empty() {
+                ElementExtractor::extract(&file.elements)
+            } else {
+                file.code_elements.clone()
+            };
+            context.files.push(FileContext {
+                path: file.path.clone(),
+                name: file.name.clone(),
+                elements,
+            });
+        }
+
+        context
+    }
+}
@@ -0,0 +1,7 @@
+pub mod code_generator;
+pub mod emitter;
+pub mod generate_context;
+
+pub use code_generator::{CodeGenerator, GenTemplate, GeneratedFile};
+pub use emitter::{Emitter, FileEmitter, MemoryEmitter};
+pub use generate_context::{ClassContext, FileContext, GenerateContext};
@@ -6,4 +6,7 @@ extern crate serde;
 extern crate serde_derive;
 
 pub mod analyser;
+pub mod generator;
+pub mod navigation;
 pub mod sima;
+pub mod tags;
@@ -0,0 +1,288 @@
+use crate::navigation::{Confidence, SymbolIndex, SymbolRange};
+use scie_model::artifact::CodeFile;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use url::Url;
+
+pub const LSIF_VERSION: &str = "0.5.0";
+
+/// writes the `SymbolIndex` as LSIF in JSON lines, like `dump.lsif`. The ranges have a
+/// `confidence` of `SymbolIndex`, which is not a property of LSIF, so the readers ignore it.
+/// The lines of LSIF start from 0, and the characters are the UTF-16 code units of line like the
+/// indexes of tokens, which is the `positionEncoding` of `metaData`.
+pub struct LsifWriter<'a, W: Write> {
+    out: &'a mut W,
+    id: usize,
+}
+
+impl<'a, W: Write> LsifWriter<'a, W> {
+    /// returns the count of lines, the relative paths of files are from `root`
+    pub fn write(
+        files: &[CodeFile],
+        index: &SymbolIndex,
+        root: &Path,
+        out: &'a mut W,
+    ) -> Result<usize, String> {
+        let mut writer = LsifWriter { out, id: 0 };
+        writer.vertex(
+            "metaData",
+            json!({
+                "version": LSIF_VERSION,
+                "positionEncoding": "utf-16",
+                "projectRoot": LsifWriter::<W>::uri(root, Path::new("")),
+                "toolInfo": { "name": "scie", "version": env!("CARGO_PKG_VERSION") },
+            }),
+        )?;
+
+        let languages: Vec<String> = files.iter().map(LsifWriter::<W>::language).collect();
+        let project = writer.vertex(
+            "project",
+            json!({ "kind": LsifWriter::<W>::most_common(&languages) }),
+        )?;
+
+        let mut documents: HashMap<&str, usize> = HashMap::new();
+        for (file, language) in files.iter().zip(languages.iter()) {
+            let uri = LsifWriter::<W>::uri(root, Path::new(&file.path));
+            let document =
+                writer.vertex("document", json!({ "uri": uri, "languageId": language }))?;
+            documents.insert(&file.path, document);
+        }
+        let mut ranges: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+
+        for symbol in index.symbols.iter() {
+            let document = match documents.get(symbol.definition.path.as_str()) {
+                Some(document) => *document,
+                None => continue,
+            };
+
+            let result_set = writer.vertex("resultSet", json!({}))?;
+            let definition = writer.range(&symbol.definition, Confidence::High)?;
+            writer.edge("next", definition, json!({ "inV": result_set }))?;
+            ranges.entry(document).or_default().push(definition);
+
+            let definition_result = writer.vertex("definitionResult", json!({}))?;
+            writer.edge(
+                "textDocument/definition",
+                result_set,
+                json!({ "inV": definition_result }),
+            )?;
+            writer.edge(
+                "item",
+                definition_result,
+                json!({ "inVs": [definition], "document": document }),
+            )?;
+
+            let mut references: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+            for reference in symbol.references.iter() {
+                let reference_document = match documents.get(reference.range.path.as_str()) {
+                    Some(document) => *document,
+                    None => continue,
+                };
+                let range = writer.range(&reference.range, reference.confidence)?;
+                writer.edge("next", range, json!({ "inV": result_set }))?;
+                references
+                    .entry(reference_document)
+                    .or_default()
+                    .push(range);
+                ranges.entry(reference_document).or_default().push(range);
+            }
+
+            let reference_result = writer.vertex("referenceResult", json!({}))?;
+            writer.edge(
+                "textDocument/references",
+                result_set,
+                json!({ "inV": reference_result }),
+            )?;
+            writer.edge(
+                "item",
+                reference_result,
+                json!({ "inVs": [definition], "document": document, "property": "definitions" }),
+            )?;
+            for (reference_document, in_vs) in references {
+                writer.edge(
+                    "item",
+                    reference_result,
+                    json!({
+                        "inVs": in_vs,
+                        "document": reference_document,
+                        "property": "references"
+                    }),
+                )?;
+            }
+        }
+
+        for (document, in_vs) in ranges {
+            writer.edge("contains", document, json!({ "inVs": in_vs }))?;
+        }
+        let mut in_vs: Vec<usize> = documents.values().copied().collect();
+        in_vs.sort_unstable();
+        writer.edge("contains", project, json!({ "inVs": in_vs }))?;
+
+        Ok(writer.id)
+    }
+
+    fn range(&mut self, range: &SymbolRange, confidence: Confidence) -> Result<usize, String> {
+        self.vertex(
+            "range",
+            json!({
+                "start": { "line": range.line - 1, "character": range.start },
+                "end": { "line": range.line - 1, "character": range.end },
+                "confidence": confidence,
+            }),
+        )
+    }
+
+    fn vertex(&mut self, label: &str, properties: Value) -> Result<usize, String> {
+        self.emit("vertex", label, properties)
+    }
+
+    fn edge(&mut self, label: &str, out_v: usize, mut properties: Value) -> Result<usize, String> {
+        properties["outV"] = json!(out_v);
+        self.emit("edge", label, properties)
+    }
+
+    fn emit(&mut self, kind: &str, label: &str, mut properties: Value) -> Result<usize, String> {
+        self.id += 1;
+        properties["id"] = json!(self.id);
+        properties["type"] = json!(kind);
+        properties["label"] = json!(label);
+
+        let line = serde_json::to_string(&properties).map_err(|err| err.to_string())?;
+        writeln!(self.out, "{}", line).map_err(|err| err.to_string())?;
+        Ok(self.id)
+    }
+
+    /// the `file://` uri of the absolute path, without the `.` and `..` of path, and the spaces
+    /// and `#` of names are percent-encoded
+    fn uri(root: &Path, path: &Path) -> String {
+        let root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        let mut normalized = PathBuf::new();
+        for component in root.join(path).components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                other => normalized.push(other.as_os_str()),
+            }
+        }
+        if !normalized.is_absolute() {
+            normalized = std::env::current_dir().unwrap_or_default().join(normalized);
+        }
+
+        Url::from_file_path(&normalized)
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| format!("file://{}", normalized.display()))
+    }
+
+    /// the id of language by the root scope, like `java` of `source.java`
+    fn language(file: &CodeFile) -> String {
+        file.elements
+            .first()
+            .and_then(|element| element.scopes.first())
+            .and_then(|scope| scope.rsplit('.').next())
+            .unwrap_or("")
+            .to_string()
+    }
+
+    fn most_common(languages: &[String]) -> String {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for language in languages.iter().filter(|it| !it.is_empty()) {
+            *counts.entry(language).or_default() += 1;
+        }
+        counts
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(language, _)| language.to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::navigation::{
+        Confidence, LsifWriter, Symbol, SymbolIndex, SymbolRange, SymbolReference,
+    };
+    use scie_model::artifact::{CodeFile, TokenElement};
+    use serde_json::Value;
+    use std::path::{Path, PathBuf};
+
+    fn range(path: &str, line: i32, start: i32, end: i32) -> SymbolRange {
+        SymbolRange {
+            path: path.to_string(),
+            line,
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn should_write_lsif_lines() {
+        let mut file = CodeFile::new(PathBuf::from("src/main.rs"));
+        file.elements.push(TokenElement::new(
+            1,
+            0,
+            2,
+            "fn".to_string(),
+            vec!["source.rust".to_string()],
+        ));
+        let index = SymbolIndex {
+            symbols: vec![Symbol {
+                name: "run".to_string(),
+                kind: None,
+                definition: range("src/main.rs", 1, 3, 6),
+                references: vec![SymbolReference {
+                    range: range("src/main.rs", 5, 4, 7),
+                    confidence: Confidence::Medium,
+                }],
+            }],
+        };
+
+        let mut out = vec![];
+        let count = LsifWriter::write(&[file], &index, Path::new("/demo"), &mut out).unwrap();
+        let lines: Vec<Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(count, lines.len());
+
+        let find = |label: &str| -> Vec<&Value> {
+            lines.iter().filter(|it| it["label"] == label).collect()
+        };
+        assert_eq!("rust", find("project")[0]["kind"]);
+        assert_eq!("file:///demo/src/main.rs", find("document")[0]["uri"]);
+        assert_eq!("utf-16", find("metaData")[0]["positionEncoding"]);
+
+        let ranges = find("range");
+        assert_eq!(2, ranges.len());
+        assert_eq!(4, ranges[1]["start"]["line"]);
+        assert_eq!("medium", ranges[1]["confidence"]);
+
+        let items = find("item");
+        let references = items
+            .iter()
+            .find(|it| it["property"] == "references")
+            .unwrap();
+        assert_eq!(ranges[1]["id"], references["inVs"][0]);
+        assert_eq!(2, find("contains").len());
+    }
+
+    #[test]
+    fn should_encode_uri_of_paths() {
+        let file = CodeFile::new(PathBuf::from("src/my lib#1.rs"));
+        let index = SymbolIndex { symbols: vec![] };
+
+        let mut out = vec![];
+        LsifWriter::write(&[file], &index, Path::new("/demo"), &mut out).unwrap();
+        let document: Value = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .find(|it| it["label"] == "document")
+            .unwrap();
+        assert_eq!("file:///demo/src/my%20lib%231.rs", document["uri"]);
+    }
+}
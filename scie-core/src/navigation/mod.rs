@@ -0,0 +1,5 @@
+pub mod lsif_writer;
+pub mod symbol_index;
+
+pub use lsif_writer::LsifWriter;
+pub use symbol_index::{Confidence, Symbol, SymbolIndex, SymbolRange, SymbolReference};
@@ -0,0 +1,497 @@
+use crate::analyser::ElementExtractor;
+use scie_model::artifact::{CodeElementKind, CodeFile, TokenElement};
+use std::collections::{HashMap, HashSet};
+
+/// the constructs which own a block, like `meta.class.java` and `meta.method.declaration.ts`
+const CONSTRUCTS: [&str; 10] = [
+    "class",
+    "method",
+    "function",
+    "interface",
+    "enum",
+    "struct",
+    "trait",
+    "impl",
+    "namespace",
+    "module",
+];
+
+/// how the reference is paired with the definition
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, PartialOrd, Ord, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    // by name, one of the definitions in other files
+    Low,
+    // by name, the only definition of project, or a member like `this.name`
+    Medium,
+    // by the lexical scopes, the innermost definition of the enclosing blocks
+    High,
+}
+
+/// the range of a name, the line starts from 1 and the indexes are by UTF-16 code units like
+/// `TokenElement`
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct SymbolRange {
+    pub path: String,
+    pub line: i32,
+    pub start: i32,
+    pub end: i32,
+}
+
+impl SymbolRange {
+    /// the range of the name without the spaces of token
+    fn of(path: &str, element: &TokenElement, name: &str) -> SymbolRange {
+        let leading = element.value.len() - element.value.trim_start().len();
+        let start = element.start_index + element.value[..leading].encode_utf16().count() as i32;
+        SymbolRange {
+            path: path.to_string(),
+            line: element.line_num,
+            start,
+            end: start + name.encode_utf16().count() as i32,
+        }
+    }
+
+    pub fn contains(&self, path: &str, line: i32, character: i32) -> bool {
+        self.path == path && self.line == line && character >= self.start && character <= self.end
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct SymbolReference {
+    pub range: SymbolRange,
+    pub confidence: Confidence,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    // the kind of code element, or `None` for the locals like variables and parameters
+    pub kind: Option<CodeElementKind>,
+    pub definition: SymbolRange,
+    pub references: Vec<SymbolReference>,
+}
+
+struct Definition {
+    symbol: usize,
+    // the block which the name is visible in, `0` for the file
+    block: usize,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum ReferenceKind {
+    Type,
+    Call,
+    Member,
+    Name,
+}
+
+struct Reference {
+    name: String,
+    range: SymbolRange,
+    // the enclosing blocks from the outermost
+    blocks: Vec<usize>,
+    kind: ReferenceKind,
+}
+
+/// the definitions and references of names, which paired by the scopes of tokens:
+///
+///  - definitions: the `entity.name.*` of code elements, and the variables like
+///    `variable.other.definition` and `variable.parameter`
+///  - references: `variable.other.*`, `entity.name.function` of calls, `entity.name.type` of types
+///  - lexical scopes: the blocks of `meta.*`, like `meta.class.body.java` and `meta.block.ts`
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct SymbolIndex {
+    pub symbols: Vec<Symbol>,
+}
+
+impl SymbolIndex {
+    pub fn build(files: &[CodeFile]) -> SymbolIndex {
+        let mut index = SymbolIndex::default();
+        let mut next_block = 0;
+        let mut locals = vec![];
+        for file in files {
+            let (definitions, references) = index.collect(file, &mut next_block);
+            locals.push((definitions, references));
+        }
+
+        let mut exported: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (symbol_id, symbol) in index.symbols.iter().enumerate() {
+            if symbol.kind.is_some() {
+                exported.entry(&symbol.name).or_default().push(symbol_id);
+            }
+        }
+
+        let mut resolved = vec![];
+        for (definitions, references) in locals.iter() {
+            for reference in references {
+                let found = SymbolIndex::resolve_local(&index.symbols, definitions, reference)
+                    .map(|symbol_id| (symbol_id, Confidence::High))
+                    .or_else(|| {
+                        let candidates = exported.get(reference.name.as_str())?;
+                        SymbolIndex::resolve_global(&index.symbols, candidates, reference)
+                    });
+
+                if let Some((symbol_id, confidence)) = found {
+                    resolved.push((symbol_id, reference.range.clone(), confidence));
+                }
+            }
+        }
+
+        for (symbol_id, range, confidence) in resolved {
+            index.symbols[symbol_id]
+                .references
+                .push(SymbolReference { range, confidence });
+        }
+
+        index
+    }
+
+    /// the symbol which defined or referenced at the position, with the confidence of reference
+    pub fn find(&self, path: &str, line: i32, character: i32) -> Option<(&Symbol, Confidence)> {
+        for symbol in self.symbols.iter() {
+            if symbol.definition.contains(path, line, character) {
+                return Some((symbol, Confidence::High));
+            }
+            for reference in symbol.references.iter() {
+                if reference.range.contains(path, line, character) {
+                    return Some((symbol, reference.confidence));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn collect(
+        &mut self,
+        file: &CodeFile,
+        next_block: &mut usize,
+    ) -> (HashMap<String, Vec<Definition>>, Vec<Reference>) {
+        let elements: HashMap<(&str, i32), CodeElementKind> = file
+            .code_elements
+            .iter()
+            .filter(|element| {
+                element.kind != CodeElementKind::Import && element.kind != CodeElementKind::Package
+            })
+            .map(|element| ((element.name.as_str(), element.range.start), element.kind))
+            .collect();
+        let language = file
+            .elements
+            .first()
+            .and_then(|element| element.scopes.first())
+            .and_then(|scope| scope.rsplit('.').next())
+            .unwrap_or("")
+            .to_string();
+
+        let mut definitions: HashMap<String, Vec<Definition>> = HashMap::new();
+        let mut references = vec![];
+        let mut seen: HashSet<(&str, i32)> = HashSet::new();
+        let mut open: Vec<(usize, String, usize, bool)> = vec![];
+        for element in file.elements.iter() {
+            let blocks = SymbolIndex::blocks(&mut open, element, &language, next_block);
+            let name = element.value.trim();
+            if !SymbolIndex::is_identifier(name) {
+                continue;
+            }
+
+            let is_name = ElementExtractor::has(element, "entity.name")
+                || ElementExtractor::has(element, "variable");
+            let kind = elements.get(&(name, element.line_num)).copied();
+            let range = SymbolRange::of(&file.path, element, name);
+            if is_name && kind.is_some() && seen.insert((name, element.line_num)) {
+                // the name of construct is visible out of the block of construct
+                let block = match open.last() {
+                    Some((_, _, _, true)) => open.iter().rev().nth(1).map(|it| it.2),
+                    Some((_, _, id, false)) => Some(*id),
+                    None => None,
+                };
+                self.define(&mut definitions, name, kind, range, block.unwrap_or(0));
+            } else if SymbolIndex::is_variable_definition(element) {
+                let block = open.last().map(|it| it.2).unwrap_or(0);
+                self.define(&mut definitions, name, None, range, block);
+            } else if let Some(kind) = SymbolIndex::reference_kind(element) {
+                references.push(Reference {
+                    name: name.to_string(),
+                    range,
+                    blocks,
+                    kind,
+                });
+            }
+        }
+
+        (definitions, references)
+    }
+
+    fn define(
+        &mut self,
+        definitions: &mut HashMap<String, Vec<Definition>>,
+        name: &str,
+        kind: Option<CodeElementKind>,
+        range: SymbolRange,
+        block: usize,
+    ) {
+        self.symbols.push(Symbol {
+            name: name.to_string(),
+            kind,
+            definition: range,
+            references: vec![],
+        });
+        definitions
+            .entry(name.to_string())
+            .or_default()
+            .push(Definition {
+                symbol: self.symbols.len() - 1,
+                block,
+            });
+    }
+
+    /// the ids of enclosing blocks, the scopes which are the same as the previous token are the
+    /// same blocks; `open` is the scope index, name, id and is construct of the blocks
+    fn blocks(
+        open: &mut Vec<(usize, String, usize, bool)>,
+        element: &TokenElement,
+        language: &str,
+        next_block: &mut usize,
+    ) -> Vec<usize> {
+        let current: Vec<(usize, &str, bool)> = element
+            .scopes
+            .iter()
+            .enumerate()
+            .skip(1)
+            .flat_map(|(index, scope)| scope.split_whitespace().map(move |it| (index, it)))
+            .filter_map(|(index, scope)| {
+                SymbolIndex::block_kind(scope, language)
+                    .map(|is_construct| (index, scope, is_construct))
+            })
+            .collect();
+
+        let mut keep = 0;
+        while keep < open.len()
+            && keep < current.len()
+            && open[keep].0 == current[keep].0
+            && open[keep].1 == current[keep].1
+        {
+            keep += 1;
+        }
+        open.truncate(keep);
+        for (index, scope, is_construct) in current.into_iter().skip(keep) {
+            *next_block += 1;
+            open.push((index, scope.to_string(), *next_block, is_construct));
+        }
+
+        open.iter().map(|it| it.2).collect()
+    }
+
+    /// `Some(true)` for the constructs like `meta.class.java`, `Some(false)` for the bodies like
+    /// `meta.block.ts`, or `None` for the others like `meta.method.identifier.java`
+    fn block_kind(scope: &str, language: &str) -> Option<bool> {
+        let scope = scope.strip_prefix("meta.")?;
+        let scope = scope
+            .strip_suffix(language)
+            .and_then(|it| it.strip_suffix('.'))
+            .unwrap_or(scope);
+        let segments: Vec<&str> = scope.split('.').collect();
+
+        if matches!(segments.last(), Some(&"block") | Some(&"body")) {
+            return Some(false);
+        }
+        let is_construct = CONSTRUCTS.contains(&segments[0])
+            && (segments.len() == 1 || (segments.len() == 2 && segments[1] == "declaration"));
+        if is_construct {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    fn is_variable_definition(element: &TokenElement) -> bool {
+        ElementExtractor::has(element, "variable.other.definition")
+            || ElementExtractor::has(element, "variable.parameter")
+            || (ElementExtractor::has(element, "meta.definition.variable")
+                && ElementExtractor::has(element, "variable.other"))
+    }
+
+    /// the identifiers without scopes but `meta.*` are names too, like the variables of Java
+    fn reference_kind(element: &TokenElement) -> Option<ReferenceKind> {
+        let has = |prefix: &str| ElementExtractor::has(element, prefix);
+        if has("variable.other.property") || has("variable.other.object.property") {
+            Some(ReferenceKind::Member)
+        } else if has("variable.other") {
+            Some(ReferenceKind::Name)
+        } else if has("entity.name.function") {
+            Some(ReferenceKind::Call)
+        } else if has("entity.name.type")
+            || has("entity.other.inherited-class")
+            || (has("storage.type") && !has("storage.type.primitive"))
+        {
+            Some(ReferenceKind::Type)
+        } else if element
+            .scopes
+            .iter()
+            .skip(1)
+            .flat_map(|scope| scope.split_whitespace())
+            .all(|scope| scope.starts_with("meta."))
+        {
+            Some(ReferenceKind::Name)
+        } else {
+            None
+        }
+    }
+
+    fn is_identifier(name: &str) -> bool {
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) if first.is_alphabetic() || first == '_' || first == '$' => {
+                chars.all(|it| it.is_alphanumeric() || it == '_' || it == '$')
+            }
+            _ => false,
+        }
+    }
+
+    /// the innermost definition in the enclosing blocks, the members like `this.name` are not
+    /// the locals
+    fn resolve_local(
+        symbols: &[Symbol],
+        definitions: &HashMap<String, Vec<Definition>>,
+        reference: &Reference,
+    ) -> Option<usize> {
+        let candidates = definitions.get(&reference.name)?;
+        let depth = |block: usize| -> Option<usize> {
+            if block == 0 {
+                return Some(0);
+            }
+            reference
+                .blocks
+                .iter()
+                .position(|it| *it == block)
+                .map(|index| index + 1)
+        };
+
+        candidates
+            .iter()
+            .filter(|definition| {
+                reference.kind != ReferenceKind::Member || symbols[definition.symbol].kind.is_some()
+            })
+            .filter_map(|definition| depth(definition.block).map(|depth| (depth, definition)))
+            .max_by_key(|(depth, definition)| (*depth, std::cmp::Reverse(definition.symbol)))
+            .map(|(_, definition)| definition.symbol)
+    }
+
+    /// the only definition is `Medium`, or the nearest file by path is `Low`; the types are for
+    /// the types, and the functions are for the calls
+    fn resolve_global(
+        symbols: &[Symbol],
+        candidates: &[usize],
+        reference: &Reference,
+    ) -> Option<(usize, Confidence)> {
+        let is_kind_of = |symbol_id: &&usize| match (reference.kind, symbols[**symbol_id].kind) {
+            (ReferenceKind::Type, Some(kind)) => kind.is_type() || kind == CodeElementKind::Type,
+            (ReferenceKind::Call, Some(kind)) => {
+                kind == CodeElementKind::Function || kind == CodeElementKind::Method
+            }
+            _ => true,
+        };
+        let candidates: Vec<usize> = candidates.iter().filter(is_kind_of).copied().collect();
+        if candidates.len() == 1 {
+            return Some((candidates[0], Confidence::Medium));
+        }
+
+        let common = |path: &str| {
+            path.chars()
+                .zip(reference.range.path.chars())
+                .take_while(|(a, b)| a == b)
+                .count()
+        };
+        candidates
+            .iter()
+            .max_by_key(|symbol_id| {
+                (
+                    common(&symbols[**symbol_id].definition.path),
+                    std::cmp::Reverse(**symbol_id),
+                )
+            })
+            .map(|symbol_id| (*symbol_id, Confidence::Low))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyser::{ElementExtractor, Identify};
+    use crate::navigation::{Confidence, SymbolIndex};
+    use scie_model::artifact::CodeFile;
+    use std::path::PathBuf;
+
+    fn java_file(path: &str, code: &str) -> CodeFile {
+        let lang = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("extensions")
+            .join("java/syntaxes/java.tmLanguage.json");
+
+        let mut file = CodeFile::new(PathBuf::from(path));
+        file.elements = Identify::identify_file(lang, code.to_string());
+        file.code_elements = ElementExtractor::extract(&file.elements);
+        file
+    }
+
+    #[test]
+    fn should_pair_definitions_and_references() {
+        let demo = "public class Demo {
+    private String name;
+
+    public Demo(String name) {
+        this.name = name;
+    }
+
+    public void run() {
+        String name = load();
+        System.out.println(name);
+    }
+
+    private String load() {
+        return name;
+    }
+}";
+        let app = "public class App extends Demo {
+    public void start() {
+        Demo demo = new Demo(\"demo\");
+        demo.run();
+    }
+}";
+        let files = vec![java_file("Demo.java", demo), java_file("App.java", app)];
+        let index = SymbolIndex::build(&files);
+        let references = |path: &str, line: i32, name: &str| -> Vec<(String, i32, Confidence)> {
+            let symbol = index
+                .symbols
+                .iter()
+                .find(|it| {
+                    it.name == name && it.definition.path == path && it.definition.line == line
+                })
+                .unwrap();
+            symbol
+                .references
+                .iter()
+                .map(|it| (it.range.path.clone(), it.range.line, it.confidence))
+                .collect()
+        };
+
+        let high = |line: i32| ("Demo.java".to_string(), line, Confidence::High);
+        assert_eq!(vec![high(5), high(14)], references("Demo.java", 2, "name"));
+        assert_eq!(vec![high(5)], references("Demo.java", 4, "name"));
+        assert_eq!(vec![high(10)], references("Demo.java", 9, "name"));
+        assert_eq!(vec![high(9)], references("Demo.java", 13, "load"));
+
+        let medium = |line: i32| ("App.java".to_string(), line, Confidence::Medium);
+        assert_eq!(
+            vec![medium(1), medium(3)],
+            references("Demo.java", 1, "Demo")
+        );
+        assert_eq!(vec![medium(4)], references("Demo.java", 8, "run"));
+
+        let (symbol, confidence) = index.find("App.java", 4, 10).unwrap();
+        assert_eq!(
+            ("demo", Confidence::High),
+            (symbol.name.as_str(), confidence)
+        );
+    }
+}
@@ -1,27 +1,492 @@
-pub struct JavaSima {}
+use crate::analyser::ElementExtractor;
+use scie_model::artifact::{CodeElement, CodeElementKind, CodeFile, LineRange, TokenElement};
+use std::collections::HashMap;
 
-impl JavaSima {}
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct JavaAnnotation {
+    pub name: String,
+    // the raw text in parentheses, like `value = "/users"`
+    pub arguments: String,
+    pub line_num: i32,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct JavaParameter {
+    pub name: String,
+    pub type_name: String,
+    pub modifiers: Vec<String>,
+    pub annotations: Vec<JavaAnnotation>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct JavaField {
+    pub name: String,
+    pub type_name: String,
+    pub modifiers: Vec<String>,
+    pub annotations: Vec<JavaAnnotation>,
+    pub javadoc: Option<LineRange>,
+    pub line_num: i32,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct JavaMethod {
+    pub name: String,
+    // none for constructors
+    pub return_type: Option<String>,
+    pub parameters: Vec<JavaParameter>,
+    pub throws: Vec<String>,
+    pub modifiers: Vec<String>,
+    pub annotations: Vec<JavaAnnotation>,
+    pub javadoc: Option<LineRange>,
+    pub range: LineRange,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct JavaClass {
+    // `Class`, `Interface` or `Enum`
+    pub kind: CodeElementKind,
+    pub name: String,
+    pub modifiers: Vec<String>,
+    pub annotations: Vec<JavaAnnotation>,
+    pub extends: Vec<String>,
+    pub implements: Vec<String>,
+    // the constants of enums
+    pub constants: Vec<String>,
+    pub fields: Vec<JavaField>,
+    pub methods: Vec<JavaMethod>,
+    pub javadoc: Option<LineRange>,
+    pub range: LineRange,
+    // the outer class of inner classes
+    pub parent: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct JavaImport {
+    pub name: String,
+    pub is_static: bool,
+    pub line_num: i32,
+}
+
+/// the details of declarations which are not in `CodeElement`
+#[derive(Default)]
+struct Declaration {
+    annotations: Vec<JavaAnnotation>,
+    javadoc: Option<LineRange>,
+    extends: Vec<String>,
+    implements: Vec<String>,
+    constants: Vec<String>,
+    return_type: Option<String>,
+    parameters: Vec<JavaParameter>,
+    throws: Vec<String>,
+}
+
+/// the structural model of Java file, which built from the tokens of `java.tmLanguage.json`,
+/// classes are flatten with `parent`, and can be serialized by `BinGen::encode`
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct JavaSima {
+    pub path: String,
+    pub package: String,
+    pub imports: Vec<JavaImport>,
+    pub classes: Vec<JavaClass>,
+}
+
+impl JavaSima {
+    pub fn from_file(code_file: &CodeFile) -> JavaSima {
+        let mut sima = JavaSima::from_elements(&code_file.elements);
+        sima.path = code_file.path.clone();
+        sima
+    }
+
+    pub fn from_elements(elements: &[TokenElement]) -> JavaSima {
+        let (mut declarations, fields) = JavaSima::scan(elements);
+        let mut sima = JavaSima::default();
+
+        for element in ElementExtractor::extract(elements) {
+            let key = (element.range.start, element.name.clone());
+            let declaration = declarations.remove(&key).unwrap_or_default();
+            match element.kind {
+                CodeElementKind::Package => sima.package = element.name,
+                CodeElementKind::Import => sima.imports.push(JavaImport {
+                    is_static: element
+                        .modifiers
+                        .iter()
+                        .any(|modifier| modifier == "static"),
+                    name: element.name,
+                    line_num: element.range.start,
+                }),
+                CodeElementKind::Class | CodeElementKind::Interface | CodeElementKind::Enum => {
+                    sima.classes.push(JavaSima::class(element, declaration))
+                }
+                CodeElementKind::Method => {
+                    let index = element
+                        .parent
+                        .as_ref()
+                        .and_then(|parent| sima.class_of(Some(parent), element.range.start));
+                    if let Some(index) = index {
+                        sima.classes[index]
+                            .methods
+                            .push(JavaSima::method(element, declaration));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for field in fields {
+            if let Some(index) = sima.class_of(None, field.line_num) {
+                sima.classes[index].fields.push(field);
+            }
+        }
+
+        sima
+    }
+
+    /// the innermost class which contains the line, and has the name if given
+    fn class_of(&self, name: Option<&String>, line_num: i32) -> Option<usize> {
+        self.classes.iter().rposition(|class| {
+            class.range.contains(line_num) && name.map(|name| &class.name == name).unwrap_or(true)
+        })
+    }
+
+    fn class(element: CodeElement, declaration: Declaration) -> JavaClass {
+        JavaClass {
+            kind: element.kind,
+            name: element.name,
+            modifiers: element.modifiers,
+            annotations: declaration.annotations,
+            extends: declaration.extends,
+            implements: declaration.implements,
+            constants: declaration.constants,
+            fields: vec![],
+            methods: vec![],
+            javadoc: declaration.javadoc,
+            range: element.range,
+            parent: element.parent,
+        }
+    }
+
+    fn method(element: CodeElement, declaration: Declaration) -> JavaMethod {
+        JavaMethod {
+            name: element.name,
+            return_type: declaration.return_type,
+            parameters: declaration.parameters,
+            throws: declaration.throws,
+            modifiers: element.modifiers,
+            annotations: declaration.annotations,
+            javadoc: declaration.javadoc,
+            range: element.range,
+        }
+    }
+
+    /// the declarations by line and name, and the fields which are not in methods
+    fn scan(elements: &[TokenElement]) -> (HashMap<(i32, String), Declaration>, Vec<JavaField>) {
+        let has = ElementExtractor::has;
+        let mut declarations: HashMap<(i32, String), Declaration> = HashMap::new();
+        let mut fields: Vec<JavaField> = vec![];
+
+        let mut current: Option<(i32, String)> = None;
+        let mut javadoc_start = 0;
+        let mut javadoc: Option<LineRange> = None;
+        let mut annotations: Vec<JavaAnnotation> = vec![];
+        let mut modifiers: Vec<String> = vec![];
+        let mut type_name = String::new();
+        let mut parameter = JavaParameter::default();
+        // the previous field of `int a, b;`, and the nesting of its initializer
+        let mut declarator: Option<JavaField> = None;
+        let mut is_next_declarator = false;
+        let mut nesting = 0;
+
+        for element in elements {
+            let value = element.value.trim();
+            if has(element, "comment.block.javadoc") {
+                if value == "/**" {
+                    javadoc_start = element.line_num;
+                }
+                if value == "*/" {
+                    javadoc = Some(LineRange::new(javadoc_start, element.line_num));
+                }
+                continue;
+            }
+            if has(element, "comment") {
+                continue;
+            }
+
+            if has(element, "meta.declaration.annotation") {
+                let target = if has(element, "meta.method.identifier") {
+                    &mut parameter.annotations
+                } else {
+                    &mut annotations
+                };
+                if has(element, "storage.type.annotation") {
+                    target.push(JavaAnnotation {
+                        name: value.to_string(),
+                        arguments: String::new(),
+                        line_num: element.line_num,
+                    });
+                } else if !has(element, "punctuation.definition.annotation") {
+                    if let Some(annotation) = target.last_mut() {
+                        annotation.arguments.push_str(&element.value);
+                    }
+                }
+                continue;
+            }
+            if has(element, "string") {
+                continue;
+            }
+
+            let declaration = current.as_ref().and_then(|key| declarations.get_mut(key));
+            if has(element, "meta.method.return-type") {
+                JavaSima::push_type(&mut type_name, value);
+            } else if has(element, "entity.name.type.class")
+                || has(element, "entity.name.type.enum")
+            {
+                let key = (element.line_num, value.to_string());
+                declarations.insert(
+                    key.clone(),
+                    Declaration {
+                        annotations: std::mem::take(&mut annotations),
+                        javadoc: javadoc.take(),
+                        ..Default::default()
+                    },
+                );
+                current = Some(key);
+            } else if has(element, "entity.other.inherited-class") {
+                if let Some(declaration) = declaration {
+                    if has(element, "meta.definition.class.implemented.interfaces") {
+                        declaration.implements.push(value.to_string());
+                    } else {
+                        declaration.extends.push(value.to_string());
+                    }
+                }
+            } else if has(element, "constant.other.enum") {
+                if let Some(declaration) = declaration {
+                    declaration.constants.push(value.to_string());
+                }
+            } else if has(element, "entity.name.function") && has(element, "meta.method.identifier")
+            {
+                let key = (element.line_num, value.to_string());
+                let return_type = std::mem::take(&mut type_name);
+                declarations.insert(
+                    key.clone(),
+                    Declaration {
+                        annotations: std::mem::take(&mut annotations),
+                        javadoc: javadoc.take(),
+                        return_type: Some(return_type).filter(|name| !name.is_empty()),
+                        ..Default::default()
+                    },
+                );
+                current = Some(key);
+            } else if has(element, "meta.method.identifier") {
+                if has(element, "variable.parameter") {
+                    parameter.name = value.to_string();
+                    parameter.type_name = std::mem::take(&mut type_name);
+                    if let Some(declaration) = declaration {
+                        declaration.parameters.push(std::mem::take(&mut parameter));
+                    }
+                } else if has(element, "storage.modifier") {
+                    parameter.modifiers.push(value.to_string());
+                } else if !has(element, "punctuation.definition.parameters.begin")
+                    && !has(element, "punctuation.definition.parameters.end")
+                    && (value != "," || JavaSima::is_in_generic(&type_name))
+                {
+                    JavaSima::push_type(&mut type_name, value);
+                }
+            } else if has(element, "meta.throwables") {
+                if let Some(declaration) = declaration {
+                    if has(element, "storage.type") {
+                        declaration.throws.push(value.to_string());
+                    }
+                }
+            } else if has(element, "meta.definition.variable") {
+                if !has(element, "variable.other.definition") {
+                    JavaSima::push_type(&mut type_name, value);
+                } else if !has(element, "meta.method") {
+                    let field = JavaField {
+                        name: value.to_string(),
+                        type_name: std::mem::take(&mut type_name),
+                        modifiers: std::mem::take(&mut modifiers),
+                        annotations: std::mem::take(&mut annotations),
+                        javadoc: javadoc.take(),
+                        line_num: element.line_num,
+                    };
+                    fields.push(field.clone());
+                    declarator = Some(field);
+                    nesting = 0;
+                }
+            } else if declarator.is_some() && value.chars().all(|char| "()<>,".contains(char)) {
+                for char in value.chars() {
+                    match char {
+                        '(' | '<' => nesting += 1,
+                        ')' | '>' => nesting -= 1,
+                        ',' if nesting == 0 => is_next_declarator = true,
+                        _ => {}
+                    }
+                }
+            } else if is_next_declarator
+                && element
+                    .scopes
+                    .iter()
+                    .all(|scope| scope.starts_with("meta.") || scope.starts_with("source."))
+            {
+                // the declarators after the first one are not scoped, like `backup` of `int a, backup;`
+                if let Some(previous) = declarator.as_ref() {
+                    fields.push(JavaField {
+                        name: value.to_string(),
+                        line_num: element.line_num,
+                        ..previous.clone()
+                    });
+                }
+                is_next_declarator = false;
+            } else if has(element, "storage.modifier") {
+                modifiers.push(value.to_string());
+            }
+
+            if value.contains([';', '{', '}']) {
+                annotations.clear();
+                modifiers.clear();
+                type_name.clear();
+                javadoc = None;
+                declarator = None;
+                is_next_declarator = false;
+            }
+        }
+
+        (declarations, fields)
+    }
+
+    /// the separators of parameters and type arguments are the same, like `Map<K, V> map, int a`
+    fn is_in_generic(type_name: &str) -> bool {
+        type_name.matches('<').count() > type_name.matches('>').count()
+    }
+
+    fn push_type(type_name: &mut String, value: &str) {
+        if value == "," {
+            type_name.push_str(", ");
+        } else {
+            type_name.push_str(value);
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use crate::analyser::Analyser;
+    use crate::sima::java_sima::JavaSima;
+    use scie_bingen::bin_gen::BinGen;
+    use scie_model::artifact::{CodeElementKind, LineRange};
     use std::path::PathBuf;
 
-    #[test]
-    fn should_identify_java_hello_world() {
-        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).to_path_buf();
-        let lang = root_dir
-            .clone()
+    fn java_sima(dir: &str, name: &str) -> JavaSima {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
             .parent()
             .unwrap()
             .join("fixtures")
             .join("projects")
             .join("java")
-            .join("hello")
-            .join("HelloWorld.java");
+            .join(dir)
+            .join(name);
+
+        let files = Analyser::ident_by_dir(&path, false, false);
+        JavaSima::from_file(&files[0])
+    }
+
+    #[test]
+    fn should_identify_java_hello_world() {
+        let sima = java_sima("hello", "HelloWorld.java");
+
+        assert_eq!("hello", sima.package);
+        assert_eq!("HelloWorld", sima.classes[0].name);
+
+        let main = &sima.classes[0].methods[0];
+        assert_eq!("main", main.name);
+        assert_eq!(vec!["public", "static"], main.modifiers);
+        assert_eq!(Some("void".to_string()), main.return_type);
+        assert_eq!("args", main.parameters[0].name);
+    }
+
+    #[test]
+    fn should_build_classes_with_annotations_and_javadoc() {
+        let sima = java_sima("sima", "UserController.java");
+
+        assert_eq!("com.phodal.demo", sima.package);
+        assert_eq!(2, sima.imports.len());
+        assert!(sima.imports[1].is_static);
+
+        let controller = &sima.classes[0];
+        assert_eq!("UserController", controller.name);
+        assert_eq!(vec!["BaseController"], controller.extends);
+        assert_eq!(vec!["Api", "Serializable"], controller.implements);
+        assert_eq!(Some(LineRange::new(6, 8)), controller.javadoc);
+        assert_eq!("RestController", controller.annotations[0].name);
+        assert_eq!(
+            "value = \"/users\", produces = {\"a\", \"b\"}",
+            controller.annotations[1].arguments
+        );
+
+        let api = sima
+            .classes
+            .iter()
+            .find(|class| class.name == "Api")
+            .unwrap();
+        assert_eq!(CodeElementKind::Interface, api.kind);
+        assert_eq!(vec!["Base", "Other"], api.extends);
+        assert_eq!("name", api.methods[0].name);
+
+        let status = sima
+            .classes
+            .iter()
+            .find(|class| class.name == "Status")
+            .unwrap();
+        assert_eq!(CodeElementKind::Enum, status.kind);
+        assert_eq!(vec!["ACTIVE", "DISABLED"], status.constants);
+        assert_eq!(Some("UserController".to_string()), status.parent);
+    }
+
+    #[test]
+    fn should_build_fields_and_methods() {
+        let sima = java_sima("sima", "UserController.java");
+        let controller = &sima.classes[0];
+
+        let names: Vec<&str> = controller
+            .fields
+            .iter()
+            .map(|field| field.name.as_str())
+            .collect();
+        assert_eq!(vec!["cache", "backup", "ids"], names);
+        let cache = &controller.fields[0];
+        assert_eq!("Map<String, List<User>>", cache.type_name);
+        assert_eq!(vec!["private", "final"], cache.modifiers);
+        assert_eq!("Autowired", cache.annotations[0].name);
+        assert_eq!(Some(LineRange::new(12, 12)), cache.javadoc);
+        assert_eq!(cache.type_name, controller.fields[1].type_name);
+        assert_eq!("int[]", controller.fields[2].type_name);
+
+        let constructor = &controller.methods[0];
+        assert_eq!("UserController", constructor.name);
+        assert_eq!(None, constructor.return_type);
+
+        let find = &controller.methods[1];
+        assert_eq!(Some("List<User>".to_string()), find.return_type);
+        assert_eq!(vec!["IOException", "SQLException"], find.throws);
+        assert_eq!(LineRange::new(21, 23), find.range);
+        let id = &find.parameters[0];
+        assert_eq!(("id", "String"), (id.name.as_str(), id.type_name.as_str()));
+        assert_eq!(vec!["final"], id.modifiers);
+        assert_eq!("PathVariable", id.annotations[0].name);
+        assert_eq!("int...", find.parameters[1].type_name);
+
+        let convert = &controller.methods[2];
+        assert_eq!(Some("T[]".to_string()), convert.return_type);
+        assert_eq!("Map<String, Integer>", convert.parameters[1].type_name);
+    }
+
+    #[test]
+    fn should_serialize_by_bin_gen() {
+        let sima = java_sima("sima", "UserController.java");
+        let path = std::env::temp_dir().join("java_sima.bin");
 
-        let files = Analyser::ident_by_dir(&lang, false, false);
-        let file = &files[0];
-        println!("{:?}", file);
+        BinGen::encode(&sima, path.to_str().unwrap()).unwrap();
+        let decoded: JavaSima = BinGen::decode(&path).unwrap();
+        assert_eq!(sima, decoded);
     }
 }
@@ -1,4 +1,6 @@
 pub mod java_sima;
+pub mod sima_rewriter;
 pub mod sima_writer;
 
+pub use sima_rewriter::{SimaRewriter, TokenSelector, TokenSpan};
 pub use sima_writer::SimaWriter;
@@ -0,0 +1,418 @@
+use std::fs;
+use std::path::Path;
+
+use scie_model::artifact::{CodeFile, LineRange, TokenElement};
+
+use crate::sima::SimaWriter;
+
+/// selects the tokens like the scope selectors of TextMate, `meta.import storage.modifier` matches
+/// the tokens which have a `meta.import` scope and a `storage.modifier` scope inside it
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct TokenSelector {
+    pub scopes: Vec<String>,
+    // the line range of the tokens, all lines if none
+    pub lines: Option<LineRange>,
+}
+
+impl TokenSelector {
+    pub fn new(selector: &str) -> TokenSelector {
+        TokenSelector {
+            scopes: selector.split_whitespace().map(|s| s.to_string()).collect(),
+            lines: None,
+        }
+    }
+
+    pub fn in_lines(mut self, start: i32, end: i32) -> TokenSelector {
+        self.lines = Some(LineRange::new(start, end));
+        self
+    }
+
+    pub fn matches(&self, element: &TokenElement) -> bool {
+        if let Some(lines) = &self.lines {
+            if element.line_num < lines.start || element.line_num > lines.end {
+                return false;
+            }
+        }
+
+        let mut selectors = self.scopes.iter().peekable();
+        for scope in element
+            .scopes
+            .iter()
+            .flat_map(|scope| scope.split_whitespace())
+        {
+            if let Some(selector) = selectors.peek() {
+                if TokenSelector::is_scope_of(scope, selector) {
+                    selectors.next();
+                }
+            }
+        }
+
+        selectors.peek().is_none()
+    }
+
+    /// `variable.other` is the scope of `variable.other.definition.java`, but not `variable.others`
+    fn is_scope_of(scope: &str, selector: &str) -> bool {
+        scope == selector
+            || (scope.starts_with(selector) && scope[selector.len()..].starts_with('.'))
+    }
+}
+
+/// the contiguous selected tokens in a line, like `java.util.List` of an import
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct TokenSpan {
+    pub line_num: i32,
+    pub start_index: i32,
+    pub end_index: i32,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+struct TextEdit {
+    line_num: i32,
+    start_index: i32,
+    end_index: i32,
+    text: String,
+}
+
+/// edits the code by tokens, and the code which is not edited is kept as is; the indexes are the
+/// UTF-16 code units of a line like `TokenElement`, and the edits should not overlap
+pub struct SimaRewriter {
+    lines: Vec<String>,
+    elements: Vec<TokenElement>,
+    edits: Vec<TextEdit>,
+}
+
+impl SimaRewriter {
+    pub fn new(code_file: &CodeFile) -> SimaRewriter {
+        SimaRewriter {
+            lines: SimaWriter::code_to_string(code_file)
+                .split_inclusive('\n')
+                .map(|line| line.to_string())
+                .collect(),
+            elements: code_file.elements.clone(),
+            edits: vec![],
+        }
+    }
+
+    pub fn select(&self, selector: &TokenSelector) -> Vec<&TokenElement> {
+        self.elements
+            .iter()
+            .filter(|element| selector.matches(element))
+            .collect()
+    }
+
+    pub fn select_spans(&self, selector: &TokenSelector) -> Vec<TokenSpan> {
+        let mut spans: Vec<TokenSpan> = vec![];
+        for element in self.select(selector) {
+            if let Some(span) = spans.last_mut() {
+                if span.line_num == element.line_num && span.end_index == element.start_index {
+                    span.end_index = element.end_index;
+                    span.value.push_str(&element.value);
+                    continue;
+                }
+            }
+
+            spans.push(TokenSpan {
+                line_num: element.line_num,
+                start_index: element.start_index,
+                end_index: element.end_index,
+                value: element.value.clone(),
+            });
+        }
+
+        spans
+    }
+
+    /// replaces the selected tokens by the result of `replacer`, returns the count of replaced
+    pub fn replace_tokens<F>(
+        &mut self,
+        selector: &TokenSelector,
+        replacer: F,
+    ) -> Result<usize, String>
+    where
+        F: Fn(&TokenElement) -> Option<String>,
+    {
+        let edits: Vec<TextEdit> = self
+            .select(selector)
+            .into_iter()
+            .filter_map(|element| {
+                replacer(element).map(|text| TextEdit {
+                    line_num: element.line_num,
+                    start_index: element.start_index,
+                    end_index: element.end_index,
+                    text,
+                })
+            })
+            .collect();
+
+        self.add_edits(edits)
+    }
+
+    /// like `replace_tokens`, but by the spans of tokens
+    pub fn replace_spans<F>(
+        &mut self,
+        selector: &TokenSelector,
+        replacer: F,
+    ) -> Result<usize, String>
+    where
+        F: Fn(&TokenSpan) -> Option<String>,
+    {
+        let edits: Vec<TextEdit> = self
+            .select_spans(selector)
+            .iter()
+            .filter_map(|span| {
+                replacer(span).map(|text| TextEdit {
+                    line_num: span.line_num,
+                    start_index: span.start_index,
+                    end_index: span.end_index,
+                    text,
+                })
+            })
+            .collect();
+
+        self.add_edits(edits)
+    }
+
+    pub fn replace_range(
+        &mut self,
+        line_num: i32,
+        start_index: i32,
+        end_index: i32,
+        text: &str,
+    ) -> Result<(), String> {
+        self.add_edits(vec![TextEdit {
+            line_num,
+            start_index,
+            end_index,
+            text: text.to_string(),
+        }])
+        .map(|_| ())
+    }
+
+    /// the inserts at same position are kept by the order of calls
+    pub fn insert(&mut self, line_num: i32, index: i32, text: &str) -> Result<(), String> {
+        self.replace_range(line_num, index, index, text)
+    }
+
+    pub fn emit(&self) -> String {
+        let mut edits: Vec<&TextEdit> = self.edits.iter().collect();
+        edits.sort_by_key(|edit| (edit.line_num, edit.start_index, edit.end_index));
+
+        let mut code = String::new();
+        let mut edits = edits.into_iter().peekable();
+        for (index, line) in self.lines.iter().enumerate() {
+            let line_num = index as i32 + 1;
+            let mut position = 0;
+            while let Some(edit) = edits.next_if(|edit| edit.line_num == line_num) {
+                let start = SimaRewriter::byte_index(line, edit.start_index);
+                code.push_str(&line[position..start]);
+                code.push_str(&edit.text);
+                position = SimaRewriter::byte_index(line, edit.end_index);
+            }
+            code.push_str(&line[position..]);
+        }
+
+        code
+    }
+
+    pub fn emit_to_file(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.emit()).map_err(|err| err.to_string())
+    }
+
+    fn add_edits(&mut self, edits: Vec<TextEdit>) -> Result<usize, String> {
+        for (index, edit) in edits.iter().enumerate() {
+            let line = match self.lines.get((edit.line_num - 1).max(0) as usize) {
+                Some(line) if edit.line_num > 0 => line,
+                _ => return Err(format!("line {} is out of file", edit.line_num)),
+            };
+            let length = line.trim_end_matches(['\r', '\n']).encode_utf16().count() as i32;
+            if edit.start_index < 0 || edit.start_index > edit.end_index || edit.end_index > length
+            {
+                return Err(format!(
+                    "{}:{}-{} is out of line",
+                    edit.line_num, edit.start_index, edit.end_index
+                ));
+            }
+
+            if let Some(other) = self
+                .edits
+                .iter()
+                .chain(edits.iter().take(index))
+                .find(|other| SimaRewriter::is_overlapped(edit, other))
+            {
+                return Err(format!(
+                    "{}:{}-{} is overlapped with {}:{}-{}",
+                    edit.line_num,
+                    edit.start_index,
+                    edit.end_index,
+                    other.line_num,
+                    other.start_index,
+                    other.end_index
+                ));
+            }
+        }
+
+        let count = edits.len();
+        self.edits.extend(edits);
+        Ok(count)
+    }
+
+    /// the inserts can be at the boundaries of replaced ranges, but not inside them
+    fn is_overlapped(a: &TextEdit, b: &TextEdit) -> bool {
+        if a.line_num != b.line_num {
+            return false;
+        }
+        if a.start_index == a.end_index && b.start_index == b.end_index {
+            return false;
+        }
+
+        a.start_index < b.end_index && b.start_index < a.end_index
+            || a.start_index == b.start_index && a.end_index == b.end_index
+    }
+
+    // the byte index of the UTF-16 index, like the emojis which are two code units
+    fn byte_index(line: &str, utf16_index: i32) -> usize {
+        let mut units = 0;
+        for (index, char) in line.char_indices() {
+            if units >= utf16_index {
+                return index;
+            }
+            units += char.len_utf16() as i32;
+        }
+
+        line.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::analyser::Identify;
+    use crate::sima::{SimaRewriter, TokenSelector};
+    use scie_model::artifact::{CodeFile, LineBreaks};
+
+    fn java_file(code: &str) -> CodeFile {
+        let lang = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("extensions")
+            .join("java")
+            .join("syntaxes")
+            .join("java.tmLanguage.json");
+
+        let mut file = CodeFile::new(PathBuf::from("Hello.java"));
+        file.elements = Identify::identify_file(lang, code.to_string());
+        file.line_breaks = LineBreaks::from_code(code);
+        file
+    }
+
+    const CODE: &str = "import java.util.List;\r\n\r\nclass Hello {\r\n\tprivate List<String> names;\r\n\tint size() {\r\n\t\treturn  names.size(); // 名字\r\n\t}\r\n}";
+
+    #[test]
+    fn should_match_scope_selector() {
+        let rewriter = SimaRewriter::new(&java_file(CODE));
+
+        let selector = TokenSelector::new("meta.import storage.modifier.import");
+        let values: Vec<&str> = rewriter
+            .select(&selector)
+            .iter()
+            .map(|element| element.value.as_str())
+            .collect();
+        assert_eq!(vec!["java", ".", "util", ".", "List"], values);
+
+        assert_eq!(
+            0,
+            rewriter
+                .select(&TokenSelector::new("storage.modifier.imports"))
+                .len()
+        );
+        assert_eq!(0, rewriter.select(&selector.in_lines(2, 8)).len());
+    }
+
+    #[test]
+    fn should_keep_code_without_edits() {
+        let rewriter = SimaRewriter::new(&java_file(CODE));
+        assert_eq!(CODE, rewriter.emit());
+    }
+
+    #[test]
+    fn should_rename_variables() {
+        let mut rewriter = SimaRewriter::new(&java_file(CODE));
+        let count = rewriter
+            .replace_tokens(&TokenSelector::new("variable.other"), |element| {
+                (element.value == "names").then(|| "items".to_string())
+            })
+            .unwrap();
+
+        assert_eq!(2, count);
+        assert_eq!(CODE.replace("names", "items"), rewriter.emit());
+    }
+
+    #[test]
+    fn should_swap_import() {
+        let mut rewriter = SimaRewriter::new(&java_file(CODE));
+        rewriter
+            .replace_spans(&TokenSelector::new("storage.modifier.import"), |span| {
+                (span.value == "java.util.List").then(|| "java.util.ArrayList".to_string())
+            })
+            .unwrap();
+        rewriter.insert(1, 0, "import java.util.Map;\r\n").unwrap();
+
+        assert_eq!(
+            CODE.replace(
+                "import java.util.List;",
+                "import java.util.Map;\r\nimport java.util.ArrayList;"
+            ),
+            rewriter.emit()
+        );
+    }
+
+    #[test]
+    fn should_rewrite_after_emoji() {
+        let code = "class Hello {\n\tString earth = \"🌏\"; int size = 1;\n}";
+        let mut rewriter = SimaRewriter::new(&java_file(code));
+        // the tokens after the emoji are picked by their values
+        let count = rewriter
+            .replace_tokens(&TokenSelector::new("source.java"), |element| match element
+                .value
+                .as_str()
+            {
+                "size" => Some("count".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        let spans = rewriter
+            .replace_spans(&TokenSelector::new("constant.numeric"), |span| {
+                match span.value.as_str() {
+                    "1" => Some("2".to_string()),
+                    _ => None,
+                }
+            })
+            .unwrap();
+        // the `;` is at 20 by UTF-16, and at 19 by chars
+        rewriter.replace_range(2, 20, 21, ",").unwrap();
+
+        assert_eq!((1, 1), (count, spans));
+        assert_eq!(
+            "class Hello {\n\tString earth = \"🌏\", int count = 2;\n}",
+            rewriter.emit()
+        );
+    }
+
+    #[test]
+    fn should_reject_overlapped_edits() {
+        let mut rewriter = SimaRewriter::new(&java_file(CODE));
+        rewriter.replace_range(6, 10, 15, "items").unwrap();
+
+        assert!(rewriter.replace_range(6, 12, 17, "x").is_err());
+        assert!(rewriter.insert(6, 12, "x").is_err());
+        assert!(rewriter.insert(20, 0, "x").is_err());
+        assert!(rewriter.insert(6, 10, "this.").is_ok());
+
+        assert_eq!(
+            CODE.replace("  names.size()", "  this.items.size()"),
+            rewriter.emit()
+        );
+    }
+}
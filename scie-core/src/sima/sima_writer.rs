@@ -1,7 +1,7 @@
 use std::fs::File;
 use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
-use scie_model::artifact::CodeFile;
+use scie_model::artifact::{CodeFile, TokenElement};
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct SimaWriter {}
@@ -9,41 +9,57 @@ pub struct SimaWriter {}
 impl SimaWriter {
     pub fn code_to_file(code_file: &CodeFile) -> Cursor<Vec<u8>> {
         let mut c = Cursor::new(Vec::new());
+        c.write_all(SimaWriter::code_to_string(code_file).as_bytes())
+            .unwrap();
 
-        let mut current_line = 1;
-        let mut position = 0;
-        let mut index = 0;
+        c.seek(SeekFrom::Start(0)).unwrap();
+        c
+    }
 
-        let mut stop = false;
-        let length = code_file.elements.len();
+    /// the tokens of a line are contiguous, so the code is the values of tokens and the
+    /// `line_breaks`; the gaps of tokens (which should not happen) are filled with spaces
+    pub fn code_to_string(code_file: &CodeFile) -> String {
+        let breaks = &code_file.line_breaks;
+        let last_line = code_file
+            .elements
+            .iter()
+            .map(|element| element.line_num)
+            .max()
+            .unwrap_or(0)
+            .max(breaks.lines);
+
+        let mut code = String::new();
+        let mut elements = code_file.elements.iter().peekable();
+        for line_num in 1..=last_line {
+            let mut position = 0;
+            while let Some(element) = elements.next_if(|element| element.line_num <= line_num) {
+                // the empty tokens may be out of the line, like the end of lines with wide chars
+                if element.line_num < line_num || element.value.is_empty() {
+                    continue;
+                }
 
-        while !stop {
-            let element = &code_file.elements[index];
-            if current_line < element.line_num {
-                c.write_all("\n".as_ref()).unwrap();
-                current_line = current_line + 1;
-                continue;
+                if element.start_index > position {
+                    code.push_str(&" ".repeat((element.start_index - position) as usize));
+                    position = element.start_index;
+                }
+                // skip the overlapped chars, the indexes are UTF-16 code units
+                let units: Vec<u16> = element.value.encode_utf16().collect();
+                let skip = position - element.start_index;
+                code.push_str(&TokenElement::utf16_value(&units, skip, units.len() as i32));
+                position = position.max(element.start_index + units.len() as i32);
             }
 
-            if element.start_index == position {
-                c.write_all(element.value.as_ref()).unwrap();
-                position = element.end_index;
-            } else {
-                let has_next = index < length - 1;
-                if has_next {
-                    let next_element = &code_file.elements[index + 1];
-                    let offset = next_element.end_index - position as i32;
-                    c.write_all(" ".repeat(offset as usize).as_ref()).unwrap();
+            if breaks.lines == 0 {
+                // the code files which are serialized without line breaks
+                if line_num < last_line {
+                    code.push('\n');
                 }
+            } else {
+                code.push_str(breaks.line_break(line_num));
             }
-            if index + 1 == length {
-                stop = true;
-            }
-            index = index + 1;
         }
 
-        c.seek(SeekFrom::Start(0)).unwrap();
-        c
+        code
     }
 
     fn output_to_file(path: String, c: &mut Cursor<Vec<u8>>) {
@@ -71,17 +87,35 @@ mod tests {
     use std::io::Read;
     use std::path::PathBuf;
 
-    use crate::analyser::Analyser;
+    use crate::analyser::{Analyser, Identify};
     use crate::sima::SimaWriter;
+    use scie_model::artifact::{CodeFile, LineBreaks};
 
-    #[test]
-    fn should_build_from_element() {
-        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).to_path_buf();
-        let lang = root_dir
-            .clone()
+    fn fixtures() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
             .parent()
             .unwrap()
             .join("fixtures")
+    }
+
+    fn java_file(code: &str) -> CodeFile {
+        let lang = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("extensions")
+            .join("java")
+            .join("syntaxes")
+            .join("java.tmLanguage.json");
+
+        let mut file = CodeFile::new(PathBuf::from("Hello.java"));
+        file.elements = Identify::identify_file(lang, code.to_string());
+        file.line_breaks = LineBreaks::from_code(code);
+        file
+    }
+
+    #[test]
+    fn should_build_from_element() {
+        let lang = fixtures()
             .join("projects")
             .join("java")
             .join("simple")
@@ -99,4 +133,37 @@ mod tests {
         assert_eq!(14, output.len());
         assert_eq!(origin_buffer.clone(), output);
     }
+
+    #[test]
+    fn should_rebuild_crlf_tabs_and_unicode() {
+        let code =
+            "class Hello {\r\n\tString name = \"你好, 世界 🌏\";  \r\n\r\n}\n// end\t\n// 名字";
+        let output = SimaWriter::code_to_string(&java_file(code));
+
+        assert_eq!(code, output);
+    }
+
+    #[test]
+    fn should_rebuild_empty_lines_and_files() {
+        for code in ["", "\n", "\n\n\n", "class Hello {}\n\n\n"] {
+            assert_eq!(code, SimaWriter::code_to_string(&java_file(code)));
+        }
+
+        let empty = CodeFile::new(PathBuf::from("Empty.java"));
+        assert_eq!("", SimaWriter::code_to_string(&empty));
+    }
+
+    #[test]
+    fn should_rebuild_all_fixtures() {
+        let mut count = 0;
+        for dir in ["projects", "samples", "test-cases"] {
+            for code_file in Analyser::ident_by_dir(&fixtures().join(dir), false, false) {
+                let origin = std::fs::read_to_string(&code_file.path).unwrap();
+                let output = SimaWriter::code_to_string(&code_file);
+                assert!(origin == output, "{}", code_file.path);
+                count += 1;
+            }
+        }
+        assert!(count > 0);
+    }
 }
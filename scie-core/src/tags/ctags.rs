@@ -0,0 +1,133 @@
+use crate::tags::TagEntry;
+use scie_model::artifact::CodeFile;
+use std::path::Path;
+
+const HEADER: [(&str, &str, &str); 4] = [
+    (
+        "!_TAG_FILE_FORMAT",
+        "2",
+        "extended format; --format=1 will not append ;\" to lines",
+    ),
+    ("!_TAG_FILE_SORTED", "1", "0=unsorted, 1=sorted, 2=foldcase"),
+    ("!_TAG_PROGRAM_NAME", "scie", ""),
+    ("!_TAG_PROGRAM_URL", "https://github.com/phodal/scie", ""),
+];
+
+/// the `tags` file of Universal Ctags in the extended format, like:
+///
+/// ```text
+/// getNames<TAB>src/Demo.java<TAB>/^    public List<String> getNames() {$/;"<TAB>kind:method<TAB>line:14<TAB>class:Demo<TAB>end:16
+/// ```
+pub struct Ctags {}
+
+impl Ctags {
+    /// `base` is the directory of `tags` file
+    pub fn generate(files: &[CodeFile], base: &Path) -> String {
+        Ctags::write(&TagEntry::from_files(files, base))
+    }
+
+    /// the lines are sorted by the bytes of names, for the binary search of editors
+    pub fn write(entries: &[TagEntry]) -> String {
+        let mut entries: Vec<&TagEntry> = entries.iter().collect();
+        entries.sort_by(|a, b| {
+            (a.name.as_bytes(), &a.path, a.range.start).cmp(&(
+                b.name.as_bytes(),
+                &b.path,
+                b.range.start,
+            ))
+        });
+
+        let mut output = String::new();
+        for (name, value, comment) in HEADER.iter() {
+            output.push_str(&format!("{}\t{}\t/{}/\n", name, value, comment));
+        }
+        for entry in entries {
+            output.push_str(&Ctags::line(entry));
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn line(entry: &TagEntry) -> String {
+        let kind = TagEntry::kind_name(entry.kind).unwrap_or("unknown");
+        let mut fields = vec![
+            format!("kind:{}", kind),
+            format!("line:{}", entry.range.start),
+        ];
+        if let Some((scope_kind, scope)) = &entry.scope {
+            let scope_kind = TagEntry::kind_name(*scope_kind).unwrap_or("unknown");
+            fields.push(format!("{}:{}", scope_kind, scope));
+        }
+        fields.push(format!("end:{}", entry.range.end));
+
+        format!(
+            "{}\t{}\t/^{}$/;\"\t{}",
+            entry.name,
+            entry.path,
+            Ctags::escape(&entry.text),
+            fields.join("\t")
+        )
+    }
+
+    /// the search pattern of ex, which escapes `\` and `/`
+    fn escape(text: &str) -> String {
+        text.replace('\\', "\\\\").replace('/', "\\/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyser::{ElementExtractor, Identify};
+    use crate::tags::Ctags;
+    use scie_model::artifact::{CodeFile, LineBreaks};
+    use std::path::{Path, PathBuf};
+
+    fn java_file(code: &str) -> CodeFile {
+        let lang = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("extensions")
+            .join("java/syntaxes/java.tmLanguage.json");
+
+        let mut file = CodeFile::new(PathBuf::from("/demo/src/Demo.java"));
+        file.elements = Identify::identify_file(lang, code.to_string());
+        file.code_elements = ElementExtractor::extract(&file.elements);
+        file.line_breaks = LineBreaks::from_code(code);
+        file
+    }
+
+    #[test]
+    fn should_generate_ctags() {
+        let code = "package demo;
+
+public class Demo {
+    private String path = \"a/b\";
+
+    public String getPath() {
+        return path;
+    }
+
+    interface Inner {
+        void call();
+    }
+}
+";
+        let tags = Ctags::generate(&[java_file(code)], Path::new("/demo"));
+        let lines: Vec<&str> = tags.lines().collect();
+
+        assert!(lines[0].starts_with("!_TAG_FILE_FORMAT\t2\t"));
+        assert_eq!(
+            "Demo\tsrc/Demo.java\t/^public class Demo {$/;\"\tkind:class\tline:3\tend:13",
+            lines[4]
+        );
+        assert_eq!(
+            "call\tsrc/Demo.java\t/^        void call();$/;\"\tkind:method\tline:11\tinterface:Demo.Inner\tend:11",
+            lines[6]
+        );
+        assert_eq!(
+            "path\tsrc/Demo.java\t/^    private String path = \"a\\/b\";$/;\"\tkind:field\tline:4\tclass:Demo\tend:4",
+            lines[9]
+        );
+    }
+}
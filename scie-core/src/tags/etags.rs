@@ -0,0 +1,95 @@
+use crate::tags::TagEntry;
+use scie_model::artifact::CodeFile;
+use std::path::Path;
+
+/// the `TAGS` file of Emacs, a section for each file:
+///
+/// ```text
+/// \x0c
+/// src/Demo.java,<size of the tags>
+/// public class Demo\x7fDemo\x016,120
+/// ```
+///
+/// a tag is the text of line until the name, the name, the line and the offset of line
+pub struct Etags {}
+
+impl Etags {
+    /// `base` is the directory of `TAGS` file
+    pub fn generate(files: &[CodeFile], base: &Path) -> String {
+        Etags::write(&TagEntry::from_files(files, base))
+    }
+
+    /// the sections are in the order of files, and the tags are in the order of lines
+    pub fn write(entries: &[TagEntry]) -> String {
+        let mut paths: Vec<&str> = vec![];
+        for entry in entries {
+            if !paths.contains(&entry.path.as_str()) {
+                paths.push(&entry.path);
+            }
+        }
+
+        let mut output = String::new();
+        for path in paths {
+            let mut tags: Vec<&TagEntry> = entries.iter().filter(|it| it.path == path).collect();
+            tags.sort_by_key(|entry| entry.range.start);
+
+            let body: String = tags.iter().map(|entry| Etags::line(entry)).collect();
+            output.push_str(&format!("\x0c\n{},{}\n{}", path, body.len(), body));
+        }
+
+        output
+    }
+
+    fn line(entry: &TagEntry) -> String {
+        let text = match entry.text.find(&entry.name) {
+            Some(index) => &entry.text[..index + entry.name.len()],
+            None => &entry.text,
+        };
+
+        format!(
+            "{}\x7f{}\x01{},{}\n",
+            text, entry.name, entry.range.start, entry.offset
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tags::{Etags, TagEntry};
+    use scie_model::artifact::{CodeElementKind, LineRange};
+
+    fn entry(path: &str, name: &str, line: i32, text: &str, offset: usize) -> TagEntry {
+        TagEntry {
+            name: name.to_string(),
+            path: path.to_string(),
+            kind: CodeElementKind::Function,
+            range: LineRange::new(line, line),
+            scope: None,
+            text: text.to_string(),
+            offset,
+        }
+    }
+
+    #[test]
+    fn should_generate_etags() {
+        let entries = vec![
+            entry("src/main.rs", "run", 5, "pub fn run() {", 40),
+            entry("src/lib.rs", "parse", 1, "fn parse(text: &str) {", 0),
+            entry("src/main.rs", "main", 1, "fn main() {", 0),
+        ];
+
+        let tags = Etags::write(&entries);
+        let main_tags = "fn main\x7fmain\x011,0\npub fn run\x7frun\x015,40\n";
+        let lib_tags = "fn parse\x7fparse\x011,0\n";
+        assert_eq!(
+            format!(
+                "\x0c\nsrc/main.rs,{}\n{}\x0c\nsrc/lib.rs,{}\n{}",
+                main_tags.len(),
+                main_tags,
+                lib_tags.len(),
+                lib_tags
+            ),
+            tags
+        );
+    }
+}
@@ -0,0 +1,7 @@
+pub mod ctags;
+pub mod etags;
+pub mod tag_entry;
+
+pub use ctags::Ctags;
+pub use etags::Etags;
+pub use tag_entry::TagEntry;
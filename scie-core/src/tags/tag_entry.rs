@@ -0,0 +1,139 @@
+use crate::analyser::ElementExtractor;
+use crate::sima::SimaWriter;
+use scie_model::artifact::{CodeElement, CodeElementKind, CodeFile, LineRange};
+use std::path::Path;
+
+/// a symbol of tags files, from the `CodeElement`s of code files
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct TagEntry {
+    pub name: String,
+    // the path relative to the directory of tags file
+    pub path: String,
+    pub kind: CodeElementKind,
+    pub range: LineRange,
+    // the kind and the qualified name of enclosing element, like `Demo.Inner` of a method
+    pub scope: Option<(CodeElementKind, String)>,
+    // the text of the line without line break
+    pub text: String,
+    // the offset of the line by bytes
+    pub offset: usize,
+}
+
+impl TagEntry {
+    /// `base` is the directory of tags file, the paths out of it are kept
+    pub fn from_files(files: &[CodeFile], base: &Path) -> Vec<TagEntry> {
+        files
+            .iter()
+            .flat_map(|file| TagEntry::from_file(file, base))
+            .collect()
+    }
+
+    pub fn from_file(file: &CodeFile, base: &Path) -> Vec<TagEntry> {
+        // the results which are written without code elements
+        let extracted;
+        let elements = if file.code_elements.is_empty() {
+            extracted = ElementExtractor::extract(&file.elements);
+            &extracted
+        } else {
+            &file.code_elements
+        };
+
+        let path = Path::new(&file.path);
+        let path = path
+            .strip_prefix(base)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        let code = SimaWriter::code_to_string(file);
+        let mut lines = vec![];
+        let mut offset = 0;
+        for line in code.split('\n') {
+            lines.push((line.trim_end_matches('\r'), offset));
+            offset += line.len() + 1;
+        }
+
+        let parents = TagEntry::parents(elements);
+        elements
+            .iter()
+            .enumerate()
+            .filter(|(_, element)| TagEntry::kind_name(element.kind).is_some())
+            .map(|(index, element)| {
+                let (text, offset) = lines
+                    .get(element.range.start as usize - 1)
+                    .copied()
+                    .unwrap_or(("", 0));
+                let scope = element.parent.as_ref().map(|parent| {
+                    let kind = parents[index]
+                        .map(|index| elements[index].kind)
+                        .unwrap_or(CodeElementKind::Type);
+                    let mut names = vec![parent.clone()];
+                    let mut current = parents[index];
+                    while let Some(index) = current {
+                        match (&elements[index].parent, parents[index]) {
+                            (Some(name), next) if names.len() < elements.len() => {
+                                names.insert(0, name.clone());
+                                current = next;
+                            }
+                            _ => break,
+                        }
+                    }
+
+                    (kind, names.join("."))
+                });
+
+                TagEntry {
+                    name: element.name.clone(),
+                    path: path.clone(),
+                    kind: element.kind,
+                    range: element.range.clone(),
+                    scope,
+                    text: text.to_string(),
+                    offset,
+                }
+            })
+            .collect()
+    }
+
+    /// the names of kinds in Universal Ctags, the imports are not tags
+    pub fn kind_name(kind: CodeElementKind) -> Option<&'static str> {
+        match kind {
+            CodeElementKind::Package => Some("package"),
+            CodeElementKind::Import => None,
+            CodeElementKind::Module => Some("module"),
+            CodeElementKind::Class => Some("class"),
+            CodeElementKind::Interface => Some("interface"),
+            CodeElementKind::Enum => Some("enum"),
+            CodeElementKind::Struct => Some("struct"),
+            CodeElementKind::Trait => Some("trait"),
+            CodeElementKind::Impl => Some("implementation"),
+            CodeElementKind::Type => Some("typedef"),
+            CodeElementKind::Function => Some("function"),
+            CodeElementKind::Method => Some("method"),
+            CodeElementKind::Field => Some("field"),
+        }
+    }
+
+    /// the index of parent by name, which is the innermost type contains the element, or the
+    /// type of the name in the file, like the receivers of Go
+    fn parents(elements: &[CodeElement]) -> Vec<Option<usize>> {
+        elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| {
+                let parent = element.parent.as_ref()?;
+                let candidates = || {
+                    elements.iter().enumerate().filter(move |(other, it)| {
+                        *other != index && it.kind.is_type() && &it.name == parent
+                    })
+                };
+
+                candidates()
+                    .filter(|(_, it)| it.range.contains(element.range.start))
+                    .max_by_key(|(_, it)| it.range.start)
+                    .or_else(|| candidates().next())
+                    .map(|(other, _)| other)
+            })
+            .collect()
+    }
+}
@@ -1,3 +1,6 @@
+use scie_model::artifact::CodeFileKind;
+use std::path::Path;
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct ContentRoot {
     pub root_dirs: Vec<String>,
@@ -8,6 +11,9 @@ pub struct ContentRoot {
     pub get_test_dirs: Vec<String>,
     pub get_test_source_dirs: Vec<String>,
     pub exclude_dirs: Vec<String>,
+    // for languages which keep tests next to sources, like `*_test.go`, `*.spec.ts` and `__tests__`
+    #[serde(default)]
+    pub test_patterns: Vec<String>,
 }
 
 impl Default for ContentRoot {
@@ -21,6 +27,155 @@ impl Default for ContentRoot {
             get_test_dirs: vec![],
             get_test_source_dirs: vec![],
             exclude_dirs: vec![],
+            test_patterns: vec![],
+        }
+    }
+}
+
+impl ContentRoot {
+    pub fn new(root: &Path) -> ContentRoot {
+        let mut content_root = ContentRoot::default();
+        content_root.root_dirs.push(root.display().to_string());
+        content_root
+    }
+
+    pub fn root(&self) -> &str {
+        self.root_dirs.first().map(|dir| dir.as_str()).unwrap_or("")
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.root_dirs
+            .iter()
+            .any(|root| path.starts_with(Path::new(root)))
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        ContentRoot::in_dirs(&self.exclude_dirs, path)
+    }
+
+    /// the generated dirs maybe under source dirs, like `build/generated` in gradle, so check it first
+    pub fn classify(&self, path: &Path) -> CodeFileKind {
+        if ContentRoot::in_dirs(&self.gen_source_dirs, path) {
+            return CodeFileKind::Generated;
+        }
+
+        if ContentRoot::in_dirs(&self.test_source_dirs, path) || self.match_test_pattern(path) {
+            return CodeFileKind::Test;
+        }
+
+        if ContentRoot::in_dirs(&self.resource_dirs, path) {
+            return CodeFileKind::Resource;
+        }
+
+        if ContentRoot::in_dirs(&self.source_dirs, path) {
+            return CodeFileKind::Source;
+        }
+
+        CodeFileKind::Unknown
+    }
+
+    pub fn add_dir(dirs: &mut Vec<String>, dir: &Path) {
+        let dir = dir.display().to_string();
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
         }
     }
+
+    /// add conventional dirs only when it exists, the `*_dirs` of overrides are always added
+    pub fn add_existing_dirs(dirs: &mut Vec<String>, root: &Path, names: &[&str]) {
+        for name in names {
+            let dir = root.join(name);
+            if dir.exists() {
+                ContentRoot::add_dir(dirs, &dir);
+            }
+        }
+    }
+
+    fn in_dirs(dirs: &[String], path: &Path) -> bool {
+        dirs.iter().any(|dir| path.starts_with(Path::new(dir)))
+    }
+
+    /// patterns match the file name, or any dir name in path when ends with `/`
+    fn match_test_pattern(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(self.root()).unwrap_or(path);
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+
+        self.test_patterns.iter().any(|pattern| {
+            if let Some(dir_pattern) = pattern.strip_suffix('/') {
+                relative
+                    .parent()
+                    .map(|parent| {
+                        parent.components().any(|component| {
+                            let name = component.as_os_str().to_str().unwrap_or("");
+                            ContentRoot::wildcard_match(dir_pattern, name)
+                        })
+                    })
+                    .unwrap_or(false)
+            } else {
+                ContentRoot::wildcard_match(pattern, file_name)
+            }
+        })
+    }
+
+    /// simple glob which only support `*`
+    pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+        let parts: Vec<&str> = pattern.split('*').collect();
+        if parts.len() == 1 {
+            return pattern == text;
+        }
+
+        let first = parts[0];
+        let last = parts[parts.len() - 1];
+        if text.len() < first.len() + last.len()
+            || !text.starts_with(first)
+            || !text.ends_with(last)
+        {
+            return false;
+        }
+
+        let mut rest = &text[first.len()..text.len() - last.len()];
+        for part in &parts[1..parts.len() - 1] {
+            match rest.find(part) {
+                Some(index) => rest = &rest[index + part.len()..],
+                None => return false,
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content_root::ContentRoot;
+    use scie_model::artifact::CodeFileKind;
+    use std::path::Path;
+
+    #[test]
+    fn should_match_wildcard() {
+        assert!(ContentRoot::wildcard_match("*_test.go", "main_test.go"));
+        assert!(ContentRoot::wildcard_match("*.spec.*", "app.spec.ts"));
+        assert!(ContentRoot::wildcard_match("test_*.py", "test_app.py"));
+        assert!(!ContentRoot::wildcard_match("*_test.go", "main.go"));
+        assert!(!ContentRoot::wildcard_match("*.spec.*", "spec.ts"));
+    }
+
+    #[test]
+    fn should_classify_by_dirs_and_patterns() {
+        let mut root = ContentRoot::new(Path::new("web"));
+        root.source_dirs.push("web/src".to_string());
+        root.gen_source_dirs.push("web/src/generated".to_string());
+        root.test_patterns.push("__tests__/".to_string());
+        root.test_patterns.push("*.test.*".to_string());
+
+        let kind = |path: &str| root.classify(Path::new(path));
+        assert_eq!(CodeFileKind::Source, kind("web/src/app.js"));
+        assert_eq!(CodeFileKind::Test, kind("web/src/app.test.js"));
+        assert_eq!(CodeFileKind::Test, kind("web/src/__tests__/app.js"));
+        assert_eq!(CodeFileKind::Generated, kind("web/src/generated/api.js"));
+        assert_eq!(CodeFileKind::Unknown, kind("web/README.md"));
+    }
 }
@@ -0,0 +1,126 @@
+use crate::content_root::ContentRoot;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+pub struct CargoConvention {}
+
+impl CargoConvention {
+    /// `src`, `tests`, `benches` and `examples`, with the `path` of `[lib]`, `[[bin]]`, `[[test]]` ... targets
+    pub fn content_root(module_dir: &Path) -> ContentRoot {
+        let mut root = ContentRoot::new(module_dir);
+        ContentRoot::add_existing_dirs(
+            &mut root.source_dirs,
+            module_dir,
+            &["src", "examples", "build.rs"],
+        );
+        ContentRoot::add_existing_dirs(
+            &mut root.test_source_dirs,
+            module_dir,
+            &["tests", "benches"],
+        );
+        ContentRoot::add_dir(&mut root.exclude_dirs, &module_dir.join("target"));
+
+        let manifest = CargoConvention::manifest(module_dir);
+        for (key, is_test) in [
+            ("lib", false),
+            ("bin", false),
+            ("example", false),
+            ("test", true),
+            ("bench", true),
+        ]
+        .iter()
+        {
+            let targets = match manifest.get(key) {
+                Some(Value::Table(table)) => vec![Value::Table(table.clone())],
+                Some(Value::Array(targets)) => targets.clone(),
+                _ => continue,
+            };
+
+            for target in targets {
+                let path = match target.get("path").and_then(|path| path.as_str()) {
+                    Some(path) => module_dir.join(path),
+                    None => continue,
+                };
+                let dirs = if *is_test {
+                    &mut root.test_source_dirs
+                } else {
+                    &mut root.source_dirs
+                };
+                ContentRoot::add_dir(dirs, &path);
+            }
+        }
+
+        root
+    }
+
+    /// the `[workspace] members`, glob is only supported at the end, like `crates/*`
+    pub fn members(root_dir: &Path) -> Vec<PathBuf> {
+        let manifest = CargoConvention::manifest(root_dir);
+        let members = manifest
+            .get("workspace")
+            .and_then(|workspace| workspace.get("members"))
+            .and_then(|members| members.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        let mut dirs = vec![];
+        for member in members.iter().filter_map(|member| member.as_str()) {
+            match member.strip_suffix("/*") {
+                Some(parent) => {
+                    if let Ok(entries) = fs::read_dir(root_dir.join(parent)) {
+                        let mut children: Vec<PathBuf> = entries
+                            .filter_map(|entry| entry.ok())
+                            .map(|entry| entry.path())
+                            .filter(|path| path.join("Cargo.toml").exists())
+                            .collect();
+                        children.sort();
+                        dirs.append(&mut children);
+                    }
+                }
+                None => dirs.push(root_dir.join(member)),
+            }
+        }
+
+        dirs
+    }
+
+    fn manifest(dir: &Path) -> Value {
+        fs::read_to_string(dir.join("Cargo.toml"))
+            .ok()
+            .and_then(|code| code.parse::<Value>().ok())
+            .unwrap_or_else(|| Value::Table(Default::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::convention::CargoConvention;
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_build_cargo_content_root_for_self() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let content_root = CargoConvention::content_root(&root);
+
+        assert_eq!(
+            vec![root.join("src").display().to_string()],
+            content_root.source_dirs
+        );
+        assert_eq!(
+            vec![root.join("target").display().to_string()],
+            content_root.exclude_dirs
+        );
+    }
+
+    #[test]
+    fn should_read_workspace_members() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .to_path_buf();
+        let members = CargoConvention::members(&root);
+
+        assert!(members.contains(&root.join("scie-detector")));
+    }
+}
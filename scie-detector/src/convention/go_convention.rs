@@ -0,0 +1,17 @@
+use crate::content_root::ContentRoot;
+use std::path::Path;
+
+pub struct GoConvention {}
+
+impl GoConvention {
+    /// go keep tests next to sources, and `testdata` is ignored by the go tool
+    pub fn content_root(module_dir: &Path) -> ContentRoot {
+        let mut root = ContentRoot::new(module_dir);
+        ContentRoot::add_dir(&mut root.source_dirs, module_dir);
+        ContentRoot::add_dir(&mut root.exclude_dirs, &module_dir.join("vendor"));
+        root.test_patterns.push("*_test.go".to_string());
+        root.test_patterns.push("testdata/".to_string());
+
+        root
+    }
+}
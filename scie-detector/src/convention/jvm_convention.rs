@@ -0,0 +1,267 @@
+use crate::content_root::ContentRoot;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    static ref QUOTED: Regex = Regex::new(r#"["']([^"']+)["']"#).unwrap();
+    static ref GRADLE_INCLUDE: Regex = Regex::new(r"(?m)^\s*include\s*\(?(.*)$").unwrap();
+    // `main.java.srcDirs = ['src']` or `test.resources.srcDir 'res'`
+    static ref GRADLE_DOT_SRC_DIRS: Regex =
+        Regex::new(r"(main|test)\.(java|kotlin|groovy|scala|resources)\.srcDirs?\s*(\+?=)?\s*\[?([^\]\n]*)")
+            .unwrap();
+    // `java { srcDirs = ['src'] }` inside the `main { }` or `test { }` block
+    static ref GRADLE_BLOCK_SRC_DIRS: Regex =
+        Regex::new(r"(java|kotlin|groovy|scala|resources)\s*\{[^}]*?srcDirs?\s*(\+?=)?\s*\[?([^\]\n}]*)")
+            .unwrap();
+    static ref MAVEN_MODULE: Regex = Regex::new(r"<module>\s*([^<\s]+)\s*</module>").unwrap();
+    static ref MAVEN_SOURCE_DIR: Regex =
+        Regex::new(r"<sourceDirectory>\s*([^<\s]+)\s*</sourceDirectory>").unwrap();
+    static ref MAVEN_TEST_SOURCE_DIR: Regex =
+        Regex::new(r"<testSourceDirectory>\s*([^<\s]+)\s*</testSourceDirectory>").unwrap();
+    static ref MAVEN_RESOURCE_DIR: Regex =
+        Regex::new(r"(?s)<(resource|testResource)>.*?<directory>\s*([^<\s]+)\s*</directory>").unwrap();
+}
+
+const JVM_LANGUAGES: [&str; 4] = ["java", "kotlin", "groovy", "scala"];
+
+pub struct JvmConvention {}
+
+impl JvmConvention {
+    /// `src/main/java`, `src/test/java`, `src/main/resources` ... for both maven and gradle
+    fn standard_layout(module_dir: &Path, gen_dir: &str, out_dir: &str) -> ContentRoot {
+        let mut root = ContentRoot::new(module_dir);
+        for lang in JVM_LANGUAGES.iter() {
+            let main = format!("src/main/{}", lang);
+            let test = format!("src/test/{}", lang);
+            ContentRoot::add_existing_dirs(&mut root.source_dirs, module_dir, &[&main]);
+            ContentRoot::add_existing_dirs(&mut root.test_source_dirs, module_dir, &[&test]);
+        }
+
+        ContentRoot::add_existing_dirs(
+            &mut root.resource_dirs,
+            module_dir,
+            &["src/main/resources"],
+        );
+        ContentRoot::add_existing_dirs(
+            &mut root.test_source_dirs,
+            module_dir,
+            &["src/test/resources"],
+        );
+        ContentRoot::add_dir(&mut root.gen_source_dirs, &module_dir.join(gen_dir));
+        ContentRoot::add_dir(&mut root.exclude_dirs, &module_dir.join(out_dir));
+
+        root
+    }
+
+    pub fn gradle_content_root(module_dir: &Path) -> ContentRoot {
+        let mut root = JvmConvention::standard_layout(module_dir, "build/generated", "build");
+        let script = fs::read_to_string(module_dir.join("build.gradle"))
+            .or_else(|_| fs::read_to_string(module_dir.join("build.gradle.kts")))
+            .unwrap_or_default();
+
+        for capture in GRADLE_DOT_SRC_DIRS.captures_iter(&script) {
+            let is_test = &capture[1] == "test";
+            let is_resource = &capture[2] == "resources";
+            let is_append = capture.get(3).map(|op| op.as_str() == "+=").unwrap_or(true);
+            JvmConvention::apply_override(
+                &mut root,
+                module_dir,
+                is_test,
+                is_resource,
+                is_append,
+                &capture[4],
+            );
+        }
+
+        for (set, block) in JvmConvention::source_set_blocks(&script) {
+            let is_test = set == "test";
+            for capture in GRADLE_BLOCK_SRC_DIRS.captures_iter(&block) {
+                let is_resource = &capture[1] == "resources";
+                let is_append = capture.get(2).map(|op| op.as_str() == "+=").unwrap_or(true);
+                JvmConvention::apply_override(
+                    &mut root,
+                    module_dir,
+                    is_test,
+                    is_resource,
+                    is_append,
+                    &capture[3],
+                );
+            }
+        }
+
+        root
+    }
+
+    pub fn maven_content_root(module_dir: &Path) -> ContentRoot {
+        let mut root =
+            JvmConvention::standard_layout(module_dir, "target/generated-sources", "target");
+        let pom = fs::read_to_string(module_dir.join("pom.xml")).unwrap_or_default();
+
+        if let Some(capture) = MAVEN_SOURCE_DIR.captures(&pom) {
+            root.source_dirs = vec![JvmConvention::maven_dir(module_dir, &capture[1])];
+        }
+        if let Some(capture) = MAVEN_TEST_SOURCE_DIR.captures(&pom) {
+            root.test_source_dirs = vec![JvmConvention::maven_dir(module_dir, &capture[1])];
+        }
+        for capture in MAVEN_RESOURCE_DIR.captures_iter(&pom) {
+            let dir = JvmConvention::maven_dir(module_dir, &capture[2]);
+            if &capture[1] == "testResource" {
+                root.test_source_dirs.push(dir);
+            } else {
+                root.resource_dirs.push(dir);
+            }
+        }
+
+        root
+    }
+
+    /// the `include ':app', ':lib:core'` projects in `settings.gradle`
+    pub fn gradle_modules(root_dir: &Path) -> Vec<PathBuf> {
+        let settings = fs::read_to_string(root_dir.join("settings.gradle"))
+            .or_else(|_| fs::read_to_string(root_dir.join("settings.gradle.kts")))
+            .unwrap_or_default();
+
+        let mut modules = vec![];
+        for include in GRADLE_INCLUDE.captures_iter(&settings) {
+            for project in QUOTED.captures_iter(&include[1]) {
+                let relative = project[1].trim_start_matches(':').replace(':', "/");
+                modules.push(root_dir.join(relative));
+            }
+        }
+
+        modules
+    }
+
+    pub fn maven_modules(root_dir: &Path) -> Vec<PathBuf> {
+        let pom = fs::read_to_string(root_dir.join("pom.xml")).unwrap_or_default();
+        MAVEN_MODULE
+            .captures_iter(&pom)
+            .map(|capture| root_dir.join(&capture[1]))
+            .collect()
+    }
+
+    fn apply_override(
+        root: &mut ContentRoot,
+        module_dir: &Path,
+        is_test: bool,
+        is_resource: bool,
+        is_append: bool,
+        dirs: &str,
+    ) {
+        let target = match (is_test, is_resource) {
+            (false, false) => &mut root.source_dirs,
+            (false, true) => &mut root.resource_dirs,
+            (true, _) => &mut root.test_source_dirs,
+        };
+        if !is_append {
+            target.clear();
+        }
+
+        for dir in QUOTED.captures_iter(dirs) {
+            ContentRoot::add_dir(target, &module_dir.join(&dir[1]));
+        }
+    }
+
+    /// the body of `main { }` and `test { }` inside `sourceSets { }`
+    fn source_set_blocks(script: &str) -> Vec<(String, String)> {
+        let mut blocks = vec![];
+        let source_sets = match script.find("sourceSets") {
+            Some(index) => JvmConvention::block_body(&script[index..]),
+            None => return blocks,
+        };
+
+        for set in ["main", "test"].iter() {
+            let pattern = Regex::new(&format!(r"\b{}\s*\{{", set)).unwrap();
+            if let Some(found) = pattern.find(&source_sets) {
+                let body = JvmConvention::block_body(&source_sets[found.start()..]);
+                blocks.push((set.to_string(), body));
+            }
+        }
+
+        blocks
+    }
+
+    fn block_body(text: &str) -> String {
+        let start = match text.find('{') {
+            Some(index) => index + 1,
+            None => return "".to_string(),
+        };
+
+        let mut depth = 1;
+        for (index, char) in text[start..].char_indices() {
+            match char {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                return text[start..start + index].to_string();
+            }
+        }
+
+        text[start..].to_string()
+    }
+
+    fn maven_dir(module_dir: &Path, dir: &str) -> String {
+        let dir = dir
+            .replace("${project.basedir}/", "")
+            .replace("${basedir}/", "");
+        module_dir.join(dir).display().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::convention::JvmConvention;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn jvm_project_dir(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("java")
+            .join(name)
+    }
+
+    #[test]
+    fn should_read_gradle_modules() {
+        let root = jvm_project_dir("simple");
+        let modules = JvmConvention::gradle_modules(&root);
+
+        assert_eq!(vec![root.join("app")], modules);
+    }
+
+    #[test]
+    fn should_apply_gradle_source_sets() {
+        let root = jvm_project_dir("source-sets");
+        let content_root = JvmConvention::gradle_content_root(&root);
+
+        assert_eq!(
+            vec![root.join("src").display().to_string()],
+            content_root.source_dirs
+        );
+        assert_eq!(
+            vec![root.join("test").display().to_string()],
+            content_root.test_source_dirs
+        );
+        assert_eq!(
+            vec![root.join("build/generated").display().to_string()],
+            content_root.gen_source_dirs
+        );
+    }
+
+    #[test]
+    fn should_use_maven_standard_layout_and_overrides() {
+        let root = jvm_project_dir("maven");
+        let content_root = JvmConvention::maven_content_root(&root);
+
+        assert!(content_root.source_dirs[0].ends_with("src/main/java"));
+        assert!(content_root.test_source_dirs[0].ends_with("src/it/java"));
+        assert!(content_root.resource_dirs[0].ends_with("config"));
+        assert!(fs::metadata(&content_root.source_dirs[0]).is_ok());
+    }
+}
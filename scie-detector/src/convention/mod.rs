@@ -0,0 +1,11 @@
+pub mod cargo_convention;
+pub mod go_convention;
+pub mod jvm_convention;
+pub mod npm_convention;
+pub mod python_convention;
+
+pub use cargo_convention::CargoConvention;
+pub use go_convention::GoConvention;
+pub use jvm_convention::JvmConvention;
+pub use npm_convention::NpmConvention;
+pub use python_convention::PythonConvention;
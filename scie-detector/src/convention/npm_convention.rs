@@ -0,0 +1,45 @@
+use crate::content_root::ContentRoot;
+use std::path::Path;
+
+pub struct NpmConvention {}
+
+impl NpmConvention {
+    /// jest and mocha style, `__tests__` dirs and `*.test.js`, `*.spec.ts` files
+    pub fn content_root(module_dir: &Path) -> ContentRoot {
+        let mut root = ContentRoot::new(module_dir);
+        if module_dir.join("src").exists() {
+            ContentRoot::add_dir(&mut root.source_dirs, &module_dir.join("src"));
+        } else {
+            ContentRoot::add_dir(&mut root.source_dirs, module_dir);
+        }
+
+        ContentRoot::add_existing_dirs(
+            &mut root.test_source_dirs,
+            module_dir,
+            &["test", "tests", "spec", "e2e"],
+        );
+        ContentRoot::add_existing_dirs(
+            &mut root.resource_dirs,
+            module_dir,
+            &["public", "static", "assets"],
+        );
+        ContentRoot::add_existing_dirs(
+            &mut root.gen_source_dirs,
+            module_dir,
+            &["dist", "build", "out"],
+        );
+        ContentRoot::add_dir(&mut root.exclude_dirs, &module_dir.join("node_modules"));
+        ContentRoot::add_existing_dirs(
+            &mut root.exclude_dirs,
+            module_dir,
+            &["coverage", "bower_components"],
+        );
+
+        root.test_patterns.push("__tests__/".to_string());
+        root.test_patterns.push("__mocks__/".to_string());
+        root.test_patterns.push("*.test.*".to_string());
+        root.test_patterns.push("*.spec.*".to_string());
+
+        root
+    }
+}
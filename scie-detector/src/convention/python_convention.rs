@@ -0,0 +1,29 @@
+use crate::content_root::ContentRoot;
+use std::path::Path;
+
+pub struct PythonConvention {}
+
+impl PythonConvention {
+    /// pytest discovery rules, `test_*.py` or `*_test.py` files, and `tests` dirs
+    pub fn content_root(module_dir: &Path) -> ContentRoot {
+        let mut root = ContentRoot::new(module_dir);
+        if module_dir.join("src").exists() {
+            ContentRoot::add_dir(&mut root.source_dirs, &module_dir.join("src"));
+        } else {
+            ContentRoot::add_dir(&mut root.source_dirs, module_dir);
+        }
+
+        ContentRoot::add_existing_dirs(&mut root.test_source_dirs, module_dir, &["tests", "test"]);
+        ContentRoot::add_existing_dirs(
+            &mut root.exclude_dirs,
+            module_dir,
+            &[".venv", "venv", ".tox", "build", "dist", ".eggs"],
+        );
+
+        root.test_patterns.push("test_*.py".to_string());
+        root.test_patterns.push("*_test.py".to_string());
+        root.test_patterns.push("conftest.py".to_string());
+
+        root
+    }
+}
@@ -0,0 +1,189 @@
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum Ecosystem {
+    Jvm,
+    JavaScript,
+    C,
+    Go,
+    Rust,
+    Python,
+}
+
+impl Ecosystem {
+    pub fn from_extension(ext: &str) -> Option<Ecosystem> {
+        match ext {
+            "java" | "groovy" | "gradle" | "kt" | "kts" | "scala" => Some(Ecosystem::Jvm),
+            "js" | "jsx" | "ts" | "tsx" | "mjs" | "vue" => Some(Ecosystem::JavaScript),
+            "c" | "h" => Some(Ecosystem::C),
+            "go" => Some(Ecosystem::Go),
+            "rs" => Some(Ecosystem::Rust),
+            "py" => Some(Ecosystem::Python),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum BuildSystem {
+    Gradle,
+    Maven,
+    Npm,
+    Bower,
+    CMake,
+    GoModules,
+    Cargo,
+    Pip,
+    Setuptools,
+    Poetry,
+    Pipenv,
+    PyProject,
+}
+
+impl BuildSystem {
+    pub fn ecosystem(&self) -> Ecosystem {
+        match self {
+            BuildSystem::Gradle | BuildSystem::Maven => Ecosystem::Jvm,
+            BuildSystem::Npm | BuildSystem::Bower => Ecosystem::JavaScript,
+            BuildSystem::CMake => Ecosystem::C,
+            BuildSystem::GoModules => Ecosystem::Go,
+            BuildSystem::Cargo => Ecosystem::Rust,
+            BuildSystem::Pip
+            | BuildSystem::Setuptools
+            | BuildSystem::Poetry
+            | BuildSystem::Pipenv
+            | BuildSystem::PyProject => Ecosystem::Python,
+        }
+    }
+}
+
+/// `High` for the manifest of build system, like `pom.xml`; `Medium` for the by-products, like
+/// `node_modules`; `Low` for guessing by source files, like `main.go` without `go.mod`
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Detection {
+    pub ecosystem: Ecosystem,
+    pub build_system: Option<BuildSystem>,
+    // the files which detected from, relative to the root of workspace
+    pub evidences: Vec<String>,
+    pub confidence: Confidence,
+}
+
+impl Detection {
+    pub fn build_system(build_system: BuildSystem, evidences: Vec<String>) -> Detection {
+        Detection {
+            ecosystem: build_system.ecosystem(),
+            build_system: Some(build_system),
+            evidences,
+            confidence: Confidence::High,
+        }
+    }
+
+    pub fn with_confidence(mut self, confidence: Confidence) -> Detection {
+        self.confidence = confidence;
+        self
+    }
+}
+
+/// the framework which matched by the rules, see `FrameworkRules`
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct FrameworkUsage {
+    pub name: String,
+    pub ecosystem: Ecosystem,
+    // the version of first matched dependency which has version
+    pub version: String,
+    pub evidences: Vec<String>,
+    pub confidence: Confidence,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct DetectResult {
+    pub path: String,
+    pub detections: Vec<Detection>,
+    #[serde(default)]
+    pub frameworks: Vec<FrameworkUsage>,
+}
+
+impl DetectResult {
+    pub fn new(path: &str) -> DetectResult {
+        DetectResult {
+            path: path.to_string(),
+            detections: vec![],
+            frameworks: vec![],
+        }
+    }
+
+    pub fn add(&mut self, detection: Detection) {
+        self.detections.push(detection);
+    }
+
+    pub fn has_build_system(&self, build_system: BuildSystem) -> bool {
+        self.detections
+            .iter()
+            .any(|detection| detection.build_system == Some(build_system))
+    }
+
+    pub fn has_ecosystem(&self, ecosystem: Ecosystem) -> bool {
+        self.detections
+            .iter()
+            .any(|detection| detection.ecosystem == ecosystem)
+    }
+
+    pub fn get(&self, build_system: BuildSystem) -> Option<&Detection> {
+        self.detections
+            .iter()
+            .find(|detection| detection.build_system == Some(build_system))
+    }
+
+    pub fn ecosystems(&self) -> Vec<Ecosystem> {
+        let mut ecosystems: Vec<Ecosystem> = self
+            .detections
+            .iter()
+            .map(|detection| detection.ecosystem)
+            .collect();
+        ecosystems.sort();
+        ecosystems.dedup();
+        ecosystems
+    }
+
+    pub fn has_framework(&self, name: &str) -> bool {
+        self.frameworks
+            .iter()
+            .any(|framework| framework.name == name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.detections.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::detect_result::{BuildSystem, Confidence, DetectResult, Detection, Ecosystem};
+
+    #[test]
+    fn should_serialize_detect_result() {
+        let mut result = DetectResult::new("demo");
+        result.add(Detection::build_system(
+            BuildSystem::Gradle,
+            vec!["build.gradle".to_string()],
+        ));
+        result.add(
+            Detection::build_system(BuildSystem::Npm, vec!["node_modules".to_string()])
+                .with_confidence(Confidence::Medium),
+        );
+
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains("\"build_system\":\"Gradle\""));
+        assert!(json.contains("\"ecosystem\":\"Jvm\""));
+        assert_eq!(result, serde_json::from_str(&json).unwrap());
+        assert_eq!(
+            vec![Ecosystem::Jvm, Ecosystem::JavaScript],
+            result.ecosystems()
+        );
+    }
+}
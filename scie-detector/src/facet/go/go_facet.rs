@@ -0,0 +1,93 @@
+use crate::dependency::base_library::BaseLibrary;
+use crate::facet::go::{GoMod, GoSum, VendorModules};
+use std::fs;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct GoFacet {
+    pub has_mod: bool,
+    pub has_sum: bool,
+    pub has_vendor: bool,
+
+    pub go_mod: GoMod,
+    pub go_sum: Option<GoSum>,
+    pub vendor: Option<VendorModules>,
+
+    pub dependencies: Vec<BaseLibrary>,
+}
+
+impl GoFacet {
+    /// build facet from a module dir, which contains `go.mod`, `go.sum` and `vendor/modules.txt`
+    pub fn from_dir(dir: &Path) -> GoFacet {
+        let mod_code = fs::read_to_string(dir.join("go.mod")).ok();
+        let sum_code = fs::read_to_string(dir.join("go.sum")).ok();
+        let vendor_code = fs::read_to_string(dir.join("vendor").join("modules.txt")).ok();
+
+        let go_mod = mod_code
+            .as_ref()
+            .map(|code| GoMod::parse(code))
+            .unwrap_or_default();
+        let go_sum = sum_code.as_ref().map(|code| GoSum::parse(code));
+        let vendor = vendor_code.as_ref().map(|code| VendorModules::parse(code));
+
+        let mut dependencies = go_mod.libraries();
+        // before Go 1.14, modules.txt may be the only record of vendored modules
+        if let Some(vendor) = &vendor {
+            for module in vendor.modules.iter() {
+                if dependencies.iter().any(|lib| lib.name == module.path) {
+                    continue;
+                }
+
+                let library = BaseLibrary {
+                    name: module.path.clone(),
+                    version: module.version.clone(),
+                    group: "".to_string(),
+                    source: module.path.split('/').next().unwrap_or("").to_string(),
+                    scope: "Vendor".to_string(),
+                };
+                dependencies.push(library);
+            }
+        }
+
+        GoFacet {
+            has_mod: mod_code.is_some(),
+            has_sum: go_sum.is_some(),
+            has_vendor: vendor.is_some(),
+            go_mod,
+            go_sum,
+            vendor,
+            dependencies,
+        }
+    }
+
+    pub fn module_path(&self) -> &str {
+        &self.go_mod.module
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::facet::go::GoFacet;
+    use std::path::PathBuf;
+
+    #[test]
+    fn should_build_go_facet_from_dir() {
+        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .to_path_buf();
+        let module_dir = root_dir
+            .join("fixtures")
+            .join("projects")
+            .join("go")
+            .join("modules");
+
+        let facet = GoFacet::from_dir(&module_dir);
+
+        assert!(facet.has_mod && facet.has_sum && facet.has_vendor);
+        assert_eq!("github.com/phodal/scie-go-example", facet.module_path());
+        assert_eq!("1.15", facet.go_mod.go_version);
+        assert_eq!(4, facet.dependencies.len());
+        assert_eq!("Indirect", facet.dependencies[1].scope);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::dependency::base_library::BaseLibrary;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct GoModule {
+    pub path: String,
+    // local replacements like `=> ../local` have no version
+    pub version: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct GoRequire {
+    pub module: GoModule,
+    pub indirect: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct GoReplace {
+    pub old: GoModule,
+    pub new: GoModule,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct GoMod {
+    pub module: String,
+    pub go_version: String,
+    pub requires: Vec<GoRequire>,
+    pub replaces: Vec<GoReplace>,
+    pub excludes: Vec<GoModule>,
+}
+
+impl Default for GoMod {
+    fn default() -> Self {
+        GoMod {
+            module: "".to_string(),
+            go_version: "".to_string(),
+            requires: vec![],
+            replaces: vec![],
+            excludes: vec![],
+        }
+    }
+}
+
+impl GoMod {
+    /// parse `go.mod`, both single line directives and `require ( ... )` blocks are supported
+    pub fn parse(code: &str) -> GoMod {
+        let mut go_mod = GoMod::default();
+        let mut block: Option<String> = None;
+
+        for line in code.lines() {
+            let (content, comment) = GoMod::split_comment(line);
+            let content = content.trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            if block.is_some() {
+                if content == ")" {
+                    block = None;
+                } else {
+                    let verb = block.clone().unwrap();
+                    go_mod.add_directive(&verb, content, comment);
+                }
+                continue;
+            }
+
+            let mut parts = content.splitn(2, char::is_whitespace);
+            let verb = parts.next().unwrap_or("");
+            let args = parts.next().unwrap_or("").trim();
+            if args == "(" {
+                block = Some(verb.to_string());
+                continue;
+            }
+
+            go_mod.add_directive(verb, args, comment);
+        }
+
+        go_mod
+    }
+
+    /// requires with `replace` and `exclude` directives applied
+    pub fn libraries(&self) -> Vec<BaseLibrary> {
+        let mut libraries = vec![];
+        for require in self.requires.iter() {
+            if self.excludes.contains(&require.module) {
+                continue;
+            }
+
+            let mut module = require.module.clone();
+            if let Some(replace) = self.find_replace(&module) {
+                if !replace.new.version.is_empty() {
+                    module.version = replace.new.version.clone();
+                }
+            }
+
+            let scope = if require.indirect { "Indirect" } else { "" };
+            libraries.push(GoMod::to_library(&module, scope));
+        }
+
+        libraries
+    }
+
+    pub fn to_library(module: &GoModule, scope: &str) -> BaseLibrary {
+        let source = module.path.split('/').next().unwrap_or("");
+        BaseLibrary {
+            name: module.path.clone(),
+            version: module.version.clone(),
+            group: "".to_string(),
+            source: source.to_string(),
+            scope: scope.to_string(),
+        }
+    }
+
+    fn find_replace(&self, module: &GoModule) -> Option<&GoReplace> {
+        self.replaces.iter().find(|replace| {
+            replace.old.path == module.path
+                && (replace.old.version.is_empty() || replace.old.version == module.version)
+        })
+    }
+
+    fn add_directive(&mut self, verb: &str, args: &str, comment: &str) {
+        match verb {
+            "module" => self.module = GoMod::unquote(args),
+            "go" => self.go_version = args.to_string(),
+            "require" => {
+                self.requires.push(GoRequire {
+                    module: GoMod::parse_module(args),
+                    indirect: comment.trim() == "indirect",
+                });
+            }
+            "exclude" => self.excludes.push(GoMod::parse_module(args)),
+            "replace" => {
+                let mut sides = args.splitn(2, "=>");
+                let old = GoMod::parse_module(sides.next().unwrap_or(""));
+                let new = GoMod::parse_module(sides.next().unwrap_or(""));
+                self.replaces.push(GoReplace { old, new });
+            }
+            _ => {}
+        }
+    }
+
+    fn parse_module(text: &str) -> GoModule {
+        let mut fields = text.split_whitespace();
+        GoModule {
+            path: GoMod::unquote(fields.next().unwrap_or("")),
+            version: fields.next().unwrap_or("").to_string(),
+        }
+    }
+
+    fn split_comment(line: &str) -> (&str, &str) {
+        match line.find("//") {
+            Some(index) => (&line[..index], &line[index + 2..]),
+            None => (line, ""),
+        }
+    }
+
+    fn unquote(text: &str) -> String {
+        text.trim().trim_matches('"').trim_matches('`').to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::facet::go::GoMod;
+
+    #[test]
+    fn should_parse_module_and_go_version() {
+        let go_mod = GoMod::parse("module github.com/phodal/scie\n\ngo 1.15\n");
+
+        assert_eq!("github.com/phodal/scie", go_mod.module);
+        assert_eq!("1.15", go_mod.go_version);
+    }
+
+    #[test]
+    fn should_parse_require_block_and_indirect() {
+        let code = "module demo
+
+require (
+    github.com/gin-gonic/gin v1.6.3
+    github.com/stretchr/testify v1.6.1 // indirect
+)
+require gopkg.in/yaml.v2 v2.3.0
+";
+        let go_mod = GoMod::parse(code);
+
+        assert_eq!(3, go_mod.requires.len());
+        assert_eq!("github.com/gin-gonic/gin", go_mod.requires[0].module.path);
+        assert_eq!("v1.6.3", go_mod.requires[0].module.version);
+        assert!(go_mod.requires[1].indirect);
+        assert!(!go_mod.requires[2].indirect);
+    }
+
+    #[test]
+    fn should_apply_replace_and_exclude() {
+        let code = "module demo
+require (
+    golang.org/x/text v0.3.3
+    github.com/gin-gonic/gin v1.6.2
+)
+replace golang.org/x/text => golang.org/x/text v0.3.4
+replace github.com/phodal/local => ../local
+exclude github.com/gin-gonic/gin v1.6.2
+";
+        let go_mod = GoMod::parse(code);
+        assert_eq!(2, go_mod.replaces.len());
+        assert_eq!("../local", go_mod.replaces[1].new.path);
+        assert_eq!("", go_mod.replaces[1].new.version);
+
+        let libraries = go_mod.libraries();
+        assert_eq!(1, libraries.len());
+        assert_eq!("golang.org/x/text", libraries[0].name);
+        assert_eq!("v0.3.4", libraries[0].version);
+        assert_eq!("golang.org", libraries[0].source);
+    }
+}
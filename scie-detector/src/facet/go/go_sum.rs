@@ -0,0 +1,68 @@
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct GoSumEntry {
+    pub path: String,
+    pub version: String,
+    pub hash: String,
+    // `/go.mod` entries only hash the module's go.mod file
+    pub is_go_mod: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct GoSum {
+    pub entries: Vec<GoSumEntry>,
+}
+
+impl GoSum {
+    pub fn parse(code: &str) -> GoSum {
+        let mut entries = vec![];
+        for line in code.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 3 {
+                continue;
+            }
+
+            let (version, is_go_mod) = match fields[1].strip_suffix("/go.mod") {
+                Some(version) => (version, true),
+                None => (fields[1], false),
+            };
+
+            entries.push(GoSumEntry {
+                path: fields[0].to_string(),
+                version: version.to_string(),
+                hash: fields[2].to_string(),
+                is_go_mod,
+            });
+        }
+
+        GoSum { entries }
+    }
+
+    /// the hash of module content, without the `/go.mod` only entries
+    pub fn checksum(&self, path: &str, version: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| !entry.is_go_mod && entry.path == path && entry.version == version)
+            .map(|entry| entry.hash.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::facet::go::GoSum;
+
+    #[test]
+    fn should_parse_go_sum_entries() {
+        let code = "github.com/gin-gonic/gin v1.6.3 h1:ahKq=
+github.com/gin-gonic/gin v1.6.3/go.mod h1:75u5=
+";
+        let go_sum = GoSum::parse(code);
+
+        assert_eq!(2, go_sum.entries.len());
+        assert!(go_sum.entries[1].is_go_mod);
+        assert_eq!("v1.6.3", go_sum.entries[1].version);
+        assert_eq!(
+            Some("h1:ahKq="),
+            go_sum.checksum("github.com/gin-gonic/gin", "v1.6.3")
+        );
+    }
+}
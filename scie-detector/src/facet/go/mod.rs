@@ -0,0 +1,9 @@
+pub mod go_facet;
+pub mod go_mod;
+pub mod go_sum;
+pub mod vendor_modules;
+
+pub use go_facet::GoFacet;
+pub use go_mod::{GoMod, GoModule, GoReplace, GoRequire};
+pub use go_sum::{GoSum, GoSumEntry};
+pub use vendor_modules::{VendorModule, VendorModules};
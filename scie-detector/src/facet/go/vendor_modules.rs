@@ -0,0 +1,80 @@
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct VendorModule {
+    pub path: String,
+    pub version: String,
+    // the `=> path version` part of a replaced module
+    pub replace: Option<String>,
+    // marked `## explicit`, it means required in the go.mod
+    pub explicit: bool,
+    pub packages: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct VendorModules {
+    pub modules: Vec<VendorModule>,
+}
+
+impl VendorModules {
+    /// parse `vendor/modules.txt` which is generated by `go mod vendor`
+    pub fn parse(code: &str) -> VendorModules {
+        let mut modules: Vec<VendorModule> = vec![];
+        for line in code.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with("##") {
+                if let Some(module) = modules.last_mut() {
+                    module.explicit = line.contains("explicit");
+                }
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix("# ") {
+                let mut sides = header.splitn(2, "=>");
+                let mut fields = sides.next().unwrap_or("").split_whitespace();
+                modules.push(VendorModule {
+                    path: fields.next().unwrap_or("").to_string(),
+                    version: fields.next().unwrap_or("").to_string(),
+                    replace: sides.next().map(|replace| replace.trim().to_string()),
+                    explicit: false,
+                    packages: vec![],
+                });
+                continue;
+            }
+
+            if let Some(module) = modules.last_mut() {
+                module.packages.push(line.to_string());
+            }
+        }
+
+        VendorModules { modules }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::facet::go::VendorModules;
+
+    #[test]
+    fn should_parse_vendor_modules() {
+        let code = "# github.com/gin-gonic/gin v1.6.3
+## explicit
+github.com/gin-gonic/gin
+github.com/gin-gonic/gin/binding
+# golang.org/x/text v0.3.3 => golang.org/x/text v0.3.4
+golang.org/x/text/unicode/norm
+";
+        let vendor = VendorModules::parse(code);
+
+        assert_eq!(2, vendor.modules.len());
+        assert!(vendor.modules[0].explicit);
+        assert_eq!(2, vendor.modules[0].packages.len());
+        assert_eq!(
+            Some("golang.org/x/text v0.3.4".to_string()),
+            vendor.modules[1].replace
+        );
+        assert!(!vendor.modules[1].explicit);
+    }
+}
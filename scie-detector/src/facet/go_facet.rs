@@ -1,4 +0,0 @@
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
-pub struct GoFacet {
-    pub has_mod: bool,
-}
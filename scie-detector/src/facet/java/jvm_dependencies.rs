@@ -0,0 +1,125 @@
+use crate::dependency::base_library::BaseLibrary;
+use regex::Regex;
+
+lazy_static! {
+    // `implementation 'group:name:version'` or `testImplementation("group:name")`
+    static ref GRADLE_DEPENDENCY: Regex = Regex::new(
+        r#"(?m)^\s*(\w+)\s*\(?\s*["']([^:"'\s]+):([^:"'\s]+)(?::([^:"'\s@]+))?[^"']*["']"#
+    )
+    .unwrap();
+    static ref MAVEN_DEPENDENCY: Regex =
+        Regex::new(r"(?s)<dependency>(.*?)</dependency>").unwrap();
+    static ref MAVEN_FIELD: Regex =
+        Regex::new(r"<(groupId|artifactId|version|scope)>\s*([^<\s]+)\s*</").unwrap();
+}
+
+pub struct JvmDependencies {}
+
+impl JvmDependencies {
+    /// the string notation of gradle, map notation like `group: 'a', name: 'b'` is not supported
+    pub fn parse_gradle(code: &str) -> Vec<BaseLibrary> {
+        GRADLE_DEPENDENCY
+            .captures_iter(code)
+            .map(|capture| {
+                let configuration = capture[1].to_lowercase();
+                let scope = if configuration.starts_with("test") {
+                    "Test"
+                } else if configuration.contains("compileonly") || configuration == "kapt" {
+                    "Provided"
+                } else {
+                    ""
+                };
+
+                JvmDependencies::library(
+                    &capture[2],
+                    &capture[3],
+                    capture.get(4).map(|version| version.as_str()).unwrap_or(""),
+                    scope,
+                )
+            })
+            .collect()
+    }
+
+    /// the `<dependency>` in `pom.xml`, include the ones in `<dependencyManagement>`
+    pub fn parse_maven(code: &str) -> Vec<BaseLibrary> {
+        let mut libraries = vec![];
+        for dependency in MAVEN_DEPENDENCY.captures_iter(code) {
+            let (mut group, mut name, mut version, mut scope) = ("", "", "", "");
+            for field in MAVEN_FIELD.captures_iter(&dependency[1]) {
+                let value = field.get(2).unwrap().as_str();
+                match &field[1] {
+                    "groupId" => group = value,
+                    "artifactId" => name = value,
+                    "version" => version = value,
+                    _ => scope = value,
+                }
+            }
+
+            let scope = match scope {
+                "test" => "Test",
+                "provided" => "Provided",
+                _ => "",
+            };
+            if !name.is_empty() {
+                libraries.push(JvmDependencies::library(group, name, version, scope));
+            }
+        }
+
+        libraries
+    }
+
+    fn library(group: &str, name: &str, version: &str, scope: &str) -> BaseLibrary {
+        BaseLibrary {
+            name: name.to_string(),
+            version: version.to_string(),
+            group: group.to_string(),
+            source: "maven".to_string(),
+            scope: scope.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::facet::java::JvmDependencies;
+
+    #[test]
+    fn should_parse_gradle_dependencies() {
+        let code = "dependencies {
+    implementation 'org.springframework.boot:spring-boot-starter-web:2.3.4.RELEASE'
+    compileOnly(\"org.projectlombok:lombok\")
+    testImplementation \"junit:junit:4.13@jar\"
+}";
+        let libraries = JvmDependencies::parse_gradle(code);
+
+        assert_eq!(3, libraries.len());
+        assert_eq!("org.springframework.boot", libraries[0].group);
+        assert_eq!("spring-boot-starter-web", libraries[0].name);
+        assert_eq!("2.3.4.RELEASE", libraries[0].version);
+        assert_eq!("Provided", libraries[1].scope);
+        assert_eq!("4.13", libraries[2].version);
+        assert_eq!("Test", libraries[2].scope);
+    }
+
+    #[test]
+    fn should_parse_maven_dependencies() {
+        let code = "<dependencies>
+    <dependency>
+        <groupId>org.springframework</groupId>
+        <artifactId>spring-webmvc</artifactId>
+        <version>5.2.9.RELEASE</version>
+    </dependency>
+    <dependency>
+        <groupId>junit</groupId>
+        <artifactId>junit</artifactId>
+        <scope>test</scope>
+    </dependency>
+</dependencies>";
+        let libraries = JvmDependencies::parse_maven(code);
+
+        assert_eq!(2, libraries.len());
+        assert_eq!("spring-webmvc", libraries[0].name);
+        assert_eq!("5.2.9.RELEASE", libraries[0].version);
+        assert_eq!("Test", libraries[1].scope);
+    }
+}
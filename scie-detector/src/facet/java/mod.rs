@@ -1,5 +1,7 @@
 pub mod java_facet;
 pub mod java_module_data;
+pub mod jvm_dependencies;
 
 pub use java_facet::JavaFacet;
 pub use java_module_data::JavaModuleData;
+pub use jvm_dependencies::JvmDependencies;
@@ -1,3 +1,5 @@
 pub mod javascript_facet;
+pub mod package_json;
 
 pub use javascript_facet::JavaScriptFacet;
+pub use package_json::PackageJson;
@@ -0,0 +1,51 @@
+use crate::dependency::base_library::BaseLibrary;
+use serde_json::Value;
+
+pub struct PackageJson {}
+
+impl PackageJson {
+    /// `dependencies` and `devDependencies` in `package.json`
+    pub fn dependencies(code: &str) -> Vec<BaseLibrary> {
+        let mut libraries = vec![];
+        let value: Value = match serde_json::from_str(code) {
+            Ok(value) => value,
+            Err(_) => return libraries,
+        };
+
+        for (key, scope) in [("dependencies", ""), ("devDependencies", "Dev")].iter() {
+            if let Some(deps) = value.get(key).and_then(|deps| deps.as_object()) {
+                for (name, version) in deps.iter() {
+                    libraries.push(BaseLibrary {
+                        name: name.to_string(),
+                        version: version.as_str().unwrap_or("").to_string(),
+                        group: "".to_string(),
+                        source: "npm".to_string(),
+                        scope: scope.to_string(),
+                    });
+                }
+            }
+        }
+
+        libraries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::facet::javascript::PackageJson;
+
+    #[test]
+    fn should_parse_package_json_dependencies() {
+        let code = r#"{
+  "name": "web",
+  "dependencies": { "react": "^17.0.1", "react-dom": "^17.0.1" },
+  "devDependencies": { "jest": "^26.6.0" }
+}"#;
+        let libraries = PackageJson::dependencies(code);
+
+        assert_eq!(3, libraries.len());
+        assert_eq!("react", libraries[0].name);
+        assert_eq!("^17.0.1", libraries[0].version);
+        assert_eq!("Dev", libraries[2].scope);
+    }
+}
@@ -1,6 +1,3 @@
-pub mod go_facet;
-pub mod python_facet;
-
 pub mod rust_facet;
 
 /// Java
@@ -11,6 +8,16 @@ pub use java::JavaFacet;
 pub use java::JavaModuleData;
 pub use jvm_facet::JvmFacet;
 
+// Go
+pub mod go;
+
+pub use go::GoFacet;
+
+// Python
+pub mod python;
+
+pub use python::PythonFacet;
+
 // JavaScript
 pub mod javascript;
 
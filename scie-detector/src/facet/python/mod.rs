@@ -0,0 +1,9 @@
+pub mod pyproject;
+pub mod python_facet;
+pub mod requirements;
+pub mod setup_tools;
+
+pub use pyproject::PyProject;
+pub use python_facet::PythonFacet;
+pub use requirements::Requirements;
+pub use setup_tools::SetupTools;
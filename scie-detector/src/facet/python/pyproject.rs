@@ -0,0 +1,164 @@
+use crate::dependency::base_library::BaseLibrary;
+use crate::facet::python::Requirements;
+use toml::Value;
+
+pub struct PyProject {
+    pub is_poetry: bool,
+    pub dependencies: Vec<BaseLibrary>,
+}
+
+impl PyProject {
+    /// parse `pyproject.toml`, both PEP 621 `[project]` and `[tool.poetry]` tables
+    pub fn parse(code: &str) -> PyProject {
+        let mut dependencies = vec![];
+        let value: Value = match code.parse() {
+            Ok(value) => value,
+            Err(_) => {
+                return PyProject {
+                    is_poetry: false,
+                    dependencies,
+                }
+            }
+        };
+
+        if let Some(project) = value.get("project") {
+            if let Some(requires) = project.get("dependencies").and_then(|deps| deps.as_array()) {
+                dependencies.append(&mut PyProject::parse_pep508_list(requires, ""));
+            }
+
+            let optional = project
+                .get("optional-dependencies")
+                .and_then(|deps| deps.as_table());
+            if let Some(groups) = optional {
+                for (_group, requires) in groups.iter() {
+                    if let Some(requires) = requires.as_array() {
+                        let mut libs = PyProject::parse_pep508_list(requires, "Optional");
+                        dependencies.append(&mut libs);
+                    }
+                }
+            }
+        }
+
+        let poetry = value.get("tool").and_then(|tool| tool.get("poetry"));
+        if let Some(poetry) = poetry {
+            if let Some(deps) = poetry.get("dependencies") {
+                dependencies.append(&mut PyProject::parse_table(deps, ""));
+            }
+            if let Some(deps) = poetry.get("dev-dependencies") {
+                dependencies.append(&mut PyProject::parse_table(deps, "Dev"));
+            }
+        }
+
+        PyProject {
+            is_poetry: poetry.is_some(),
+            dependencies,
+        }
+    }
+
+    /// parse `Pipfile`, it is toml with `[packages]` and `[dev-packages]`
+    pub fn parse_pipfile(code: &str) -> Vec<BaseLibrary> {
+        let mut dependencies = vec![];
+        if let Ok(value) = code.parse::<Value>() {
+            if let Some(packages) = value.get("packages") {
+                dependencies.append(&mut PyProject::parse_table(packages, ""));
+            }
+            if let Some(packages) = value.get("dev-packages") {
+                dependencies.append(&mut PyProject::parse_table(packages, "Dev"));
+            }
+        }
+
+        dependencies
+    }
+
+    fn parse_pep508_list(requires: &[Value], scope: &str) -> Vec<BaseLibrary> {
+        requires
+            .iter()
+            .filter_map(|require| require.as_str())
+            .filter_map(|require| Requirements::parse_requirement(require, scope))
+            .collect()
+    }
+
+    /// the `name = "^1.0"` or `name = { version = "^1.0" }` style tables, `python` is not a dependency
+    fn parse_table(table: &Value, scope: &str) -> Vec<BaseLibrary> {
+        let mut libraries = vec![];
+        let table = match table.as_table() {
+            Some(table) => table,
+            None => return libraries,
+        };
+
+        for (name, spec) in table.iter() {
+            if name == "python" {
+                continue;
+            }
+
+            let version = match spec {
+                Value::String(version) => version.as_str(),
+                Value::Table(detail) => detail
+                    .get("version")
+                    .and_then(|version| version.as_str())
+                    .unwrap_or(""),
+                _ => "",
+            };
+            let version = if version == "*" { "" } else { version };
+            libraries.push(Requirements::library(name, version, scope));
+        }
+
+        libraries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::facet::python::PyProject;
+
+    #[test]
+    fn should_parse_pep_621_project() {
+        let code = "[project]
+name = \"demo\"
+dependencies = [\"flask>=1.1\", \"click\"]
+
+[project.optional-dependencies]
+test = [\"pytest\"]
+";
+        let project = PyProject::parse(code);
+
+        assert!(!project.is_poetry);
+        assert_eq!(3, project.dependencies.len());
+        assert_eq!("flask", project.dependencies[0].name);
+        assert_eq!("Optional", project.dependencies[2].scope);
+    }
+
+    #[test]
+    fn should_parse_poetry_dependencies() {
+        let code = "[tool.poetry.dependencies]
+python = \"^3.8\"
+fastapi = \"^0.61.1\"
+uvicorn = { version = \"^0.12.2\", extras = [\"standard\"] }
+
+[tool.poetry.dev-dependencies]
+pytest = \"^6.1\"
+";
+        let project = PyProject::parse(code);
+
+        assert!(project.is_poetry);
+        assert_eq!(3, project.dependencies.len());
+        assert_eq!("^0.12.2", project.dependencies[1].version);
+        assert_eq!("Dev", project.dependencies[2].scope);
+    }
+
+    #[test]
+    fn should_parse_pipfile() {
+        let code = "[packages]
+flask = \"*\"
+requests = { version = \"==2.24.0\" }
+
+[dev-packages]
+pytest = \"*\"
+";
+        let libraries = PyProject::parse_pipfile(code);
+
+        assert_eq!(3, libraries.len());
+        assert_eq!("", libraries[0].version);
+        assert_eq!("2.24.0", libraries[1].version);
+    }
+}
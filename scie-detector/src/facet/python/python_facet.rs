@@ -0,0 +1,152 @@
+use crate::dependency::base_library::BaseLibrary;
+use crate::facet::python::{PyProject, Requirements, SetupTools};
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+lazy_static! {
+    static ref REQUIREMENTS_FILE: Regex = Regex::new(r"(?i)^(.*)requirements(.*)\.txt$").unwrap();
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct PythonFacet {
+    pub has_requirements: bool,
+    pub has_setup_py: bool,
+    pub has_setup_cfg: bool,
+    pub has_pyproject: bool,
+    pub has_pipfile: bool,
+    pub is_poetry: bool,
+
+    pub is_django: bool,
+    pub is_flask: bool,
+    pub is_fastapi: bool,
+
+    pub dependencies: Vec<BaseLibrary>,
+}
+
+impl PythonFacet {
+    pub fn is_requirements_file(file_name: &str) -> bool {
+        REQUIREMENTS_FILE.is_match(file_name)
+    }
+
+    pub fn is_python_project_file(file_name: &str) -> bool {
+        PythonFacet::is_requirements_file(file_name)
+            || file_name == "setup.py"
+            || file_name == "setup.cfg"
+            || file_name == "pyproject.toml"
+            || file_name == "Pipfile"
+    }
+
+    /// build facet from the project files in the root of dir
+    pub fn from_dir(dir: &Path) -> PythonFacet {
+        let mut facet = PythonFacet::default();
+        let mut dependencies = vec![];
+
+        let mut requirement_files: Vec<String> = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_string()))
+                    .filter(|name| PythonFacet::is_requirements_file(name))
+                    .collect()
+            })
+            .unwrap_or_default();
+        requirement_files.sort();
+
+        for file_name in requirement_files.iter() {
+            if let Ok(code) = fs::read_to_string(dir.join(file_name)) {
+                facet.has_requirements = true;
+                let scope = Requirements::scope_by_file_name(file_name);
+                dependencies.append(&mut Requirements::parse(&code, scope));
+            }
+        }
+
+        if let Ok(code) = fs::read_to_string(dir.join("setup.py")) {
+            facet.has_setup_py = true;
+            dependencies.append(&mut SetupTools::parse_setup_py(&code));
+        }
+
+        if let Ok(code) = fs::read_to_string(dir.join("setup.cfg")) {
+            facet.has_setup_cfg = true;
+            dependencies.append(&mut SetupTools::parse_setup_cfg(&code));
+        }
+
+        if let Ok(code) = fs::read_to_string(dir.join("pyproject.toml")) {
+            facet.has_pyproject = true;
+            let mut project = PyProject::parse(&code);
+            facet.is_poetry = project.is_poetry;
+            dependencies.append(&mut project.dependencies);
+        }
+
+        if let Ok(code) = fs::read_to_string(dir.join("Pipfile")) {
+            facet.has_pipfile = true;
+            dependencies.append(&mut PyProject::parse_pipfile(&code));
+        }
+
+        for library in dependencies {
+            let exists = facet.dependencies.iter().any(|lib| {
+                lib.name.eq_ignore_ascii_case(&library.name) && lib.scope == library.scope
+            });
+            if !exists {
+                facet.dependencies.push(library);
+            }
+        }
+
+        facet.is_django = facet.has_dependency("django") || dir.join("manage.py").exists();
+        facet.is_flask = facet.has_dependency("flask");
+        facet.is_fastapi = facet.has_dependency("fastapi");
+
+        facet
+    }
+
+    pub fn has_dependency(&self, name: &str) -> bool {
+        self.dependencies
+            .iter()
+            .any(|lib| lib.name.eq_ignore_ascii_case(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::facet::python::PythonFacet;
+    use std::path::PathBuf;
+
+    fn python_project_dir(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("fixtures")
+            .join("projects")
+            .join("python")
+            .join(name)
+    }
+
+    #[test]
+    fn should_ident_requirements_file() {
+        assert!(PythonFacet::is_requirements_file("requirements.txt"));
+        assert!(PythonFacet::is_requirements_file("dev-requirements.txt"));
+        assert!(!PythonFacet::is_requirements_file("requirements.in"));
+    }
+
+    #[test]
+    fn should_build_django_facet() {
+        let facet = PythonFacet::from_dir(&python_project_dir("django"));
+
+        assert!(facet.has_requirements && facet.has_setup_py);
+        assert!(facet.is_django);
+        assert!(!facet.is_flask);
+        // Django in both requirements.txt and setup.py
+        assert_eq!(8, facet.dependencies.len());
+        assert!(facet.has_dependency("pytest-django"));
+    }
+
+    #[test]
+    fn should_build_poetry_fastapi_facet() {
+        let facet = PythonFacet::from_dir(&python_project_dir("poetry"));
+
+        assert!(facet.has_pyproject && facet.is_poetry);
+        assert!(facet.is_fastapi);
+        assert!(!facet.is_django);
+        assert_eq!(3, facet.dependencies.len());
+    }
+}
@@ -0,0 +1,113 @@
+use crate::dependency::base_library::BaseLibrary;
+
+pub struct Requirements {}
+
+impl Requirements {
+    /// parse `requirements*.txt`, options like `-r`, `-e` and `--index-url` are ignored
+    pub fn parse(code: &str, scope: &str) -> Vec<BaseLibrary> {
+        let mut libraries = vec![];
+        for line in code.lines() {
+            let line = match line.find(" #") {
+                Some(index) => &line[..index],
+                None => line,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
+                continue;
+            }
+
+            if let Some(library) = Requirements::parse_requirement(line, scope) {
+                libraries.push(library);
+            }
+        }
+
+        libraries
+    }
+
+    /// the scope from file name, like `requirements-dev.txt` or `test-requirements.txt`
+    pub fn scope_by_file_name(file_name: &str) -> &'static str {
+        let name = file_name.to_lowercase();
+        if name.contains("test") {
+            "Test"
+        } else if name.contains("dev") {
+            "Dev"
+        } else {
+            ""
+        }
+    }
+
+    /// parse a PEP 508 requirement, such as `requests[security]==2.24.0 ; python_version >= "3.6"`
+    pub fn parse_requirement(text: &str, scope: &str) -> Option<BaseLibrary> {
+        let spec = text.split(';').next().unwrap_or("").trim();
+        if spec.is_empty() || spec.contains("://") {
+            return None;
+        }
+
+        let name_end = spec
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || c == '.'))
+            .unwrap_or(spec.len());
+        let name = &spec[..name_end];
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut version = spec[name_end..].trim();
+        if version.starts_with('[') {
+            version = match version.find(']') {
+                Some(index) => version[index + 1..].trim(),
+                None => "",
+            };
+        }
+
+        Some(Requirements::library(name, version, scope))
+    }
+
+    pub fn library(name: &str, version: &str, scope: &str) -> BaseLibrary {
+        BaseLibrary {
+            name: name.to_string(),
+            version: version.trim_start_matches("==").to_string(),
+            group: "".to_string(),
+            source: "pypi".to_string(),
+            scope: scope.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::facet::python::Requirements;
+
+    #[test]
+    fn should_parse_requirements_txt() {
+        let code = "# web
+Django>=3.1,<3.2
+djangorestframework==3.12.1  # api
+requests[security]==2.24.0 ; python_version >= \"3.6\"
+-r requirements-dev.txt
+-e git+https://github.com/phodal/demo.git#egg=demo
+psycopg2-binary
+";
+        let libraries = Requirements::parse(code, "");
+
+        assert_eq!(4, libraries.len());
+        assert_eq!("Django", libraries[0].name);
+        assert_eq!(">=3.1,<3.2", libraries[0].version);
+        assert_eq!("3.12.1", libraries[1].version);
+        assert_eq!("requests", libraries[2].name);
+        assert_eq!("2.24.0", libraries[2].version);
+        assert_eq!("", libraries[3].version);
+    }
+
+    #[test]
+    fn should_get_scope_by_file_name() {
+        assert_eq!("", Requirements::scope_by_file_name("requirements.txt"));
+        assert_eq!(
+            "Dev",
+            Requirements::scope_by_file_name("requirements-dev.txt")
+        );
+        assert_eq!(
+            "Test",
+            Requirements::scope_by_file_name("test-requirements.txt")
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use crate::dependency::base_library::BaseLibrary;
+use crate::facet::python::Requirements;
+use regex::Regex;
+
+lazy_static! {
+    static ref SETUP_REQUIRES: Regex =
+        Regex::new(r"(?s)(install_requires|tests_require)\s*=\s*\[(.*?)\]").unwrap();
+    static ref QUOTED: Regex = Regex::new(r#"["']([^"']+)["']"#).unwrap();
+}
+
+pub struct SetupTools {}
+
+impl SetupTools {
+    /// parse the `install_requires` and `tests_require` lists in `setup.py`
+    pub fn parse_setup_py(code: &str) -> Vec<BaseLibrary> {
+        let mut libraries = vec![];
+        for capture in SETUP_REQUIRES.captures_iter(code) {
+            let scope = SetupTools::scope_by_key(&capture[1]);
+            for quoted in QUOTED.captures_iter(&capture[2]) {
+                if let Some(library) = Requirements::parse_requirement(&quoted[1], scope) {
+                    libraries.push(library);
+                }
+            }
+        }
+
+        libraries
+    }
+
+    /// parse the `[options]` section in `setup.cfg`, values are dangling lists
+    pub fn parse_setup_cfg(code: &str) -> Vec<BaseLibrary> {
+        let mut libraries = vec![];
+        let mut section = String::new();
+        let mut current_key = String::new();
+
+        for line in code.lines() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            let is_continuation = line.starts_with(' ') || line.starts_with('\t');
+            let line = line.trim();
+            if line.starts_with('[') {
+                section = line.trim_matches(|c| c == '[' || c == ']').to_string();
+                current_key.clear();
+                continue;
+            }
+
+            let value = if is_continuation {
+                line
+            } else {
+                let mut pair = line.splitn(2, '=');
+                current_key = pair.next().unwrap_or("").trim().to_string();
+                pair.next().unwrap_or("").trim()
+            };
+
+            if section != "options" || value.is_empty() {
+                continue;
+            }
+
+            if current_key == "install_requires" || current_key == "tests_require" {
+                let scope = SetupTools::scope_by_key(&current_key);
+                if let Some(library) = Requirements::parse_requirement(value, scope) {
+                    libraries.push(library);
+                }
+            }
+        }
+
+        libraries
+    }
+
+    fn scope_by_key(key: &str) -> &'static str {
+        if key == "tests_require" {
+            "Test"
+        } else {
+            ""
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::facet::python::SetupTools;
+
+    #[test]
+    fn should_parse_setup_py() {
+        let code = "setup(
+    name=\"mysite\",
+    install_requires=[
+        \"Django>=3.1,<3.2\",
+        'gunicorn==20.0.4',
+    ],
+    tests_require=[\"pytest\"],
+)";
+        let libraries = SetupTools::parse_setup_py(code);
+
+        assert_eq!(3, libraries.len());
+        assert_eq!("gunicorn", libraries[1].name);
+        assert_eq!("20.0.4", libraries[1].version);
+        assert!(libraries[2].is_dev());
+    }
+
+    #[test]
+    fn should_parse_setup_cfg() {
+        let code = "[metadata]
+name = demo
+
+[options]
+packages = find:
+install_requires =
+    flask>=1.1
+    click
+tests_require = pytest
+";
+        let libraries = SetupTools::parse_setup_cfg(code);
+
+        assert_eq!(3, libraries.len());
+        assert_eq!("flask", libraries[0].name);
+        assert_eq!(">=1.1", libraries[0].version);
+        assert_eq!("Test", libraries[2].scope);
+    }
+}
@@ -1,4 +0,0 @@
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
-pub struct PythonFacet {
-    pub has_requirements: bool,
-}
@@ -1,2 +1,77 @@
+use crate::dependency::base_library::BaseLibrary;
+use toml::Value;
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct RustFacet {}
+
+impl RustFacet {
+    /// the `[dependencies]`, `[dev-dependencies]` and `[build-dependencies]` of `Cargo.toml`
+    pub fn dependencies(manifest: &str) -> Vec<BaseLibrary> {
+        let mut libraries = vec![];
+        let value: Value = match manifest.parse() {
+            Ok(value) => value,
+            Err(_) => return libraries,
+        };
+
+        let tables = [
+            ("dependencies", ""),
+            ("dev-dependencies", "Dev"),
+            ("build-dependencies", "Build"),
+        ];
+        for (key, scope) in tables.iter() {
+            let deps = match value.get(key).and_then(|deps| deps.as_table()) {
+                Some(deps) => deps,
+                None => continue,
+            };
+
+            for (name, spec) in deps.iter() {
+                let version = match spec {
+                    Value::String(version) => version.as_str(),
+                    Value::Table(detail) => detail
+                        .get("version")
+                        .and_then(|version| version.as_str())
+                        .unwrap_or(""),
+                    _ => "",
+                };
+
+                libraries.push(BaseLibrary {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                    group: "".to_string(),
+                    source: "crates.io".to_string(),
+                    scope: scope.to_string(),
+                });
+            }
+        }
+
+        libraries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::facet::rust_facet::RustFacet;
+
+    #[test]
+    fn should_parse_cargo_dependencies() {
+        let code = "[package]
+name = \"demo\"
+
+[dependencies]
+actix-web = \"3\"
+serde = { version = \"1.0\", features = [\"derive\"] }
+scie_model = { path = \"../scie-model\" }
+
+[dev-dependencies]
+actix-rt = \"1.0\"
+";
+        let libraries = RustFacet::dependencies(code);
+
+        assert_eq!(4, libraries.len());
+        assert_eq!("actix-web", libraries[0].name);
+        // the tables are sorted by name
+        assert_eq!("", libraries[1].version);
+        assert_eq!("1.0", libraries[2].version);
+        assert_eq!("Dev", libraries[3].scope);
+    }
+}
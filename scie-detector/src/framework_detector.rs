@@ -1,8 +1,23 @@
-use crate::facet::{JavaFacet, JvmFacet};
+use crate::content_root::ContentRoot;
+use crate::convention::{
+    CargoConvention, GoConvention, JvmConvention, NpmConvention, PythonConvention,
+};
+use crate::dependency::base_library::BaseLibrary;
+use crate::detect_result::{BuildSystem, Confidence, DetectResult, Detection, Ecosystem};
+use crate::facet::java::JvmDependencies;
+use crate::facet::javascript::PackageJson;
+use crate::facet::python::PyProject;
+use crate::facet::rust_facet::RustFacet;
+use crate::facet::{GoFacet, JavaFacet, JavaModuleData, JvmFacet, PythonFacet};
+use crate::rules::{FrameworkMatcher, FrameworkRules};
+use scie_model::artifact::{CodeFile, CodeFileKind};
 use std::collections::hash_map::RandomState;
-use std::collections::{BTreeMap, HashSet};
-use walkdir::WalkDir;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::{DirEntry, WalkDir};
 
+#[derive(Debug, Clone)]
 pub struct Framework {
     pub name: String,
     pub path: String,
@@ -15,37 +30,72 @@ pub struct Framework {
     pub framework_files: Vec<String>,
     // in JVM projects, has different languages, such as Java, Groovy, Kotlin...
     pub language: Vec<String>,
+    pub content_roots: Vec<ContentRoot>,
+    // the dependencies which declared by the module
+    pub dependencies: Vec<BaseLibrary>,
 }
 
-pub struct FrameworkDetector<'a> {
-    pub tags: BTreeMap<&'a str, bool>,
+pub struct FrameworkDetector {
+    pub result: DetectResult,
     pub frameworks: Vec<Framework>,
     pub java_facets: Vec<JavaFacet>,
+    pub java_modules: Vec<JavaModuleData>,
+    pub go_facets: Vec<GoFacet>,
+    pub python_facets: Vec<PythonFacet>,
+    // the dependencies of all modules
+    pub dependencies: Vec<BaseLibrary>,
+    pub rules: FrameworkRules,
+    // the evidences of framework tokens by rules, from `match_file`
+    token_evidences: Vec<Vec<String>>,
 }
 
-impl<'a> FrameworkDetector<'a> {
+impl FrameworkDetector {
     pub fn new() -> Self {
         FrameworkDetector {
-            tags: Default::default(),
+            result: Default::default(),
             frameworks: vec![],
             java_facets: vec![],
+            java_modules: vec![],
+            go_facets: vec![],
+            python_facets: vec![],
+            dependencies: vec![],
+            rules: Default::default(),
+            token_evidences: vec![],
         }
     }
 
     pub fn run(&mut self, path: String) {
-        self.light_detector(path)
+        self.result = DetectResult::new(&path);
+        self.light_detector(path.clone());
+        self.detect_go_modules(path.clone());
+
+        self.rules = FrameworkRules::load(Path::new(&path));
+        self.detect_frameworks(&[]);
+    }
+
+    /// match framework rules with the dependencies, and the tokens of files if it's analysed
+    pub fn detect_frameworks(&mut self, files: &[CodeFile]) {
+        self.result.frameworks = FrameworkMatcher::detect(&self.rules, &self.dependencies, files);
+    }
+
+    /// like `detect_frameworks`, but file by file, for the files which are not kept after analysed
+    pub fn match_file(&mut self, file: &CodeFile) {
+        FrameworkMatcher::match_tokens(&self.rules, file, &mut self.token_evidences);
+    }
+
+    pub fn detect_frameworks_by_matched(&mut self) {
+        self.result.frameworks =
+            FrameworkMatcher::usages(&self.rules, &self.dependencies, &self.token_evidences);
     }
 
     fn deep_detector(&mut self, _path: String) {}
 
-    fn build_frameworks_info(&mut self) {
-        if self.tags.contains_key("workspace.java.gradle")
-            || self.tags.contains_key("workspace.java.pom")
-        {
+    fn build_frameworks_info(&mut self, path: &str) {
+        if self.result.has_ecosystem(Ecosystem::Jvm) {
             let facet = JavaFacet {
                 jvm: JvmFacet {
-                    is_gradle: self.tags.contains_key("workspace.java.gradle"),
-                    is_maven: self.tags.contains_key("workspace.java.pom"),
+                    is_gradle: self.result.has_build_system(BuildSystem::Gradle),
+                    is_maven: self.result.has_build_system(BuildSystem::Maven),
                     has_java: false,
                     has_groovy: false,
                     has_kotlin: false,
@@ -56,42 +106,344 @@ impl<'a> FrameworkDetector<'a> {
 
             self.java_facets.push(facet)
         }
+
+        if self.result.has_build_system(BuildSystem::Gradle) {
+            self.build_jvm_modules(path, "gradle");
+        } else if self.result.has_build_system(BuildSystem::Maven) {
+            self.build_jvm_modules(path, "maven");
+        }
+
+        if self.result.has_build_system(BuildSystem::Cargo) {
+            let root = Path::new(path);
+            let mut module_dirs = vec![root.to_path_buf()];
+            module_dirs.append(&mut CargoConvention::members(root));
+            for dir in module_dirs {
+                let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap_or_default();
+
+                let content_root = CargoConvention::content_root(&dir);
+                self.push_module(
+                    path,
+                    &dir,
+                    "cargo",
+                    vec!["Cargo.toml"],
+                    "rust",
+                    content_root,
+                );
+                self.add_module_dependencies(RustFacet::dependencies(&manifest));
+            }
+        }
+
+        if self.result.has_build_system(BuildSystem::Npm) {
+            let root = Path::new(path);
+            let package = fs::read_to_string(root.join("package.json")).unwrap_or_default();
+
+            let content_root = NpmConvention::content_root(root);
+            self.push_module(
+                path,
+                root,
+                "npm",
+                vec!["package.json"],
+                "javascript",
+                content_root,
+            );
+            self.add_module_dependencies(PackageJson::dependencies(&package));
+        }
+
+        if self.result.has_ecosystem(Ecosystem::Python) {
+            let root = Path::new(path);
+            let facet = PythonFacet::from_dir(root);
+
+            let mut framework_files = vec![];
+            for name in FrameworkDetector::build_level_one_name_set(path.to_string()) {
+                if PythonFacet::is_python_project_file(&name) {
+                    framework_files.push(name);
+                }
+            }
+            framework_files.sort();
+
+            self.frameworks.push(Framework {
+                name: "python".to_string(),
+                path: path.to_string(),
+                relative_path: "".to_string(),
+                framework_files,
+                language: vec!["python".to_string()],
+                content_roots: vec![PythonConvention::content_root(root)],
+                dependencies: facet.dependencies.clone(),
+            });
+            self.dependencies.extend(facet.dependencies.iter().cloned());
+            self.python_facets.push(facet);
+        }
+    }
+
+    /// the root project and its subprojects, like `include ':app'` in gradle and `<module>` in maven
+    fn build_jvm_modules(&mut self, path: &str, build_tool: &str) {
+        let root = Path::new(path);
+        let mut module_dirs = vec![root.to_path_buf()];
+        let framework_files = if build_tool == "gradle" {
+            module_dirs.append(&mut JvmConvention::gradle_modules(root));
+            vec!["build.gradle", "settings.gradle"]
+        } else {
+            module_dirs.append(&mut JvmConvention::maven_modules(root));
+            vec!["pom.xml"]
+        };
+
+        for dir in module_dirs {
+            let (content_root, dependencies) = if build_tool == "gradle" {
+                let script = fs::read_to_string(dir.join("build.gradle"))
+                    .or_else(|_| fs::read_to_string(dir.join("build.gradle.kts")))
+                    .unwrap_or_default();
+                (
+                    JvmConvention::gradle_content_root(&dir),
+                    JvmDependencies::parse_gradle(&script),
+                )
+            } else {
+                let pom = fs::read_to_string(dir.join("pom.xml")).unwrap_or_default();
+                (
+                    JvmConvention::maven_content_root(&dir),
+                    JvmDependencies::parse_maven(&pom),
+                )
+            };
+            let module_name = match dir.file_name() {
+                Some(name) => name.to_str().unwrap_or("").to_string(),
+                None => "".to_string(),
+            };
+            self.java_modules.push(JavaModuleData {
+                module_name,
+                content_roots: vec![content_root.clone()],
+            });
+            self.push_module(
+                path,
+                &dir,
+                build_tool,
+                framework_files.clone(),
+                "java",
+                content_root,
+            );
+            self.add_module_dependencies(dependencies);
+        }
+    }
+
+    fn push_module(
+        &mut self,
+        root: &str,
+        dir: &Path,
+        name: &str,
+        files: Vec<&str>,
+        language: &str,
+        content_root: ContentRoot,
+    ) {
+        let framework_files = files
+            .into_iter()
+            .filter(|file| dir.join(file).exists())
+            .map(|file| file.to_string())
+            .collect();
+        let relative_path: PathBuf = dir.strip_prefix(root).unwrap_or(dir).to_path_buf();
+
+        self.frameworks.push(Framework {
+            name: name.to_string(),
+            path: dir.display().to_string(),
+            relative_path: relative_path.display().to_string(),
+            framework_files,
+            language: vec![language.to_string()],
+            content_roots: vec![content_root],
+            dependencies: vec![],
+        });
+    }
+
+    /// the dependencies of the last pushed module
+    fn add_module_dependencies(&mut self, dependencies: Vec<BaseLibrary>) {
+        self.dependencies.extend(dependencies.iter().cloned());
+        if let Some(framework) = self.frameworks.last_mut() {
+            framework.dependencies.extend(dependencies);
+        }
+    }
+
+    /// the content root of the nearest module, for nested modules like `tools/go.mod`
+    pub fn content_root_of(&self, path: &Path) -> Option<&ContentRoot> {
+        self.frameworks
+            .iter()
+            .flat_map(|framework| framework.content_roots.iter())
+            .filter(|content_root| content_root.contains(path))
+            .max_by_key(|content_root| content_root.root().len())
+    }
+
+    pub fn classify(&self, path: &Path) -> CodeFileKind {
+        match self.content_root_of(path) {
+            Some(content_root) => content_root.classify(path),
+            None => CodeFileKind::Unknown,
+        }
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        match self.content_root_of(path) {
+            Some(content_root) => content_root.is_excluded(path),
+            None => false,
+        }
     }
 
     fn light_detector(&mut self, path: String) {
-        let name_set = FrameworkDetector::build_level_one_name_set(path);
-        self.tags
-            .insert("workspace.java.gradle", name_set.contains("build.gradle"));
-        self.tags.insert(
-            "workspace.java.gradle.composite",
-            name_set.contains("build.gradle") && name_set.contains("settings.gradle"),
-        );
+        let names = FrameworkDetector::build_level_one_name_set(path.clone());
 
-        self.tags
-            .insert("workspace.java.pom", name_set.contains("pom.xml"));
+        if names.contains("build.gradle") || names.contains("build.gradle.kts") {
+            let files = [
+                "build.gradle",
+                "build.gradle.kts",
+                "settings.gradle",
+                "settings.gradle.kts",
+            ];
+            self.detect_by_files(&names, BuildSystem::Gradle, &files, Confidence::High);
+        }
+        self.detect_by_files(&names, BuildSystem::Maven, &["pom.xml"], Confidence::High);
 
-        self.tags.insert(
-            "workspace.bower",
-            name_set.contains("bower.json") || name_set.contains("bower_components"),
-        );
+        let npm_files = ["package.json", "package-lock.json", "yarn.lock"];
+        if !self.detect_by_files(&names, BuildSystem::Npm, &npm_files, Confidence::High) {
+            self.detect_by_files(
+                &names,
+                BuildSystem::Npm,
+                &["node_modules"],
+                Confidence::Medium,
+            );
+        }
+        if !self.detect_by_files(
+            &names,
+            BuildSystem::Bower,
+            &["bower.json"],
+            Confidence::High,
+        ) {
+            let files = ["bower_components"];
+            self.detect_by_files(&names, BuildSystem::Bower, &files, Confidence::Medium);
+        }
 
-        self.tags.insert(
-            "workspace.npm",
-            name_set.contains("package.json") || name_set.contains("node_modules"),
+        self.detect_by_files(
+            &names,
+            BuildSystem::CMake,
+            &["CMakeLists.txt"],
+            Confidence::High,
         );
+        let cargo_files = ["Cargo.toml", "Cargo.lock"];
+        self.detect_by_files(&names, BuildSystem::Cargo, &cargo_files, Confidence::High);
+
+        // the modules are detected by `detect_go_modules`, `main.go` only is the GOPATH style
+        if !names.contains("go.mod") && names.contains("main.go") {
+            self.result.add(Detection {
+                ecosystem: Ecosystem::Go,
+                build_system: None,
+                evidences: vec!["main.go".to_string()],
+                confidence: Confidence::Low,
+            });
+        }
 
-        self.tags
-            .insert("workspace.c", name_set.contains("CMakeLists.txt"));
+        self.detect_python(&path, &names);
+        self.build_frameworks_info(&path);
+    }
+
+    fn detect_python(&mut self, path: &str, names: &HashSet<String>) {
+        let mut requirements: Vec<&str> = names
+            .iter()
+            .filter(|name| PythonFacet::is_requirements_file(name))
+            .map(|name| name.as_str())
+            .collect();
+        requirements.sort();
+        self.detect_by_files(names, BuildSystem::Pip, &requirements, Confidence::High);
 
-        self.tags.insert(
-            "workspace.go",
-            name_set.contains("go.mod") || name_set.contains("main.got"),
+        let setup_files = ["setup.py", "setup.cfg"];
+        self.detect_by_files(
+            names,
+            BuildSystem::Setuptools,
+            &setup_files,
+            Confidence::High,
         );
+        let pipenv_files = ["Pipfile", "Pipfile.lock"];
+        self.detect_by_files(names, BuildSystem::Pipenv, &pipenv_files, Confidence::High);
+
+        if names.contains("pyproject.toml") {
+            let code =
+                fs::read_to_string(Path::new(path).join("pyproject.toml")).unwrap_or_default();
+            let build_system = if PyProject::parse(&code).is_poetry {
+                BuildSystem::Poetry
+            } else {
+                BuildSystem::PyProject
+            };
+            let files = ["pyproject.toml", "poetry.lock"];
+            self.detect_by_files(names, build_system, &files, Confidence::High);
+        }
+    }
 
-        self.tags
-            .insert("workspace.rust.cargo", name_set.contains("Cargo.toml"));
+    /// add detection with the existing files as evidences, returns false when none of them exists
+    fn detect_by_files(
+        &mut self,
+        names: &HashSet<String>,
+        build_system: BuildSystem,
+        files: &[&str],
+        confidence: Confidence,
+    ) -> bool {
+        let evidences: Vec<String> = files
+            .iter()
+            .filter(|file| names.contains(**file))
+            .map(|file| file.to_string())
+            .collect();
+        if evidences.is_empty() {
+            return false;
+        }
 
-        self.build_frameworks_info();
+        self.result
+            .add(Detection::build_system(build_system, evidences).with_confidence(confidence));
+        true
+    }
+
+    /// every `go.mod` in the repository is a module, include nested modules like `tools/go.mod`
+    fn detect_go_modules(&mut self, path: String) {
+        let root = Path::new(&path);
+        let walk_dir = WalkDir::new(root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| !FrameworkDetector::is_skip_dir(entry));
+
+        for entry in walk_dir.filter_map(|entry| entry.ok()) {
+            if entry.file_name() != "go.mod" {
+                continue;
+            }
+
+            let module_dir = entry.path().parent().unwrap_or(root);
+            let facet = GoFacet::from_dir(module_dir);
+
+            let mut framework_files = vec!["go.mod".to_string()];
+            if facet.has_sum {
+                framework_files.push("go.sum".to_string());
+            }
+            if facet.has_vendor {
+                framework_files.push("vendor/modules.txt".to_string());
+            }
+
+            let relative_path = module_dir.strip_prefix(root).unwrap_or(module_dir);
+            let evidences = framework_files
+                .iter()
+                .map(|file| relative_path.join(file).display().to_string())
+                .collect();
+            self.result
+                .add(Detection::build_system(BuildSystem::GoModules, evidences));
+
+            self.frameworks.push(Framework {
+                name: "go".to_string(),
+                path: module_dir.display().to_string(),
+                relative_path: relative_path.display().to_string(),
+                framework_files,
+                language: vec!["go".to_string()],
+                content_roots: vec![GoConvention::content_root(module_dir)],
+                dependencies: facet.dependencies.clone(),
+            });
+            self.dependencies.extend(facet.dependencies.iter().cloned());
+            self.go_facets.push(facet);
+        }
+    }
+
+    fn is_skip_dir(entry: &DirEntry) -> bool {
+        if entry.depth() == 0 || !entry.file_type().is_dir() {
+            return false;
+        }
+
+        let name = entry.file_name().to_str().unwrap_or("");
+        name.starts_with('.') || name == "vendor" || name == "testdata" || name == "node_modules"
     }
 
     pub fn build_level_one_name_set(path: String) -> HashSet<String, RandomState> {
@@ -104,7 +456,7 @@ impl<'a> FrameworkDetector<'a> {
 
             let entry = dir_entry.unwrap();
             if entry.path().file_name().is_none() {
-                println!("none file_name {:?}", entry.path());
+                eprintln!("none file_name {:?}", entry.path());
                 continue;
             }
 
@@ -118,10 +470,12 @@ impl<'a> FrameworkDetector<'a> {
 
 #[cfg(test)]
 mod tests {
+    use crate::detect_result::{BuildSystem, Confidence, Ecosystem};
     use crate::framework_detector::FrameworkDetector;
+    use scie_model::artifact::CodeFileKind;
     use std::path::PathBuf;
 
-    fn build_test_detector<'a>() -> FrameworkDetector<'a> {
+    fn build_test_detector() -> FrameworkDetector {
         let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
             .parent()
             .unwrap()
@@ -143,12 +497,11 @@ mod tests {
     fn should_detect_java_gradle_project() {
         let detector = build_test_detector();
 
-        assert!(detector.tags.get("workspace.java.gradle").unwrap());
-        assert!(detector
-            .tags
-            .get("workspace.java.gradle.composite")
-            .unwrap());
-        assert_eq!(&false, detector.tags.get("workspace.npm").unwrap());
+        let gradle = detector.result.get(BuildSystem::Gradle).unwrap();
+        assert_eq!(vec!["build.gradle", "settings.gradle"], gradle.evidences);
+        assert_eq!(Confidence::High, gradle.confidence);
+        assert_eq!(vec![Ecosystem::Jvm], detector.result.ecosystems());
+        assert!(!detector.result.has_build_system(BuildSystem::Npm));
     }
 
     #[test]
@@ -157,4 +510,139 @@ mod tests {
 
         assert_eq!(1, detector.java_facets.len());
     }
+
+    #[test]
+    fn should_build_gradle_modules_with_content_roots() {
+        let detector = build_test_detector();
+
+        assert_eq!(2, detector.java_modules.len());
+        assert_eq!("app", detector.java_modules[1].module_name);
+        assert_eq!(2, detector.frameworks.len());
+        assert_eq!("app", detector.frameworks[1].relative_path);
+    }
+
+    #[test]
+    fn should_classify_go_test_file() {
+        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .to_path_buf();
+        let project_dir = root_dir
+            .join("fixtures")
+            .join("projects")
+            .join("go")
+            .join("modules");
+
+        let mut detector = FrameworkDetector::new();
+        detector.run(project_dir.display().to_string());
+
+        let nested_dir = project_dir.join("tools").join("lint");
+        assert_eq!(
+            nested_dir.display().to_string(),
+            detector
+                .content_root_of(&nested_dir.join("main.go"))
+                .unwrap()
+                .root()
+        );
+        assert_eq!(
+            CodeFileKind::Source,
+            detector.classify(&project_dir.join("main.go"))
+        );
+        assert_eq!(
+            CodeFileKind::Test,
+            detector.classify(&project_dir.join("main_test.go"))
+        );
+        assert!(detector.is_excluded(&project_dir.join("vendor").join("modules.txt")));
+    }
+
+    #[test]
+    fn should_detect_python_project() {
+        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .to_path_buf();
+        let test_project_dir = root_dir
+            .join("fixtures")
+            .join("projects")
+            .join("python")
+            .join("django");
+
+        let mut detector = FrameworkDetector::new();
+        detector.run(test_project_dir.display().to_string());
+
+        assert!(detector.result.has_build_system(BuildSystem::Pip));
+        assert!(detector.result.has_build_system(BuildSystem::Setuptools));
+        assert_eq!(1, detector.python_facets.len());
+        assert!(detector.python_facets[0].is_django);
+        assert_eq!(
+            vec!["requirements-dev.txt", "requirements.txt", "setup.py"],
+            detector.frameworks[0].framework_files
+        );
+    }
+
+    #[test]
+    fn should_detect_frameworks_by_dependencies_and_local_rules() {
+        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .to_path_buf();
+        let test_project_dir = root_dir
+            .join("fixtures")
+            .join("projects")
+            .join("java")
+            .join("spring");
+
+        let mut detector = FrameworkDetector::new();
+        detector.run(test_project_dir.display().to_string());
+
+        let frameworks = &detector.result.frameworks;
+        assert_eq!("Spring Boot", frameworks[0].name);
+        assert_eq!("2.3.4.RELEASE", frameworks[0].version);
+        assert_eq!(Confidence::Medium, frameworks[0].confidence);
+        assert!(detector.result.has_framework("JUnit"));
+        // from `.scie/frameworks.toml`
+        assert!(detector.result.has_framework("Spring Web"));
+    }
+
+    #[test]
+    fn should_detect_nested_go_modules() {
+        let root_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .to_path_buf();
+        let test_project_dir = root_dir
+            .join("fixtures")
+            .join("projects")
+            .join("go")
+            .join("modules");
+
+        let mut detector = FrameworkDetector::new();
+        detector.run(test_project_dir.display().to_string());
+
+        let evidences: Vec<&str> = detector
+            .result
+            .detections
+            .iter()
+            .filter(|detection| detection.build_system == Some(BuildSystem::GoModules))
+            .flat_map(|detection| detection.evidences.iter().map(|file| file.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                "go.mod",
+                "go.sum",
+                "vendor/modules.txt",
+                "tools/lint/go.mod"
+            ],
+            evidences
+        );
+        assert_eq!(2, detector.frameworks.len());
+        assert_eq!(2, detector.go_facets.len());
+
+        let nested = detector
+            .frameworks
+            .iter()
+            .find(|framework| framework.relative_path.ends_with("lint"))
+            .unwrap();
+        assert_eq!(vec!["go.mod".to_string()], nested.framework_files);
+    }
 }
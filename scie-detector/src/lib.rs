@@ -7,9 +7,13 @@ extern crate lazy_static;
 extern crate serde_derive;
 extern crate regex;
 extern crate serde;
+extern crate toml;
 
 pub mod framework_detector;
 
 pub mod content_root;
+pub mod convention;
 pub mod dependency;
+pub mod detect_result;
 pub mod facet;
+pub mod rules;
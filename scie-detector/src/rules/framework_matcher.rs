@@ -0,0 +1,236 @@
+use crate::content_root::ContentRoot;
+use crate::dependency::base_library::BaseLibrary;
+use crate::detect_result::{Confidence, Ecosystem, FrameworkUsage};
+use crate::rules::{FrameworkRule, FrameworkRules};
+use scie_model::artifact::CodeFile;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub struct FrameworkMatcher {}
+
+impl FrameworkMatcher {
+    /// the rules matched by both dependencies and tokens have `High` confidence, else `Medium`
+    pub fn detect(
+        rules: &FrameworkRules,
+        dependencies: &[BaseLibrary],
+        files: &[CodeFile],
+    ) -> Vec<FrameworkUsage> {
+        let mut token_evidences = vec![vec![]; rules.rules.len()];
+        for file in files {
+            FrameworkMatcher::match_tokens(rules, file, &mut token_evidences);
+        }
+
+        FrameworkMatcher::usages(rules, dependencies, &token_evidences)
+    }
+
+    /// collects the evidences of tokens by the index of rules, file by file, so the files need not
+    /// to be kept in memory
+    pub fn match_tokens(rules: &FrameworkRules, file: &CodeFile, evidences: &mut Vec<Vec<String>>) {
+        evidences.resize(rules.rules.len(), vec![]);
+        let ecosystem = Path::new(&file.path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Ecosystem::from_extension);
+        if ecosystem.is_none() {
+            return;
+        }
+
+        let lines = FrameworkMatcher::code_lines(file);
+        for (index, rule) in rules.rules.iter().enumerate() {
+            if ecosystem != Some(rule.ecosystem) {
+                continue;
+            }
+
+            if let Some((line_num, token)) = FrameworkMatcher::find_token(rule, &lines) {
+                evidences[index].push(format!("token {} in {}:{}", token, file.path, line_num));
+            }
+        }
+    }
+
+    pub fn usages(
+        rules: &FrameworkRules,
+        dependencies: &[BaseLibrary],
+        token_evidences: &[Vec<String>],
+    ) -> Vec<FrameworkUsage> {
+        let mut usages = vec![];
+        for (index, rule) in rules.rules.iter().enumerate() {
+            let mut evidences = vec![];
+            let mut version = "".to_string();
+            for library in dependencies {
+                if !FrameworkMatcher::match_dependency(rule, library) {
+                    continue;
+                }
+
+                if version.is_empty() {
+                    version = library.version.clone();
+                }
+                let coordinate = FrameworkMatcher::coordinate(library);
+                evidences.push(format!("dependency {}", coordinate));
+            }
+            let has_dependency = !evidences.is_empty();
+
+            let tokens = token_evidences
+                .get(index)
+                .map(|tokens| tokens.as_slice())
+                .unwrap_or(&[]);
+            let has_token = !tokens.is_empty();
+            evidences.extend(tokens.iter().cloned());
+
+            if !has_dependency && !has_token {
+                continue;
+            }
+
+            let confidence = if has_dependency && has_token {
+                Confidence::High
+            } else {
+                Confidence::Medium
+            };
+            usages.push(FrameworkUsage {
+                name: rule.name.clone(),
+                ecosystem: rule.ecosystem,
+                version,
+                evidences,
+                confidence,
+            });
+        }
+
+        usages
+    }
+
+    fn match_dependency(rule: &FrameworkRule, library: &BaseLibrary) -> bool {
+        let name = library.name.to_lowercase();
+        let coordinate = FrameworkMatcher::coordinate(library).to_lowercase();
+        rule.dependencies.iter().any(|pattern| {
+            let pattern = pattern.to_lowercase();
+            if pattern.contains(':') {
+                ContentRoot::wildcard_match(&pattern, &coordinate)
+            } else {
+                ContentRoot::wildcard_match(&pattern, &name)
+            }
+        })
+    }
+
+    fn coordinate(library: &BaseLibrary) -> String {
+        if library.group.is_empty() {
+            library.name.clone()
+        } else {
+            format!("{}:{}", library.group, library.name)
+        }
+    }
+
+    fn find_token<'a>(rule: &'a FrameworkRule, lines: &[(i32, String)]) -> Option<(i32, &'a str)> {
+        for (line_num, code) in lines {
+            for token in rule.tokens.iter() {
+                if code.contains(&FrameworkMatcher::normalize(token)) {
+                    return Some((*line_num, token));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// join the tokens of each line, comments are skipped
+    fn code_lines(file: &CodeFile) -> Vec<(i32, String)> {
+        let mut lines: BTreeMap<i32, String> = BTreeMap::new();
+        for element in file.elements.iter() {
+            if element.scopes.iter().any(|scope| scope.contains("comment")) {
+                continue;
+            }
+
+            lines
+                .entry(element.line_num)
+                .or_default()
+                .push_str(&element.value);
+        }
+
+        lines
+            .into_iter()
+            .map(|(line_num, code)| (line_num, FrameworkMatcher::normalize(&code)))
+            .collect()
+    }
+
+    fn normalize(code: &str) -> String {
+        code.split_whitespace().collect::<Vec<&str>>().join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dependency::base_library::BaseLibrary;
+    use crate::detect_result::Confidence;
+    use crate::rules::{FrameworkMatcher, FrameworkRules};
+    use scie_model::artifact::{CodeFile, TokenElement};
+    use std::path::PathBuf;
+
+    fn library(group: &str, name: &str, version: &str) -> BaseLibrary {
+        BaseLibrary {
+            name: name.to_string(),
+            version: version.to_string(),
+            group: group.to_string(),
+            source: "".to_string(),
+            scope: "".to_string(),
+        }
+    }
+
+    fn code_file(path: &str, lines: Vec<Vec<(&str, &str)>>) -> CodeFile {
+        let mut file = CodeFile::new(PathBuf::from(path));
+        for (index, tokens) in lines.into_iter().enumerate() {
+            let mut start = 0;
+            for (value, scope) in tokens {
+                let end = start + value.len() as i32;
+                file.elements.push(TokenElement::new(
+                    index as i32 + 1,
+                    start,
+                    end,
+                    value.to_string(),
+                    vec!["source.java".to_string(), scope.to_string()],
+                ));
+                start = end;
+            }
+        }
+        file
+    }
+
+    #[test]
+    fn should_detect_framework_by_dependency_and_token() {
+        let dependencies = vec![library(
+            "org.springframework.boot",
+            "spring-boot-starter-web",
+            "2.3.4.RELEASE",
+        )];
+        let file = code_file(
+            "src/App.java",
+            vec![
+                vec![("// @SpringBootApplication", "comment.line.java")],
+                vec![
+                    ("@", "punctuation.definition.annotation.java"),
+                    ("SpringBootApplication", "storage.type.annotation.java"),
+                ],
+            ],
+        );
+
+        let usages = FrameworkMatcher::detect(&FrameworkRules::builtin(), &dependencies, &[file]);
+
+        assert_eq!(1, usages.len());
+        assert_eq!("Spring Boot", usages[0].name);
+        assert_eq!("2.3.4.RELEASE", usages[0].version);
+        assert_eq!(Confidence::High, usages[0].confidence);
+        assert_eq!(
+            "token @SpringBootApplication in src/App.java:2",
+            usages[0].evidences[1]
+        );
+    }
+
+    #[test]
+    fn should_detect_framework_by_dependency_only() {
+        let dependencies = vec![library("", "Django", ">=3.1"), library("", "requests", "")];
+
+        let usages = FrameworkMatcher::detect(&FrameworkRules::builtin(), &dependencies, &[]);
+
+        assert_eq!(1, usages.len());
+        assert_eq!("Django", usages[0].name);
+        assert_eq!(">=3.1", usages[0].version);
+        assert_eq!(Confidence::Medium, usages[0].confidence);
+    }
+}
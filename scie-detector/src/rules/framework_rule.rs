@@ -0,0 +1,108 @@
+use crate::detect_result::Ecosystem;
+use std::fs;
+use std::path::Path;
+
+const BUILTIN_RULES: &str = include_str!("frameworks.toml");
+pub const LOCAL_RULES_FILE: &str = ".scie/frameworks.toml";
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct FrameworkRule {
+    pub name: String,
+    pub ecosystem: Ecosystem,
+    // `name` or `group:name`, with `*` wildcard
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    // the code in one line, like `@SpringBootApplication` or `import React`
+    #[serde(default)]
+    pub tokens: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct FrameworkRules {
+    #[serde(default, rename = "framework")]
+    pub rules: Vec<FrameworkRule>,
+}
+
+impl FrameworkRules {
+    pub fn parse(code: &str) -> Result<FrameworkRules, toml::de::Error> {
+        toml::from_str(code)
+    }
+
+    pub fn builtin() -> FrameworkRules {
+        FrameworkRules::parse(BUILTIN_RULES).unwrap()
+    }
+
+    /// the built-in rules, with the rules in `.scie/frameworks.toml` of the project
+    pub fn load(root: &Path) -> FrameworkRules {
+        let mut rules = FrameworkRules::builtin();
+        let path = root.join(LOCAL_RULES_FILE);
+        if let Ok(code) = fs::read_to_string(&path) {
+            match FrameworkRules::parse(&code) {
+                Ok(local) => rules.merge(local),
+                Err(err) => eprintln!("invalid rules file {:?}: {}", path, err),
+            }
+        }
+
+        rules
+    }
+
+    /// the rules with the same name will be replaced
+    pub fn merge(&mut self, other: FrameworkRules) {
+        for rule in other.rules {
+            match self.rules.iter_mut().find(|exist| exist.name == rule.name) {
+                Some(exist) => *exist = rule,
+                None => self.rules.push(rule),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::detect_result::Ecosystem;
+    use crate::rules::FrameworkRules;
+
+    #[test]
+    fn should_load_builtin_rules() {
+        let rules = FrameworkRules::builtin();
+
+        let spring = rules
+            .rules
+            .iter()
+            .find(|rule| rule.name == "Spring Boot")
+            .unwrap();
+        assert_eq!(Ecosystem::Jvm, spring.ecosystem);
+        assert!(spring
+            .tokens
+            .contains(&"@SpringBootApplication".to_string()));
+    }
+
+    #[test]
+    fn should_merge_local_rules_by_name() {
+        let mut rules = FrameworkRules::builtin();
+        let count = rules.rules.len();
+        let local = FrameworkRules::parse(
+            "[[framework]]
+name = \"React\"
+ecosystem = \"JavaScript\"
+dependencies = [\"preact\"]
+
+[[framework]]
+name = \"Micronaut\"
+ecosystem = \"Jvm\"
+tokens = [\"@MicronautTest\"]
+",
+        )
+        .unwrap();
+        rules.merge(local);
+
+        assert_eq!(count + 1, rules.rules.len());
+        let react = rules
+            .rules
+            .iter()
+            .find(|rule| rule.name == "React")
+            .unwrap();
+        assert_eq!(vec!["preact"], react.dependencies);
+        assert!(react.tokens.is_empty());
+    }
+}
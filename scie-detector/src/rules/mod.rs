@@ -0,0 +1,5 @@
+pub mod framework_matcher;
+pub mod framework_rule;
+
+pub use framework_matcher::FrameworkMatcher;
+pub use framework_rule::{FrameworkRule, FrameworkRules};
@@ -5,6 +5,7 @@ use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Instant;
 
 use crate::grammar::line_tokens::{IToken, LineTokens, TokenTypeMatcher};
 use crate::grammar::local_stack_element::LocalStackElement;
@@ -49,6 +50,8 @@ pub struct Grammar {
     pub rule_container: Box<RuleContainer>,
     pub scope_name_map: Map<String, i32>,
     pub _token_type_matchers: Vec<TokenTypeMatcher>,
+    // the line is not tokenized after it, by `tokenize_line_until`
+    deadline: Option<Instant>,
 }
 
 pub fn init_grammar(raw_grammar: IRawGrammar, _base: Option<IRawRule>) -> IRawGrammar {
@@ -91,6 +94,7 @@ impl Grammar {
             _token_type_matchers: vec![],
             _empty_rule,
             rules: vec![],
+            deadline: None,
         };
 
         grammar._empty_rule.insert(-2, Box::new(EmptyRule {}));
@@ -208,6 +212,12 @@ impl Grammar {
         }
 
         while !_stop {
+            // a long line takes too many matches, like the minified files
+            if self.is_expired() {
+                line_tokens.produce(&mut stack, line_length as i32);
+                return Some(stack);
+            }
+
             let r = self.match_rule(
                 line_text,
                 is_first_line,
@@ -253,7 +263,7 @@ impl Grammar {
                     }
                     anchor_position = popped_anchor_pos;
                 } else {
-                    println!("_popped_rule {:?}", _popped_rule.clone());
+                    eprintln!("_popped_rule {:?}", _popped_rule.clone());
                     _stop = true;
                     return Some(stack);
                 }
@@ -457,7 +467,7 @@ impl Grammar {
                     ));
                 }
             } else {
-                println!("lose rule: {:?}", captures[i].clone());
+                eprintln!("lose rule: {:?}", captures[i].clone());
             }
         }
 
@@ -597,6 +607,30 @@ impl Grammar {
         self.tokenize(line_text, prev_state, false)
     }
 
+    /// like `tokenize_line`, but none when the line is not tokenized before the deadline
+    pub fn tokenize_line_until(
+        &mut self,
+        line_text: &str,
+        prev_state: &mut Option<StackElement>,
+        deadline: Instant,
+    ) -> Option<TokenizeResult> {
+        self.deadline = Some(deadline);
+        let result = self.tokenize(line_text, prev_state, false);
+        self.deadline = None;
+
+        if Instant::now() >= deadline {
+            return None;
+        }
+        Some(result)
+    }
+
+    fn is_expired(&self) -> bool {
+        match self.deadline {
+            Some(deadline) => Instant::now() >= deadline,
+            None => false,
+        }
+    }
+
     pub fn dispose(&self) {
         for (_key, _rule) in self.rule_container.rule_id2desc.iter() {
             // rule.dispose();
@@ -612,7 +646,7 @@ impl Grammar {
         let g: IRawGrammar = match serde_json::from_str(&data) {
             Ok(x) => x,
             Err(err) => {
-                println!("error path: {:?}, err: {:?}", grammar_path, err);
+                eprintln!("error path: {:?}, err: {:?}", grammar_path, err);
                 panic!(err);
             }
         };
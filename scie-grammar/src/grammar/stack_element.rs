@@ -125,6 +125,24 @@ impl StackElement {
         }
     }
 
+    /// the same rules and scopes, without the positions of line, like `StackElement.equals` of
+    /// vscode-textmate, so the next line is not tokenized again when the states are equal
+    pub fn equals(&self, other: &StackElement) -> bool {
+        if self.depth != other.depth
+            || self.rule_id != other.rule_id
+            || self.end_rule != other.end_rule
+            || self.content_name_scopes_list != other.content_name_scopes_list
+        {
+            return false;
+        }
+
+        match (&self.parent, &other.parent) {
+            (Some(parent), Some(other)) => parent.equals(other),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
     pub fn set_end_rule(&self, end_rule: String) -> StackElement {
         if self.end_rule.is_some() {
             if *self.end_rule.as_ref().unwrap() == end_rule {
@@ -62,7 +62,7 @@ pub trait AbstractRule: DynClone + erased_serde::Serialize {
             return _content_name;
         }
 
-        println!("todo: AbstractRule.get_name");
+        eprintln!("todo: AbstractRule.get_name");
         return Some(String::from(""));
     }
 
@@ -330,10 +330,13 @@ impl RegExpSource {
         line_text: &str,
         capture_indices: Vec<IOnigCaptureIndex>,
     ) -> String {
+        // the indexes are UTF-16 code units, and the unmatched captures are out of the line
+        let units: Vec<u16> = line_text.encode_utf16().collect();
         let captured_values: Vec<String> = capture_indices
             .into_iter()
-            .map(|x| {
-                return line_text[x.start..x.end].to_string();
+            .map(|x| match units.get(x.start..x.end) {
+                Some(captured) => String::from_utf16_lossy(captured),
+                None => String::new(),
             })
             .collect();
 
@@ -121,7 +121,7 @@ impl RuleFactory {
                     //     String::from(include_s.as_str()),
                     // );
                     } else {
-                        println!("todo: external grammar {:?}", pattern.include);
+                        eprintln!("todo: external grammar {:?}", pattern.include);
                         let mut _external_grammar_name: Option<String> = None;
                         let mut _external_grammar_include: Option<String> = None;
                         let include_string = pattern.include.as_ref().unwrap();
@@ -134,7 +134,7 @@ impl RuleFactory {
                             let (_, include_last) = include_string.split_at(index + 1);
                             _external_grammar_include = Some(String::from(include_last));
 
-                            println!(
+                            eprintln!(
                                 "{:?}, {:?}",
                                 _external_grammar_name, _external_grammar_include
                             );
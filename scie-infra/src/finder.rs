@@ -56,7 +56,7 @@ impl Finder {
                 };
             }
             Err(err) => {
-                println!("error: {:?}", file_path);
+                eprintln!("error: {:?}", file_path);
                 return Err(err.to_string());
             }
         };
@@ -69,7 +69,7 @@ impl Finder {
                 Ok(entry) => {
                     files.push(entry.path().to_path_buf());
                 }
-                Err(err) => println!("ERROR: {}", err),
+                Err(err) => eprintln!("ERROR: {}", err),
             }
         }
 
@@ -81,7 +81,7 @@ impl Finder {
 
         let dir = tempdir().unwrap();
         let genearte_ignore_file_path = dir.path().join("scie-ignore.txt");
-        println!("generate ignore file: {:?}", genearte_ignore_file_path);
+        eprintln!("generate ignore file: {:?}", genearte_ignore_file_path);
 
         let mut tmpfile = File::create(genearte_ignore_file_path.clone()).unwrap();
         let content: String = DEFAULT_VCS_EXCLUDES.join("\n");
@@ -1,18 +1,43 @@
 pub struct FormatOutput {}
 
 impl FormatOutput {
-    pub fn hashmap() {
-        println!(
-            "{0: <10} | {1: <10} | {2: <10} | {3: <10}",
-            "total", "blanks", "comments", "code"
-        );
-        println!("{0: <10} | {1: <10} | {2: <10} | {3: <10}", 0, 0, 0, 0);
-        println!("{0: <10} | {1: <10} | {2: <10} | {3: <10}", 77, 0, 3, 74);
-        println!("{0: <10} | {1: <10} | {2: <10} | {3: <10}", 112, 0, 6, 106);
-        println!(
-            "{0: <10} | {1: <10} | {2: <10} | {3: <10}",
-            460, 0, 10, 1371
-        );
+    /// align the columns by the widest cell, the first column is left aligned and others right
+    pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+        let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+        for row in rows {
+            for (index, cell) in row.iter().enumerate() {
+                if index < widths.len() && cell.chars().count() > widths[index] {
+                    widths[index] = cell.chars().count();
+                }
+            }
+        }
+
+        let headers: Vec<String> = headers.iter().map(|header| header.to_string()).collect();
+        let mut lines = vec![FormatOutput::line(&headers, &widths)];
+        let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        lines.push(separator.join("-|-"));
+        for row in rows {
+            lines.push(FormatOutput::line(row, &widths));
+        }
+
+        lines.join("\n")
+    }
+
+    fn line(cells: &[String], widths: &[usize]) -> String {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(index, width)| {
+                let cell = cells.get(index).map(|cell| cell.as_str()).unwrap_or("");
+                if index == 0 {
+                    format!("{0: <1$}", cell, width)
+                } else {
+                    format!("{0: >1$}", cell, width)
+                }
+            })
+            .collect();
+
+        cells.join(" | ")
     }
 }
 
@@ -21,7 +46,19 @@ mod tests {
     use crate::format_output::FormatOutput;
 
     #[test]
-    fn should_filter_gitignore_rules() {
-        FormatOutput::hashmap();
+    fn should_align_table_columns() {
+        let rows = vec![
+            vec!["java".to_string(), "77".to_string(), "3".to_string()],
+            vec!["Total".to_string(), "1371".to_string(), "10".to_string()],
+        ];
+        let table = FormatOutput::table(&["language", "code", "comments"], &rows);
+
+        assert_eq!(
+            "language | code | comments
+---------|------|---------
+java     |   77 |        3
+Total    | 1371 |       10",
+            table
+        );
     }
 }
@@ -0,0 +1,293 @@
+use lsp_types::{Position, TextDocumentContentChangeEvent};
+use scie_core::analyser::LexicalAnalyser;
+use scie_grammar::grammar::{Grammar, StackElement};
+use scie_model::artifact::TokenElement;
+
+/// the grammar of language, or the fallback by `language-configuration.json`
+pub enum Tokenizer {
+    Grammar(Box<Grammar>),
+    Lexer(LexicalAnalyser),
+}
+
+#[derive(Debug, Clone)]
+pub struct LineState {
+    pub text: String,
+    // the tokens of the line, which `line_num` is not kept for the lines are moved by edits
+    pub tokens: Vec<TokenElement>,
+    // the rule stack at the end of line, the next line is tokenized from it
+    pub end_stack: Option<StackElement>,
+    is_dirty: bool,
+}
+
+impl LineState {
+    fn new(text: &str) -> LineState {
+        LineState {
+            text: text.to_string(),
+            tokens: vec![],
+            end_stack: None,
+            is_dirty: true,
+        }
+    }
+}
+
+/// an opened text document, which only tokenizes the changed lines, and the lines after them
+/// until the rule stack is the same as before
+pub struct Document {
+    pub text: String,
+    pub version: i32,
+    lines: Vec<LineState>,
+}
+
+impl Document {
+    pub fn new(text: &str, version: i32) -> Document {
+        Document {
+            text: text.to_string(),
+            version,
+            lines: text.lines().map(LineState::new).collect(),
+        }
+    }
+
+    pub fn lines(&self) -> &[LineState] {
+        &self.lines
+    }
+
+    /// the changes are in order, and the positions of each are after the previous changes
+    pub fn apply_changes(&mut self, changes: &[TextDocumentContentChangeEvent], version: i32) {
+        for change in changes {
+            match change.range {
+                Some(range) => {
+                    let start = self.offset_of(range.start);
+                    let end = self.offset_of(range.end).max(start);
+                    self.text.replace_range(start..end, &change.text);
+                }
+                None => self.text = change.text.clone(),
+            }
+        }
+
+        self.version = version;
+        self.sync_lines();
+    }
+
+    /// returns the count of tokenized lines
+    pub fn tokenize(&mut self, tokenizer: &mut Tokenizer) -> usize {
+        if !self.lines.iter().any(|line| line.is_dirty) {
+            return 0;
+        }
+
+        match tokenizer {
+            Tokenizer::Grammar(grammar) => self.tokenize_by_grammar(grammar),
+            Tokenizer::Lexer(lexer) => {
+                for line in self.lines.iter_mut() {
+                    line.tokens.clear();
+                    line.is_dirty = false;
+                }
+                for element in lexer.tokenize(&self.text) {
+                    if let Some(line) = self.lines.get_mut(element.line_num as usize - 1) {
+                        line.tokens.push(element);
+                    }
+                }
+                self.lines.len()
+            }
+        }
+    }
+
+    /// the tokens of all lines, the lines start from 1 like `Analyser`
+    pub fn elements(&self) -> Vec<TokenElement> {
+        let mut elements = vec![];
+        for (index, line) in self.lines.iter().enumerate() {
+            for token in line.tokens.iter() {
+                elements.push(TokenElement::new(
+                    index as i32 + 1,
+                    token.start_index,
+                    token.end_index,
+                    token.value.clone(),
+                    token.scopes.clone(),
+                ));
+            }
+        }
+
+        elements
+    }
+
+    /// the offset by bytes of the position, which character is by UTF-16
+    pub fn offset_of(&self, position: Position) -> usize {
+        let mut offset = 0;
+        for (index, line) in self.text.split('\n').enumerate() {
+            if index == position.line as usize {
+                let mut units = 0;
+                for (byte, char) in line.char_indices() {
+                    if units >= position.character as usize || char == '\r' {
+                        return offset + byte;
+                    }
+                    units += char.len_utf16();
+                }
+                return offset + line.len();
+            }
+            offset += line.len() + 1;
+        }
+
+        self.text.len()
+    }
+
+    /// keeps the states of the same lines at the start and the end, the lines between them and
+    /// the first line after them are dirty
+    fn sync_lines(&mut self) {
+        let texts: Vec<&str> = self.text.lines().collect();
+        let old = std::mem::take(&mut self.lines);
+
+        let prefix = old
+            .iter()
+            .zip(texts.iter())
+            .take_while(|(line, text)| line.text == **text)
+            .count();
+        let max_suffix = old.len().min(texts.len()) - prefix;
+        let suffix = old
+            .iter()
+            .rev()
+            .zip(texts.iter().rev())
+            .take(max_suffix)
+            .take_while(|(line, text)| line.text == **text)
+            .count();
+
+        let removed = old.len() - prefix - suffix;
+        let mut old = old.into_iter();
+        self.lines.extend(old.by_ref().take(prefix));
+        let mut changed: Vec<LineState> = texts[prefix..texts.len() - suffix]
+            .iter()
+            .map(|text| LineState::new(text))
+            .collect();
+        // the last changed line keeps the end stack of replaced lines, to stop at the same state
+        let last_removed = old.by_ref().take(removed).last();
+        if let (Some(line), Some(removed)) = (changed.last_mut(), last_removed) {
+            line.end_stack = removed.end_stack;
+        }
+        self.lines.append(&mut changed);
+        self.lines.extend(old);
+
+        if let Some(line) = self.lines.get_mut(prefix) {
+            line.is_dirty = true;
+        }
+    }
+
+    fn tokenize_by_grammar(&mut self, grammar: &mut Grammar) -> usize {
+        let mut count = 0;
+        for index in 0..self.lines.len() {
+            if !self.lines[index].is_dirty {
+                continue;
+            }
+
+            let mut stack = match index {
+                0 => Some(StackElement::null()),
+                _ => self.lines[index - 1].end_stack.clone(),
+            };
+            let line = &mut self.lines[index];
+            let result = grammar.tokenize_line(&line.text, &mut stack);
+            // the indexes of tokens are UTF-16 code units
+            let units: Vec<u16> = line.text.encode_utf16().collect();
+            line.tokens = result
+                .tokens
+                .into_iter()
+                .map(|token| {
+                    let value =
+                        TokenElement::utf16_value(&units, token.start_index, token.end_index);
+                    TokenElement::new(0, token.start_index, token.end_index, value, token.scopes)
+                })
+                .collect();
+
+            let is_changed = match (&line.end_stack, &result.rule_stack) {
+                (Some(old), Some(new)) => !old.equals(new),
+                (None, None) => false,
+                _ => true,
+            };
+            line.end_stack = result.rule_stack;
+            line.is_dirty = false;
+            count += 1;
+
+            if is_changed {
+                if let Some(next) = self.lines.get_mut(index + 1) {
+                    next.is_dirty = true;
+                }
+            }
+        }
+
+        count
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::document::{Document, Tokenizer};
+    use lsp_types::{Position, Range, TextDocumentContentChangeEvent};
+    use scie_grammar::grammar::Grammar;
+    use std::path::PathBuf;
+
+    pub fn java_tokenizer() -> Tokenizer {
+        let lang = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("extensions")
+            .join("java/syntaxes/java.tmLanguage.json");
+
+        Tokenizer::Grammar(Box::new(Grammar::from_file(lang.to_str().unwrap())))
+    }
+
+    fn change(
+        line: u32,
+        start: u32,
+        end_line: u32,
+        end: u32,
+        text: &str,
+    ) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(
+                Position::new(line, start),
+                Position::new(end_line, end),
+            )),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn should_apply_changes_by_utf16() {
+        let mut document = Document::new("// 名字 😀\nint a;\r\nint b;", 1);
+        document.apply_changes(&[change(0, 6, 0, 8, "x")], 2);
+        assert_eq!("// 名字 x\nint a;\r\nint b;", document.text);
+
+        document.apply_changes(&[change(1, 6, 2, 0, "")], 3);
+        assert_eq!("// 名字 x\nint a;int b;", document.text);
+        assert_eq!(2, document.lines().len());
+    }
+
+    #[test]
+    fn should_tokenize_changed_lines_only() {
+        let code = "class Demo {\n    int a = 1;\n    int b = 2;\n    int c = 3;\n}";
+        let mut tokenizer = java_tokenizer();
+        let mut document = Document::new(code, 1);
+        assert_eq!(5, document.tokenize(&mut tokenizer));
+
+        document.apply_changes(&[change(2, 8, 2, 9, "bb")], 2);
+        assert_eq!(1, document.tokenize(&mut tokenizer));
+        assert!(document
+            .elements()
+            .iter()
+            .any(|element| element.line_num == 3 && element.value == "bb"));
+
+        // the lines after the start of comment are changed, until the end of comment
+        document.apply_changes(&[change(1, 4, 1, 4, "/* ")], 3);
+        assert_eq!(5 - 1, document.tokenize(&mut tokenizer));
+        let comment = document
+            .elements()
+            .into_iter()
+            .find(|element| element.line_num == 3 && element.value.contains("int"))
+            .unwrap();
+        assert!(comment
+            .scopes
+            .iter()
+            .any(|scope| scope.starts_with("comment")));
+
+        document.apply_changes(&[change(3, 14, 3, 14, " */")], 4);
+        assert_eq!(2, document.tokenize(&mut tokenizer));
+        assert_eq!(0, document.tokenize(&mut tokenizer));
+    }
+}
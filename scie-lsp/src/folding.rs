@@ -0,0 +1,158 @@
+use crate::document::Document;
+use lsp_types::{FoldingRange, FoldingRangeKind};
+use scie_model::artifact::TokenElement;
+use scie_model::LanguageConfig;
+use std::collections::BTreeMap;
+
+/// the folding ranges of the brackets in `language-configuration.json`, and of the begin/end
+/// rules of grammar which are in multiple lines, like the block comments
+pub struct Folding {}
+
+impl Folding {
+    pub fn ranges(document: &Document, config: Option<&LanguageConfig>) -> Vec<FoldingRange> {
+        let mut ranges: BTreeMap<u32, FoldingRange> = BTreeMap::new();
+        if let Some(config) = config {
+            for range in Folding::bracket_ranges(document, &config.bracket_pairs()) {
+                ranges.entry(range.start_line).or_insert(range);
+            }
+        }
+        // the rules of bodies are the same as the brackets, which end before the closing line
+        for range in Folding::rule_ranges(document) {
+            ranges.entry(range.start_line).or_insert(range);
+        }
+
+        ranges.into_values().collect()
+    }
+
+    /// the lines between the brackets, out of the comments and strings
+    fn bracket_ranges(document: &Document, brackets: &[(&str, &str)]) -> Vec<FoldingRange> {
+        let mut ranges = vec![];
+        let mut opened: Vec<(usize, u32)> = vec![];
+
+        for (line_index, line) in document.lines().iter().enumerate() {
+            let line_index = line_index as u32;
+            for token in line.tokens.iter() {
+                if Folding::has(token, "comment") || Folding::has(token, "string") {
+                    continue;
+                }
+
+                let value = token.value.as_str();
+                for (offset, _) in value.char_indices() {
+                    let rest = &value[offset..];
+                    if let Some(index) =
+                        brackets.iter().position(|(open, _)| rest.starts_with(open))
+                    {
+                        opened.push((index, line_index));
+                    } else if let Some(index) = brackets
+                        .iter()
+                        .position(|(_, close)| rest.starts_with(close))
+                    {
+                        let position = match opened.iter().rposition(|(open, _)| *open == index) {
+                            Some(position) => position,
+                            None => continue,
+                        };
+                        let (_, start_line) = opened[position];
+                        opened.truncate(position);
+                        if line_index > start_line + 1 {
+                            ranges.push(Folding::range(start_line, line_index - 1, None));
+                        }
+                    }
+                }
+            }
+        }
+
+        ranges
+    }
+
+    /// the lines which the rule stack is deeper than the start of the line, by the end stacks
+    fn rule_ranges(document: &Document) -> Vec<FoldingRange> {
+        let mut ranges = vec![];
+        // the line and the depth of the outermost rule began in the line
+        let mut opened: Vec<(u32, i32, Option<FoldingRangeKind>)> = vec![];
+        // the root rule of grammar
+        let mut previous_depth = 1;
+
+        for (line_index, line) in document.lines().iter().enumerate() {
+            let line_index = line_index as u32;
+            let depth = match &line.end_stack {
+                Some(stack) => stack.depth,
+                None => return ranges,
+            };
+
+            while let Some((start_line, start_depth, kind)) = opened.last().cloned() {
+                if start_depth <= depth {
+                    break;
+                }
+                opened.pop();
+                if line_index > start_line {
+                    ranges.push(Folding::range(start_line, line_index, kind));
+                }
+            }
+
+            if depth > previous_depth {
+                let kind = line
+                    .tokens
+                    .last()
+                    .filter(|token| Folding::has(token, "comment"))
+                    .map(|_| FoldingRangeKind::Comment);
+                opened.push((line_index, previous_depth + 1, kind));
+            }
+            previous_depth = depth;
+        }
+
+        ranges
+    }
+
+    fn range(start_line: u32, end_line: u32, kind: Option<FoldingRangeKind>) -> FoldingRange {
+        FoldingRange {
+            start_line,
+            start_character: None,
+            end_line,
+            end_character: None,
+            kind,
+            collapsed_text: None,
+        }
+    }
+
+    fn has(token: &TokenElement, prefix: &str) -> bool {
+        token
+            .scopes
+            .iter()
+            .skip(1)
+            .flat_map(|scopes| scopes.split_whitespace())
+            .any(|scope| scope.starts_with(prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::document::tests::java_tokenizer;
+    use crate::document::Document;
+    use crate::folding::Folding;
+    use lsp_types::FoldingRangeKind;
+    use scie_model::LanguageConfig;
+
+    #[test]
+    fn should_fold_brackets_and_comments() {
+        let code = r#"/**
+ * the demo
+ */
+class Demo {
+    void run() {
+        String a = "{";
+    }
+}"#;
+        let mut document = Document::new(code, 1);
+        document.tokenize(&mut java_tokenizer());
+        let config = LanguageConfig::parse(r#"{ "brackets": [["{", "}"], ["(", ")"]] }"#).unwrap();
+
+        let ranges = Folding::ranges(&document, Some(&config));
+        let lines: Vec<(u32, u32)> = ranges
+            .iter()
+            .map(|range| (range.start_line, range.end_line))
+            .collect();
+        assert_eq!(vec![(0, 2), (3, 6), (4, 5)], lines);
+        assert_eq!(Some(FoldingRangeKind::Comment), ranges[0].kind);
+        assert_eq!(None, ranges[1].kind);
+    }
+}
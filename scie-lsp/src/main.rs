@@ -0,0 +1,14 @@
+pub mod document;
+pub mod folding;
+pub mod semantic_tokens;
+pub mod server;
+pub mod symbols;
+
+use server::Server;
+
+fn main() {
+    if let Err(err) = Server::run() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,325 @@
+use crate::document::Document;
+use lsp_types::{
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensEdit,
+    SemanticTokensLegend,
+};
+use scie_model::artifact::TokenElement;
+
+/// the prefixes of scopes to the types of token, the longer prefixes are before the shorter ones
+const TYPE_RULES: [(&str, SemanticTokenType); 37] = [
+    ("comment", SemanticTokenType::COMMENT),
+    ("string.regexp", SemanticTokenType::REGEXP),
+    ("string", SemanticTokenType::STRING),
+    ("constant.numeric", SemanticTokenType::NUMBER),
+    ("constant.regexp", SemanticTokenType::REGEXP),
+    ("constant.character", SemanticTokenType::STRING),
+    ("constant.language", SemanticTokenType::KEYWORD),
+    ("constant.other.enum", SemanticTokenType::ENUM_MEMBER),
+    ("keyword.operator", SemanticTokenType::OPERATOR),
+    ("keyword", SemanticTokenType::KEYWORD),
+    ("storage.type.annotation", SemanticTokenType::DECORATOR),
+    ("storage.modifier", SemanticTokenType::MODIFIER),
+    ("storage.type", SemanticTokenType::TYPE),
+    ("entity.name.function.macro", SemanticTokenType::MACRO),
+    (
+        "entity.name.function.preprocessor",
+        SemanticTokenType::MACRO,
+    ),
+    ("entity.name.function", SemanticTokenType::FUNCTION),
+    ("support.function", SemanticTokenType::FUNCTION),
+    ("entity.name.type.class", SemanticTokenType::CLASS),
+    ("entity.name.type.interface", SemanticTokenType::INTERFACE),
+    ("entity.name.type.enum", SemanticTokenType::ENUM),
+    ("entity.name.type.struct", SemanticTokenType::STRUCT),
+    ("entity.name.type.module", SemanticTokenType::NAMESPACE),
+    ("entity.name.type.namespace", SemanticTokenType::NAMESPACE),
+    (
+        "entity.name.type.parameter",
+        SemanticTokenType::TYPE_PARAMETER,
+    ),
+    ("entity.name.type", SemanticTokenType::TYPE),
+    ("entity.name.namespace", SemanticTokenType::NAMESPACE),
+    ("entity.name.package", SemanticTokenType::NAMESPACE),
+    ("entity.other.inherited-class", SemanticTokenType::CLASS),
+    ("entity.other.attribute-name", SemanticTokenType::PROPERTY),
+    ("support.class", SemanticTokenType::CLASS),
+    ("support.type", SemanticTokenType::TYPE),
+    ("variable.parameter", SemanticTokenType::PARAMETER),
+    ("variable.other.enummember", SemanticTokenType::ENUM_MEMBER),
+    ("variable.other.property", SemanticTokenType::PROPERTY),
+    (
+        "variable.other.object.property",
+        SemanticTokenType::PROPERTY,
+    ),
+    ("variable.language", SemanticTokenType::KEYWORD),
+    ("variable", SemanticTokenType::VARIABLE),
+];
+
+const MODIFIERS: [SemanticTokenModifier; 4] = [
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::DOCUMENTATION,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
+];
+
+/// the scopes of definitions, which the names of functions in them are declarations
+const DEFINITION_SCOPES: [&str; 4] = [
+    "meta.definition",
+    "meta.method.identifier",
+    "meta.function.definition",
+    "meta.function.declaration",
+];
+
+/// the semantic tokens of the grammar tokens, which are the same as the TextMate highlighting
+/// for the editors without TextMate
+pub struct SemanticTokens {}
+
+impl SemanticTokens {
+    pub fn legend() -> SemanticTokensLegend {
+        let mut token_types: Vec<SemanticTokenType> = vec![];
+        for (_, token_type) in TYPE_RULES.iter() {
+            if !token_types.contains(token_type) {
+                token_types.push(token_type.clone());
+            }
+        }
+
+        SemanticTokensLegend {
+            token_types,
+            token_modifiers: MODIFIERS.to_vec(),
+        }
+    }
+
+    /// the type and the bits of modifiers in the legend, the innermost scope is first matched
+    pub fn classify(
+        element: &TokenElement,
+        token_types: &[SemanticTokenType],
+    ) -> Option<(u32, u32)> {
+        let (scope, token_type) = element.scopes.iter().skip(1).rev().find_map(|scopes| {
+            scopes.split_whitespace().rev().find_map(|scope| {
+                TYPE_RULES
+                    .iter()
+                    .find(|(prefix, _)| SemanticTokens::is_scope(scope, prefix))
+                    .map(|(_, token_type)| (scope, token_type.clone()))
+            })
+        })?;
+
+        // like `int` of Java, and `function` of TypeScript
+        let token_type = if token_type == SemanticTokenType::TYPE
+            && scope.starts_with("storage.type")
+            && element
+                .value
+                .trim()
+                .chars()
+                .all(|it| it.is_ascii_lowercase())
+        {
+            SemanticTokenType::KEYWORD
+        } else {
+            token_type
+        };
+
+        let has = |prefix: &str| {
+            element.scopes.iter().skip(1).any(|scopes| {
+                scopes
+                    .split_whitespace()
+                    .any(|scope| SemanticTokens::is_scope(scope, prefix))
+            })
+        };
+        let mut modifiers = 0;
+        let is_declaration = if token_type == SemanticTokenType::FUNCTION {
+            DEFINITION_SCOPES.iter().any(|it| has(it))
+        } else {
+            token_type == SemanticTokenType::PARAMETER
+                || scope.starts_with("entity.name.type")
+                || scope.starts_with("entity.name.namespace")
+                || scope.starts_with("entity.name.package")
+                || scope.starts_with("variable.other.definition")
+        };
+        if is_declaration {
+            modifiers |= 1 << 0;
+        }
+        if has("variable.other.constant") || has("constant.other") {
+            modifiers |= 1 << 1;
+        }
+        if has("comment.block.documentation") || has("comment.block.javadoc") {
+            modifiers |= 1 << 2;
+        }
+        if scope.starts_with("support") {
+            modifiers |= 1 << 3;
+        }
+
+        let index = token_types.iter().position(|it| *it == token_type)?;
+        Some((index as u32, modifiers))
+    }
+
+    /// the relative positions of tokens by UTF-16, the whitespaces of tokens are trimmed
+    pub fn encode(document: &Document) -> Vec<SemanticToken> {
+        let token_types = SemanticTokens::legend().token_types;
+        let mut data = vec![];
+        let mut previous_line = 0;
+        let mut previous_start = 0;
+
+        for (line_index, line) in document.lines().iter().enumerate() {
+            for token in line.tokens.iter() {
+                let value = token.value.trim_start();
+                let leading: usize = token.value[..token.value.len() - value.len()]
+                    .chars()
+                    .map(char::len_utf16)
+                    .sum();
+                let start = token.start_index as u32 + leading as u32;
+                let value = value.trim_end();
+                if value.is_empty() {
+                    continue;
+                }
+                let (token_type, token_modifiers_bitset) =
+                    match SemanticTokens::classify(token, &token_types) {
+                        Some(it) => it,
+                        None => continue,
+                    };
+
+                let line = line_index as u32;
+                let delta_start = if line == previous_line {
+                    start - previous_start
+                } else {
+                    start
+                };
+                data.push(SemanticToken {
+                    delta_line: line - previous_line,
+                    delta_start,
+                    length: value.encode_utf16().count() as u32,
+                    token_type,
+                    token_modifiers_bitset,
+                });
+                previous_line = line;
+                previous_start = start;
+            }
+        }
+
+        data
+    }
+
+    /// the edit between the tokens of the previous and the current result, by the same prefix and
+    /// suffix of them
+    pub fn delta(previous: &[SemanticToken], current: &[SemanticToken]) -> Vec<SemanticTokensEdit> {
+        let prefix = previous
+            .iter()
+            .zip(current.iter())
+            .take_while(|(old, new)| old == new)
+            .count();
+        if prefix == previous.len() && prefix == current.len() {
+            return vec![];
+        }
+
+        let max_suffix = previous.len().min(current.len()) - prefix;
+        let suffix = previous
+            .iter()
+            .rev()
+            .zip(current.iter().rev())
+            .take(max_suffix)
+            .take_while(|(old, new)| old == new)
+            .count();
+
+        vec![SemanticTokensEdit {
+            start: (prefix * 5) as u32,
+            delete_count: ((previous.len() - prefix - suffix) * 5) as u32,
+            data: Some(current[prefix..current.len() - suffix].to_vec()),
+        }]
+    }
+
+    fn is_scope(scope: &str, prefix: &str) -> bool {
+        scope.starts_with(prefix) && matches!(scope.as_bytes().get(prefix.len()), None | Some(b'.'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::document::tests::java_tokenizer;
+    use crate::document::Document;
+    use crate::semantic_tokens::SemanticTokens;
+    use lsp_types::{SemanticToken, SemanticTokenType};
+
+    fn type_name(token: &SemanticToken) -> SemanticTokenType {
+        SemanticTokens::legend().token_types[token.token_type as usize].clone()
+    }
+
+    #[test]
+    fn should_encode_java_tokens() {
+        let code = "// 名字\nclass Demo {\n    int a = 1;\n}";
+        let mut document = Document::new(code, 1);
+        document.tokenize(&mut java_tokenizer());
+
+        let data = SemanticTokens::encode(&document);
+        assert_eq!(SemanticTokenType::COMMENT, type_name(&data[0]));
+        assert_eq!(SemanticTokenType::COMMENT, type_name(&data[1]));
+        assert_eq!((2, 2), (data[0].length, data[1].length));
+
+        let class = data
+            .iter()
+            .position(|it| type_name(it) == SemanticTokenType::CLASS)
+            .unwrap();
+        assert_eq!(1, data[class].token_modifiers_bitset & 1);
+        assert_eq!(4, data[class].length);
+        assert_eq!(6, data[class].delta_start);
+
+        let int = &data[class + 1];
+        assert_eq!(SemanticTokenType::KEYWORD, type_name(int));
+        assert_eq!((1, 4), (int.delta_line, int.delta_start));
+        assert!(data
+            .iter()
+            .any(|it| type_name(it) == SemanticTokenType::NUMBER));
+    }
+
+    #[test]
+    fn should_encode_tokens_after_emoji() {
+        let code = "class Demo {\n    String s = \"🌏\"; int a = 1;\n}";
+        let mut document = Document::new(code, 1);
+        document.tokenize(&mut java_tokenizer());
+
+        let data = SemanticTokens::encode(&document);
+        let (mut line, mut start) = (0, 0);
+        let positions: Vec<(u32, u32)> = data
+            .iter()
+            .map(|it| {
+                start = if it.delta_line == 0 {
+                    start + it.delta_start
+                } else {
+                    it.delta_start
+                };
+                line += it.delta_line;
+                (line, start)
+            })
+            .collect();
+        let int = data
+            .iter()
+            .enumerate()
+            .position(|(index, it)| {
+                positions[index].0 == 1 && type_name(it) == SemanticTokenType::KEYWORD
+            })
+            .unwrap();
+
+        let text = "    String s = \"🌏\"; int a = 1;";
+        let expected = text[..text.find("int").unwrap()].encode_utf16().count() as u32;
+        assert_eq!((1, expected), positions[int]);
+        assert_eq!(3, data[int].length);
+    }
+
+    #[test]
+    fn should_build_delta_edits() {
+        let token = |delta_line: u32, length: u32| SemanticToken {
+            delta_line,
+            delta_start: 0,
+            length,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        };
+        let previous = vec![token(0, 1), token(1, 2), token(1, 3)];
+        let current = vec![token(0, 1), token(1, 4), token(1, 5), token(1, 3)];
+
+        let edits = SemanticTokens::delta(&previous, &current);
+        assert_eq!(1, edits.len());
+        assert_eq!(5, edits[0].start);
+        assert_eq!(5, edits[0].delete_count);
+        assert_eq!(2, edits[0].data.as_ref().unwrap().len());
+
+        assert!(SemanticTokens::delta(&current, &current).is_empty());
+    }
+}
@@ -0,0 +1,399 @@
+use crate::document::{Document, Tokenizer};
+use crate::folding::Folding;
+use crate::semantic_tokens::SemanticTokens;
+use crate::symbols::DocumentSymbols;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as LspNotification,
+};
+use lsp_types::request::{
+    DocumentSymbolRequest, FoldingRangeRequest, Request as LspRequest,
+    SemanticTokensFullDeltaRequest, SemanticTokensFullRequest,
+};
+use lsp_types::{
+    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentSymbolParams, DocumentSymbolResponse, FoldingRange, FoldingRangeParams,
+    FoldingRangeProviderCapability, OneOf, SemanticToken, SemanticTokensDelta,
+    SemanticTokensDeltaParams, SemanticTokensFullDeltaResult, SemanticTokensFullOptions,
+    SemanticTokensOptions, SemanticTokensParams, SemanticTokensResult,
+    SemanticTokensServerCapabilities, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use scie_bingen::grammar_gen::{GrammarGen, LangConfig};
+use scie_bingen::language_gen::LangExtGen;
+use scie_core::analyser::LexicalAnalyser;
+use scie_grammar::grammar::Grammar;
+use scie_model::LanguageConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+struct OpenDocument {
+    document: Document,
+    // the extension of path, like `.java`
+    ext: String,
+    // the result id and the data of last semantic tokens, for the delta of next request
+    previous: Option<(String, Vec<SemanticToken>)>,
+}
+
+/// the language server over stdio, the grammars and the configurations of languages are loaded
+/// by the extensions of opened documents
+pub struct Server {
+    grammars: BTreeMap<String, LangConfig>,
+    lang_ext: LangExtGen,
+    tokenizers: HashMap<String, Option<Tokenizer>>,
+    configs: HashMap<String, Option<LanguageConfig>>,
+    documents: HashMap<Url, OpenDocument>,
+    result_id: u64,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Server {
+            grammars: GrammarGen::build_grammar_map(),
+            lang_ext: LangExtGen::default(),
+            tokenizers: Default::default(),
+            configs: Default::default(),
+            documents: Default::default(),
+            result_id: 0,
+        }
+    }
+}
+
+impl Server {
+    pub fn run() -> Result<(), String> {
+        let (connection, io_threads) = Connection::stdio();
+        let capabilities =
+            serde_json::to_value(Server::capabilities()).map_err(|err| err.to_string())?;
+        connection
+            .initialize(capabilities)
+            .map_err(|err| err.to_string())?;
+
+        Server::default().main_loop(connection)?;
+        io_threads.join().map_err(|err| err.to_string())
+    }
+
+    pub fn capabilities() -> ServerCapabilities {
+        ServerCapabilities {
+            text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                TextDocumentSyncKind::INCREMENTAL,
+            )),
+            semantic_tokens_provider: Some(
+                SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+                    legend: SemanticTokens::legend(),
+                    full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                    range: None,
+                    work_done_progress_options: Default::default(),
+                }),
+            ),
+            document_symbol_provider: Some(OneOf::Left(true)),
+            folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+            ..Default::default()
+        }
+    }
+
+    fn main_loop(mut self, connection: Connection) -> Result<(), String> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection
+                        .handle_shutdown(&request)
+                        .map_err(|err| err.to_string())?
+                    {
+                        return Ok(());
+                    }
+
+                    let response = self.handle_request(request);
+                    connection
+                        .sender
+                        .send(Message::Response(response))
+                        .map_err(|err| err.to_string())?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification),
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn handle_request(&mut self, request: Request) -> Response {
+        let params = request.params;
+        let result = match request.method.as_str() {
+            SemanticTokensFullRequest::METHOD => {
+                Server::reply(params, |params: SemanticTokensParams| {
+                    self.semantic_tokens(&params.text_document.uri)
+                })
+            }
+            SemanticTokensFullDeltaRequest::METHOD => {
+                Server::reply(params, |params: SemanticTokensDeltaParams| {
+                    self.semantic_tokens_delta(
+                        &params.text_document.uri,
+                        &params.previous_result_id,
+                    )
+                })
+            }
+            DocumentSymbolRequest::METHOD => {
+                Server::reply(params, |params: DocumentSymbolParams| {
+                    self.document_symbols(&params.text_document.uri)
+                })
+            }
+            FoldingRangeRequest::METHOD => Server::reply(params, |params: FoldingRangeParams| {
+                self.folding_ranges(&params.text_document.uri)
+            }),
+            method => {
+                return Response::new_err(
+                    request.id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("unsupported request: {}", method),
+                )
+            }
+        };
+
+        match result {
+            Ok(value) => Response::new_ok(request.id, value),
+            Err(err) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, err),
+        }
+    }
+
+    /// the documents are tokenized by the next requests, not by each change
+    pub fn handle_notification(&mut self, notification: Notification) {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                if let Ok(params) =
+                    serde_json::from_value::<DidOpenTextDocumentParams>(notification.params)
+                {
+                    let document = params.text_document;
+                    let ext = Path::new(document.uri.path())
+                        .extension()
+                        .map(|ext| format!(".{}", ext.to_string_lossy()))
+                        .unwrap_or_default();
+                    self.documents.insert(
+                        document.uri,
+                        OpenDocument {
+                            document: Document::new(&document.text, document.version),
+                            ext,
+                            previous: None,
+                        },
+                    );
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                if let Ok(params) =
+                    serde_json::from_value::<DidChangeTextDocumentParams>(notification.params)
+                {
+                    if let Some(open) = self.documents.get_mut(&params.text_document.uri) {
+                        open.document
+                            .apply_changes(&params.content_changes, params.text_document.version);
+                    }
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Ok(params) =
+                    serde_json::from_value::<DidCloseTextDocumentParams>(notification.params)
+                {
+                    self.documents.remove(&params.text_document.uri);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn semantic_tokens(&mut self, uri: &Url) -> Option<SemanticTokensResult> {
+        let result_id = self.next_result_id();
+        let open = self.tokenized(uri)?;
+        let data = SemanticTokens::encode(&open.document);
+        open.previous = Some((result_id.clone(), data.clone()));
+
+        Some(SemanticTokensResult::Tokens(lsp_types::SemanticTokens {
+            result_id: Some(result_id),
+            data,
+        }))
+    }
+
+    fn semantic_tokens_delta(
+        &mut self,
+        uri: &Url,
+        previous_result_id: &str,
+    ) -> Option<SemanticTokensFullDeltaResult> {
+        let result_id = self.next_result_id();
+        let open = self.tokenized(uri)?;
+        let data = SemanticTokens::encode(&open.document);
+
+        let result = match &open.previous {
+            Some((id, previous)) if id == previous_result_id => {
+                SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+                    result_id: Some(result_id.clone()),
+                    edits: SemanticTokens::delta(previous, &data),
+                })
+            }
+            _ => SemanticTokensFullDeltaResult::Tokens(lsp_types::SemanticTokens {
+                result_id: Some(result_id.clone()),
+                data: data.clone(),
+            }),
+        };
+        open.previous = Some((result_id, data));
+
+        Some(result)
+    }
+
+    fn document_symbols(&mut self, uri: &Url) -> Option<DocumentSymbolResponse> {
+        let open = self.tokenized(uri)?;
+        Some(DocumentSymbolResponse::Nested(
+            DocumentSymbols::from_document(&open.document),
+        ))
+    }
+
+    fn folding_ranges(&mut self, uri: &Url) -> Option<Vec<FoldingRange>> {
+        let ext = self.documents.get(uri)?.ext.clone();
+        let lang_ext = &self.lang_ext;
+        let config = self
+            .configs
+            .entry(ext.clone())
+            .or_insert_with(|| Server::load_config(lang_ext, &ext))
+            .clone();
+
+        let open = self.tokenized(uri)?;
+        Some(Folding::ranges(&open.document, config.as_ref()))
+    }
+
+    /// the document which the changed lines are tokenized
+    fn tokenized(&mut self, uri: &Url) -> Option<&mut OpenDocument> {
+        let open = self.documents.get_mut(uri)?;
+        let (grammars, lang_ext) = (&self.grammars, &self.lang_ext);
+        let tokenizer = self
+            .tokenizers
+            .entry(open.ext.clone())
+            .or_insert_with(|| Server::load_tokenizer(grammars, lang_ext, &open.ext));
+
+        if let Some(tokenizer) = tokenizer {
+            open.document.tokenize(tokenizer);
+        }
+        Some(open)
+    }
+
+    /// the grammar of the extension, or the lexical analyser of `language-configuration.json`
+    fn load_tokenizer(
+        grammars: &BTreeMap<String, LangConfig>,
+        lang_ext: &LangExtGen,
+        ext: &str,
+    ) -> Option<Tokenizer> {
+        match grammars.get(ext) {
+            Some(config) if Path::new(&config.path).exists() => Some(Tokenizer::Grammar(Box::new(
+                Grammar::from_file(&config.path),
+            ))),
+            _ => LexicalAnalyser::by_ext(lang_ext, ext).map(Tokenizer::Lexer),
+        }
+    }
+
+    fn load_config(lang_ext: &LangExtGen, ext: &str) -> Option<LanguageConfig> {
+        let code = fs::read_to_string(lang_ext.language_config_path(ext)?).ok()?;
+        LanguageConfig::parse(&code).ok()
+    }
+
+    fn next_result_id(&mut self) -> String {
+        self.result_id += 1;
+        self.result_id.to_string()
+    }
+
+    fn reply<P: DeserializeOwned, R: Serialize>(
+        params: Value,
+        handle: impl FnOnce(P) -> R,
+    ) -> Result<Value, String> {
+        let params = serde_json::from_value(params).map_err(|err| err.to_string())?;
+        serde_json::to_value(handle(params)).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::Server;
+    use lsp_server::{Notification, Request, RequestId, Response};
+    use serde_json::{json, Value};
+
+    fn request(server: &mut Server, id: i32, method: &str, params: Value) -> Response {
+        server.handle_request(Request::new(
+            RequestId::from(id),
+            method.to_string(),
+            params,
+        ))
+    }
+
+    #[test]
+    fn should_handle_document_requests() {
+        let mut server = Server::default();
+        let uri = "file:///demo/src/Demo.java";
+        let code = "public class Demo {\n    public void run() {\n        int a = 1;\n    }\n}\n";
+        server.handle_notification(Notification::new(
+            "textDocument/didOpen".to_string(),
+            json!({ "textDocument": { "uri": uri, "languageId": "java", "version": 1, "text": code } }),
+        ));
+        let document = json!({ "textDocument": { "uri": uri } });
+
+        let full = request(
+            &mut server,
+            1,
+            "textDocument/semanticTokens/full",
+            document.clone(),
+        );
+        let full = full.result.unwrap();
+        assert_eq!("1", full["resultId"]);
+        assert!(!full["data"].as_array().unwrap().is_empty());
+
+        server.handle_notification(Notification::new(
+            "textDocument/didChange".to_string(),
+            json!({
+                "textDocument": { "uri": uri, "version": 2 },
+                "contentChanges": [{
+                    "range": { "start": { "line": 2, "character": 16 }, "end": { "line": 2, "character": 17 } },
+                    "text": "42"
+                }]
+            }),
+        ));
+        let mut params = document.clone();
+        params["previousResultId"] = json!("1");
+        let delta = request(
+            &mut server,
+            2,
+            "textDocument/semanticTokens/full/delta",
+            params,
+        );
+        let edits = delta.result.unwrap()["edits"].clone();
+        assert_eq!(1, edits.as_array().unwrap().len());
+        assert_eq!(5, edits[0]["deleteCount"]);
+
+        let symbols = request(
+            &mut server,
+            3,
+            "textDocument/documentSymbol",
+            document.clone(),
+        );
+        let symbols = symbols.result.unwrap();
+        assert_eq!("Demo", symbols[0]["name"]);
+        assert_eq!("run", symbols[0]["children"][0]["name"]);
+
+        let folding = request(
+            &mut server,
+            4,
+            "textDocument/foldingRange",
+            document.clone(),
+        );
+        let folding = folding.result.unwrap();
+        assert_eq!(
+            json!([[0, 3], [1, 2]]),
+            json!(folding
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|range| json!([range["startLine"], range["endLine"]]))
+                .collect::<Vec<Value>>())
+        );
+
+        let unknown = request(&mut server, 5, "textDocument/hover", document);
+        assert!(unknown.error.is_some());
+    }
+}
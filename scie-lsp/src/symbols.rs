@@ -0,0 +1,197 @@
+use crate::document::Document;
+use lsp_types::{DocumentSymbol, Position, Range, SymbolKind};
+use scie_core::analyser::ElementExtractor;
+use scie_model::artifact::{CodeElement, CodeElementKind};
+
+/// the outline of document by the code elements, the elements are nested by the lines
+pub struct DocumentSymbols {}
+
+impl DocumentSymbols {
+    pub fn from_document(document: &Document) -> Vec<DocumentSymbol> {
+        let elements = ElementExtractor::extract(&document.elements());
+        let elements: Vec<&CodeElement> = elements
+            .iter()
+            .filter(|element| DocumentSymbols::kind(element.kind).is_some())
+            .collect();
+
+        DocumentSymbols::nest(document, &elements)
+    }
+
+    pub fn kind(kind: CodeElementKind) -> Option<SymbolKind> {
+        match kind {
+            CodeElementKind::Package => Some(SymbolKind::PACKAGE),
+            CodeElementKind::Import => None,
+            CodeElementKind::Module => Some(SymbolKind::MODULE),
+            CodeElementKind::Class => Some(SymbolKind::CLASS),
+            CodeElementKind::Interface => Some(SymbolKind::INTERFACE),
+            CodeElementKind::Enum => Some(SymbolKind::ENUM),
+            CodeElementKind::Struct => Some(SymbolKind::STRUCT),
+            CodeElementKind::Trait => Some(SymbolKind::INTERFACE),
+            CodeElementKind::Impl => Some(SymbolKind::OBJECT),
+            CodeElementKind::Type => Some(SymbolKind::TYPE_PARAMETER),
+            CodeElementKind::Function => Some(SymbolKind::FUNCTION),
+            CodeElementKind::Method => Some(SymbolKind::METHOD),
+            CodeElementKind::Field => Some(SymbolKind::FIELD),
+        }
+    }
+
+    /// the elements are ordered by the start lines, the children are the next elements in the
+    /// lines of the parent
+    fn nest(document: &Document, elements: &[&CodeElement]) -> Vec<DocumentSymbol> {
+        let mut symbols = vec![];
+        let mut index = 0;
+        while index < elements.len() {
+            let parent = elements[index];
+            let is_container = parent.kind.is_type()
+                || matches!(
+                    parent.kind,
+                    CodeElementKind::Module | CodeElementKind::Function | CodeElementKind::Method
+                );
+
+            let mut end = index + 1;
+            while is_container
+                && end < elements.len()
+                && parent.range.contains(elements[end].range.start)
+                && elements[end].range.end <= parent.range.end
+            {
+                end += 1;
+            }
+
+            let children = DocumentSymbols::nest(document, &elements[index + 1..end]);
+            symbols.push(DocumentSymbols::symbol(document, parent, children));
+            index = end;
+        }
+
+        symbols
+    }
+
+    #[allow(deprecated)]
+    fn symbol(
+        document: &Document,
+        element: &CodeElement,
+        children: Vec<DocumentSymbol>,
+    ) -> DocumentSymbol {
+        let lines = document.lines();
+        let start_line = (element.range.start - 1).max(0) as u32;
+        let end_line = (element.range.end - 1).max(element.range.start - 1).max(0) as u32;
+        let utf16_len = |line: u32, chars: usize| -> u32 {
+            lines
+                .get(line as usize)
+                .map(|it| {
+                    it.text
+                        .chars()
+                        .take(chars)
+                        .map(char::len_utf16)
+                        .sum::<usize>() as u32
+                })
+                .unwrap_or(0)
+        };
+
+        let start_text = lines
+            .get(start_line as usize)
+            .map(|it| it.text.as_str())
+            .unwrap_or("");
+        let indent = start_text
+            .chars()
+            .take_while(|it| it.is_whitespace())
+            .count();
+        let range = Range::new(
+            Position::new(start_line, utf16_len(start_line, indent)),
+            Position::new(end_line, utf16_len(end_line, usize::MAX)),
+        );
+
+        let selection_range = lines
+            .get(start_line as usize)
+            .and_then(|line| {
+                line.tokens
+                    .iter()
+                    .find(|token| token.value.trim() == element.name)
+            })
+            .map(|token| {
+                // the indexes of tokens are already UTF-16 code units
+                let leading: usize = token
+                    .value
+                    .chars()
+                    .take_while(|it| it.is_whitespace())
+                    .map(char::len_utf16)
+                    .sum();
+                let start = token.start_index + leading as i32;
+                let end = start + element.name.encode_utf16().count() as i32;
+                Range::new(
+                    Position::new(start_line, start as u32),
+                    Position::new(start_line, end as u32),
+                )
+            })
+            .unwrap_or(range);
+
+        DocumentSymbol {
+            name: element.name.clone(),
+            detail: if element.modifiers.is_empty() {
+                None
+            } else {
+                Some(element.modifiers.join(" "))
+            },
+            kind: DocumentSymbols::kind(element.kind).unwrap_or(SymbolKind::NULL),
+            tags: None,
+            deprecated: None,
+            range,
+            selection_range,
+            children: if children.is_empty() {
+                None
+            } else {
+                Some(children)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::document::tests::java_tokenizer;
+    use crate::document::Document;
+    use crate::symbols::DocumentSymbols;
+    use lsp_types::{Position, Range, SymbolKind};
+
+    #[test]
+    fn should_nest_java_symbols() {
+        let code = r#"package com.phodal.demo;
+
+import java.util.List;
+
+public class Demo {
+    private int count;
+
+    public void run(List<String> names) {
+    }
+}"#;
+        let mut document = Document::new(code, 1);
+        document.tokenize(&mut java_tokenizer());
+
+        let symbols = DocumentSymbols::from_document(&document);
+        assert_eq!(2, symbols.len());
+        assert_eq!(SymbolKind::PACKAGE, symbols[0].kind);
+
+        let class = &symbols[1];
+        assert_eq!("Demo", class.name);
+        assert_eq!(SymbolKind::CLASS, class.kind);
+        assert_eq!(
+            Range::new(Position::new(4, 0), Position::new(9, 1)),
+            class.range
+        );
+        assert_eq!(
+            Range::new(Position::new(4, 13), Position::new(4, 17)),
+            class.selection_range
+        );
+
+        let children = class.children.as_ref().unwrap();
+        let names: Vec<(&str, SymbolKind)> = children
+            .iter()
+            .map(|it| (it.name.as_str(), it.kind))
+            .collect();
+        assert_eq!(
+            vec![("count", SymbolKind::FIELD), ("run", SymbolKind::METHOD)],
+            names
+        );
+        assert_eq!(Position::new(7, 4), children[1].range.start);
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct TokenElement {
     #[serde(alias = "line")]
     pub line_num: i32,
@@ -28,6 +28,93 @@ impl TokenElement {
             scopes,
         }
     }
+
+    /// the text between the indexes of a token, which are the UTF-16 code units of the line, like
+    /// `line.encode_utf16()`
+    pub fn utf16_value(units: &[u16], start_index: i32, end_index: i32) -> String {
+        let end = (end_index.max(0) as usize).min(units.len());
+        let start = (start_index.max(0) as usize).min(end);
+        String::from_utf16_lossy(&units[start..end])
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct LineRange {
+    pub start: i32,
+    pub end: i32,
+}
+
+impl LineRange {
+    pub fn new(start: i32, end: i32) -> LineRange {
+        LineRange { start, end }
+    }
+
+    pub fn contains(&self, line_num: i32) -> bool {
+        line_num >= self.start && line_num <= self.end
+    }
+
+    pub fn len(&self) -> usize {
+        (self.end - self.start + 1).max(0) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum CodeElementKind {
+    Package,
+    Import,
+    Module,
+    Class,
+    Interface,
+    Enum,
+    Struct,
+    Trait,
+    Impl,
+    Type,
+    Function,
+    Method,
+    Field,
+}
+
+impl CodeElementKind {
+    /// the kinds which can own methods and fields
+    pub fn is_type(&self) -> bool {
+        matches!(
+            self,
+            CodeElementKind::Class
+                | CodeElementKind::Interface
+                | CodeElementKind::Enum
+                | CodeElementKind::Struct
+                | CodeElementKind::Trait
+                | CodeElementKind::Impl
+        )
+    }
+}
+
+/// the structure of code, like classes and functions, which extracted from the scopes of tokens
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct CodeElement {
+    pub kind: CodeElementKind,
+    pub name: String,
+    pub range: LineRange,
+    pub modifiers: Vec<String>,
+    // the name of enclosing element, like the class of method
+    pub parent: Option<String>,
+}
+
+impl CodeElement {
+    pub fn new(kind: CodeElementKind, name: &str, line_num: i32) -> CodeElement {
+        CodeElement {
+            kind,
+            name: name.to_string(),
+            range: LineRange::new(line_num, line_num),
+            modifiers: vec![],
+            parent: None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -45,4 +132,12 @@ mod tests {
         let element = TokenElement::new(1, 1, 2, "#".to_string(), vec![]);
         assert_eq!("1:1-2", element.pos);
     }
+
+    #[test]
+    fn should_cut_value_by_utf16() {
+        let units: Vec<u16> = "s = \"🌏\";".encode_utf16().collect();
+        assert_eq!("🌏", TokenElement::utf16_value(&units, 5, 7));
+        assert_eq!("\";", TokenElement::utf16_value(&units, 7, 9));
+        assert_eq!("", TokenElement::utf16_value(&units, 9, 20));
+    }
 }
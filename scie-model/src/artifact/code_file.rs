@@ -1,11 +1,30 @@
-use crate::artifact::TokenElement;
+use crate::artifact::{CodeElement, LineRange, TokenElement};
 use std::path::PathBuf;
 
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum CodeFileKind {
+    #[default]
+    Unknown,
+    Source,
+    Test,
+    Generated,
+    Resource,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CodeFile {
     pub path: String,
     pub name: String,
+    #[serde(default)]
+    pub kind: CodeFileKind,
     pub elements: Vec<TokenElement>,
+    // inline tests in production files, like `#[cfg(test)]` modules in Rust
+    #[serde(default)]
+    pub test_ranges: Vec<LineRange>,
+    #[serde(default)]
+    pub code_elements: Vec<CodeElement>,
+    #[serde(default)]
+    pub line_breaks: LineBreaks,
 }
 
 impl CodeFile {
@@ -14,7 +33,83 @@ impl CodeFile {
         CodeFile {
             path: path.display().to_string(),
             name: String::from(name),
+            kind: CodeFileKind::Unknown,
             elements: vec![],
+            test_ranges: vec![],
+            code_elements: vec![],
+            line_breaks: LineBreaks::default(),
         }
     }
 }
+
+/// the line breaks which are dropped by tokenizing line by line, to rebuild the code byte by byte
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct LineBreaks {
+    pub lines: i32,
+    // the lines which end with `\r\n`, the others end with `\n`
+    pub crlf: Vec<LineRange>,
+    pub final_newline: bool,
+}
+
+impl LineBreaks {
+    pub fn from_code(code: &str) -> LineBreaks {
+        let mut breaks = LineBreaks {
+            lines: code.lines().count() as i32,
+            crlf: vec![],
+            final_newline: code.ends_with('\n'),
+        };
+
+        for (index, line) in code.split_inclusive('\n').enumerate() {
+            if !line.ends_with("\r\n") {
+                continue;
+            }
+            let line_num = index as i32 + 1;
+            match breaks.crlf.last_mut() {
+                Some(range) if range.end + 1 == line_num => range.end = line_num,
+                _ => breaks.crlf.push(LineRange::new(line_num, line_num)),
+            }
+        }
+
+        breaks
+    }
+
+    /// the line break after the line, empty for the last line without final newline
+    pub fn line_break(&self, line_num: i32) -> &'static str {
+        if line_num == self.lines && !self.final_newline {
+            ""
+        } else if self.crlf.iter().any(|range| range.contains(line_num)) {
+            "\r\n"
+        } else {
+            "\n"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::artifact::code_file::LineBreaks;
+    use crate::artifact::LineRange;
+
+    #[test]
+    fn should_merge_crlf_lines_to_ranges() {
+        let breaks = LineBreaks::from_code("a\r\nb\r\nc\nd\r\n");
+
+        assert_eq!(4, breaks.lines);
+        assert_eq!(
+            vec![LineRange::new(1, 2), LineRange::new(4, 4)],
+            breaks.crlf
+        );
+        assert_eq!("\r\n", breaks.line_break(2));
+        assert_eq!("\n", breaks.line_break(3));
+        assert_eq!("\r\n", breaks.line_break(4));
+    }
+
+    #[test]
+    fn should_keep_missing_final_newline() {
+        let breaks = LineBreaks::from_code("a\nb");
+
+        assert_eq!(2, breaks.lines);
+        assert!(!breaks.final_newline);
+        assert_eq!("", breaks.line_break(2));
+    }
+}
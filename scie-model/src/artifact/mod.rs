@@ -3,8 +3,8 @@ use serde::{Deserialize, Serialize};
 pub mod code_element;
 pub mod code_file;
 
-pub use code_element::TokenElement;
-pub use code_file::CodeFile;
+pub use code_element::{CodeElement, CodeElementKind, LineRange, TokenElement};
+pub use code_file::{CodeFile, CodeFileKind, LineBreaks};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Workspace {
@@ -16,6 +16,30 @@ pub struct IndentationRules {
     pub decrease_indent_pattern: Option<String>,
 }
 
+/// both `["{", "}"]` and `{ "open": "'", "close": "'", "notIn": ["string"] }` are allowed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum AutoClosingPair {
+    Pair(CharacterPair),
+    Rule {
+        open: String,
+        close: String,
+        #[serde(alias = "notIn")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        not_in: Option<Vec<String>>,
+    },
+}
+
+impl AutoClosingPair {
+    pub fn open_close(&self) -> Option<(&str, &str)> {
+        match self {
+            AutoClosingPair::Pair(pair) if pair.len() == 2 => Some((&pair[0], &pair[1])),
+            AutoClosingPair::Pair(_) => None,
+            AutoClosingPair::Rule { open, close, .. } => Some((open, close)),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LanguageConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -30,9 +54,112 @@ pub struct LanguageConfig {
     #[serde(alias = "indentationRules")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub indentation_rules: Option<IndentationRules>,
+
+    #[serde(alias = "autoClosingPairs")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_closing_pairs: Option<Vec<AutoClosingPair>>,
 }
 
-impl LanguageConfig {}
+impl LanguageConfig {
+    /// the configurations are JSON with comments and trailing commas, like `tsconfig.json`
+    pub fn parse(code: &str) -> serde_json::Result<LanguageConfig> {
+        serde_json::from_str(&LanguageConfig::strip_jsonc(code))
+    }
+
+    pub fn line_comment(&self) -> Option<&str> {
+        self.comments
+            .as_ref()
+            .and_then(|comments| comments.line_comment.as_deref())
+    }
+
+    pub fn block_comment(&self) -> Option<(&str, &str)> {
+        self.comments
+            .as_ref()
+            .and_then(|comments| comments.block_comment.as_ref())
+            .filter(|pair| pair.len() == 2)
+            .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+    }
+
+    pub fn bracket_pairs(&self) -> Vec<(&str, &str)> {
+        self.brackets
+            .iter()
+            .flatten()
+            .filter(|pair| pair.len() == 2)
+            .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+            .collect()
+    }
+
+    /// the auto closing pairs which open and close with the same quote, like `"` and `'`
+    pub fn string_quotes(&self) -> Vec<char> {
+        let mut quotes = vec![];
+        for pair in self.auto_closing_pairs.iter().flatten() {
+            if let Some((open, close)) = pair.open_close() {
+                let mut chars = open.chars();
+                if let (Some(quote), None) = (chars.next(), chars.next()) {
+                    if open == close && !quote.is_alphanumeric() && !quotes.contains(&quote) {
+                        quotes.push(quote);
+                    }
+                }
+            }
+        }
+
+        quotes
+    }
+
+    fn strip_jsonc(code: &str) -> String {
+        let mut output = String::with_capacity(code.len());
+        let mut chars = code.chars().peekable();
+        let mut in_string = false;
+        while let Some(char) = chars.next() {
+            if in_string {
+                output.push(char);
+                if char == '\\' {
+                    if let Some(escaped) = chars.next() {
+                        output.push(escaped);
+                    }
+                } else if char == '"' {
+                    in_string = false;
+                }
+                continue;
+            }
+
+            match char {
+                '"' => {
+                    in_string = true;
+                    output.push(char);
+                }
+                '/' if chars.peek() == Some(&'/') => {
+                    while let Some(next) = chars.peek() {
+                        if *next == '\n' {
+                            break;
+                        }
+                        chars.next();
+                    }
+                }
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    let mut last = ' ';
+                    for next in chars.by_ref() {
+                        if last == '*' && next == '/' {
+                            break;
+                        }
+                        last = next;
+                    }
+                }
+                ']' | '}' => {
+                    let trimmed = output.trim_end().len();
+                    if output[..trimmed].ends_with(',') {
+                        output.truncate(trimmed - 1);
+                    }
+                    output.push(char);
+                }
+                _ => output.push(char),
+            }
+        }
+
+        output
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -60,4 +187,26 @@ mod tests {
         assert_eq!("/*", block_comment[0]);
         assert_eq!("*/", block_comment[1]);
     }
+
+    #[test]
+    fn should_parse_config_with_comments_and_trailing_commas() {
+        let code = r#"{
+	// symbols used as brackets
+	"comments": { "lineComment": "//", "blockComment": ["/*", "*/"] },
+	"brackets": [["{", "}"], ["(", ")"],],
+	/* pairs */
+	"autoClosingPairs": [
+		["{", "}"],
+		{ "open": "\"", "close": "\"", "notIn": ["string"] },
+		{ "open": "'", "close": "'", "notIn": ["string", "comment"] },
+		{ "open": "/**", "close": " */", "notIn": ["string"] },
+	],
+}"#;
+        let config = LanguageConfig::parse(code).unwrap();
+
+        assert_eq!(Some("//"), config.line_comment());
+        assert_eq!(Some(("/*", "*/")), config.block_comment());
+        assert_eq!(vec![("{", "}"), ("(", ")")], config.bracket_pairs());
+        assert_eq!(vec!['"', '\''], config.string_quotes());
+    }
 }
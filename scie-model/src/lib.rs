@@ -12,6 +12,8 @@ pub mod tm_grammar;
 
 pub use self::contribute::Contribute;
 pub use self::json_package::JsonPackage;
-pub use self::language_config::{CharacterPair, CommentRule, IndentationRules, LanguageConfig};
+pub use self::language_config::{
+    AutoClosingPair, CharacterPair, CommentRule, IndentationRules, LanguageConfig,
+};
 pub use self::raw_language::RawLanguageExt;
 pub use self::tm_grammar::TMGrammar;